@@ -0,0 +1,212 @@
+use super::{EvmPrecompileResult, Precompile};
+use crate::prelude::types::{Address, EthGas};
+use crate::prelude::{
+    sdk::{
+        self,
+        io::{StorageIntermediate, IO},
+    },
+    storage::{bytes_to_key, KeyPrefix},
+    vec, Borrowed, String, ToString, Vec,
+};
+use crate::{utils, PrecompileOutput};
+use aurora_engine_types::account_id::AccountId;
+use aurora_engine_types::parameters::Erc20Metadata;
+use evm::{Context, ExitError};
+
+/// address_resolver precompile address
+///
+/// Address: `0x59b636f08b024906609540a8111b5eb33688eb80`
+/// This address is computed as: `&keccak("addressResolver")[12..]`
+pub const ADDRESS: Address = crate::make_address(0x59b636f0, 0x8b024906609540a8111b5eb33688eb80);
+
+mod costs {
+    use crate::prelude::types::EthGas;
+
+    /// Deriving an address hashes the account id, so it is priced like `KECCAK256`.
+    pub(super) const DERIVE_ADDRESS_BASE: EthGas = EthGas::new(30);
+    pub(super) const DERIVE_ADDRESS_WORD: EthGas = EthGas::new(6);
+    /// Looking up the bridged token map reads the engine storage, so it is priced like a
+    /// cold `SLOAD`.
+    pub(super) const TOKEN_LOOKUP: EthGas = EthGas::new(2_100);
+}
+
+/// Selectors of the functions of the precompile.
+pub mod selectors {
+    /// `nearAccountToEvmAddress(string) returns (address)`
+    pub const NEAR_ACCOUNT_TO_EVM_ADDRESS: [u8; 4] = [0xd9, 0x40, 0xdd, 0xdf];
+    /// `erc20ToNep141(address) returns (string)`
+    /// Returns an empty string if the ERC-20 token is not bridged.
+    pub const ERC20_TO_NEP141: [u8; 4] = [0xf3, 0x3f, 0xb9, 0xee];
+    /// `nep141ToErc20(string) returns (address)`
+    /// Returns the zero address if the NEP-141 token is not bridged.
+    pub const NEP141_TO_ERC20: [u8; 4] = [0x7f, 0xed, 0x56, 0x2a];
+    /// `erc20Metadata(address) returns (string name, string symbol, uint8 decimals, string icon)`
+    /// Returns the metadata of the NEP-141 token of a bridged ERC-20 token, or empty values if
+    /// the token is not bridged or its metadata is not known.
+    pub const ERC20_METADATA: [u8; 4] = [0x0c, 0xd1, 0xb8, 0xb4];
+}
+
+mod consts {
+    pub(super) const ERR_INVALID_SELECTOR: &str = "ERR_ADDRESS_RESOLVER_INVALID_SELECTOR";
+    pub(super) const ERR_INVALID_INPUT: &str = "ERR_ADDRESS_RESOLVER_INVALID_INPUT";
+    pub(super) const ERR_INVALID_ACCOUNT_ID: &str = "ERR_ADDRESS_RESOLVER_INVALID_ACCOUNT_ID";
+}
+
+/// Maps NEAR account ids to the EVM addresses derived from them, and bridged NEP-141
+/// tokens to their ERC-20 counterparts and back. Also returns the metadata of bridged tokens,
+/// which the engine caches when their ERC-20 token is deployed.
+pub struct AddressResolver<I> {
+    io: I,
+}
+
+impl<I> AddressResolver<I> {
+    pub fn new(io: I) -> Self {
+        Self { io }
+    }
+}
+
+fn decode_input(param: ethabi::ParamType, input: &[u8]) -> Result<ethabi::Token, ExitError> {
+    ethabi::decode(&[param], input)
+        .ok()
+        .and_then(|mut tokens| tokens.pop())
+        .ok_or(ExitError::Other(Borrowed(consts::ERR_INVALID_INPUT)))
+}
+
+fn decode_string(input: &[u8]) -> Result<String, ExitError> {
+    match decode_input(ethabi::ParamType::String, input)? {
+        ethabi::Token::String(s) => Ok(s),
+        _ => Err(ExitError::Other(Borrowed(consts::ERR_INVALID_INPUT))),
+    }
+}
+
+fn decode_address(input: &[u8]) -> Result<Address, ExitError> {
+    match decode_input(ethabi::ParamType::Address, input)? {
+        ethabi::Token::Address(a) => Ok(Address::new(a)),
+        _ => Err(ExitError::Other(Borrowed(consts::ERR_INVALID_INPUT))),
+    }
+}
+
+impl<I: IO> AddressResolver<I> {
+    fn erc20_to_nep141(&self, erc20_token: &Address) -> Option<AccountId> {
+        let key = bytes_to_key(KeyPrefix::Erc20Nep141Map, erc20_token.as_bytes());
+        let value = self.io.read_storage(&key)?.to_vec();
+        AccountId::try_from(value).ok()
+    }
+
+    fn nep141_to_erc20(&self, nep141_token: &str) -> Option<Address> {
+        let key = bytes_to_key(KeyPrefix::Nep141Erc20Map, nep141_token.as_bytes());
+        let value = self.io.read_storage(&key)?.to_vec();
+        Address::try_from_slice(&value).ok()
+    }
+
+    fn erc20_metadata(&self, erc20_token: &Address) -> Option<Erc20Metadata> {
+        let key = bytes_to_key(KeyPrefix::Erc20Metadata, erc20_token.as_bytes());
+        self.io.read_storage(&key)?.to_value().ok()
+    }
+}
+
+impl<I: IO> Precompile for AddressResolver<I> {
+    fn required_gas(input: &[u8]) -> Result<EthGas, ExitError> {
+        match input.get(0..4) {
+            Some(selector) if selector == selectors::NEAR_ACCOUNT_TO_EVM_ADDRESS => {
+                let words =
+                    u64::try_from((input.len() - 4 + 31) / 32).map_err(utils::err_usize_conv)?;
+                Ok(costs::DERIVE_ADDRESS_BASE + costs::DERIVE_ADDRESS_WORD * words)
+            }
+            _ => Ok(costs::TOKEN_LOOKUP),
+        }
+    }
+
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        if input.len() < 4 {
+            return Err(ExitError::Other(Borrowed(consts::ERR_INVALID_SELECTOR)));
+        }
+        let (selector, args) = input.split_at(4);
+        let tokens = if selector == selectors::NEAR_ACCOUNT_TO_EVM_ADDRESS {
+            let account_id = AccountId::try_from(decode_string(args)?)
+                .map_err(|_| ExitError::Other(Borrowed(consts::ERR_INVALID_ACCOUNT_ID)))?;
+            let address = sdk::types::near_account_to_evm_address(account_id.as_bytes());
+            vec![ethabi::Token::Address(address.raw())]
+        } else if selector == selectors::ERC20_TO_NEP141 {
+            let nep141_token = self
+                .erc20_to_nep141(&decode_address(args)?)
+                .map(|account_id| account_id.to_string())
+                .unwrap_or_default();
+            vec![ethabi::Token::String(nep141_token)]
+        } else if selector == selectors::NEP141_TO_ERC20 {
+            let erc20_token = self
+                .nep141_to_erc20(&decode_string(args)?)
+                .unwrap_or_else(Address::zero);
+            vec![ethabi::Token::Address(erc20_token.raw())]
+        } else if selector == selectors::ERC20_METADATA {
+            let metadata = self.erc20_metadata(&decode_address(args)?);
+            let (name, symbol, decimals, icon) = metadata.map_or_else(
+                || (String::new(), String::new(), 0, String::new()),
+                |m| (m.name, m.symbol, m.decimals, m.icon.unwrap_or_default()),
+            );
+            vec![
+                ethabi::Token::String(name),
+                ethabi::Token::String(symbol),
+                ethabi::Token::Uint(decimals.into()),
+                ethabi::Token::String(icon),
+            ]
+        } else {
+            return Err(ExitError::Other(Borrowed(consts::ERR_INVALID_SELECTOR)));
+        };
+
+        let output: Vec<u8> = ethabi::encode(&tokens);
+        Ok(PrecompileOutput::without_logs(cost, output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::sdk::types::near_account_to_evm_address;
+
+    #[test]
+    fn test_address_resolver_precompile_id() {
+        assert_eq!(
+            ADDRESS,
+            near_account_to_evm_address("addressResolver".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_selectors() {
+        let selector = |signature: &str| -> [u8; 4] {
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(&aurora_engine_sdk::keccak(signature.as_bytes())[0..4]);
+            buf
+        };
+        assert_eq!(
+            selectors::NEAR_ACCOUNT_TO_EVM_ADDRESS,
+            selector("nearAccountToEvmAddress(string)")
+        );
+        assert_eq!(
+            selectors::ERC20_TO_NEP141,
+            selector("erc20ToNep141(address)")
+        );
+        assert_eq!(
+            selectors::NEP141_TO_ERC20,
+            selector("nep141ToErc20(string)")
+        );
+        assert_eq!(
+            selectors::ERC20_METADATA,
+            selector("erc20Metadata(address)")
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use super::{EvmPrecompileResult, Precompile};
+use crate::prelude::types::{Address, EthGas};
+use crate::prelude::{Borrowed, ToString, U256};
+use crate::PrecompileOutput;
+use aurora_engine_sdk::env::Env;
+use evm::{Context, ExitError};
+
+/// near_block_context precompile address
+///
+/// Address: `0x15e0692e538d6939add325090de2ad1a04d0a8c3`
+/// This address is computed as: `&keccak("nearBlockContext")[12..]`
+pub const ADDRESS: Address = crate::make_address(0x15e0692e, 0x538d6939add325090de2ad1a04d0a8c3);
+
+mod costs {
+    use crate::prelude::types::EthGas;
+
+    // TODO(#483): Determine the correct amount of gas
+    pub(super) const BLOCK_CONTEXT_GAS: EthGas = EthGas::new(0);
+}
+
+/// Selectors of the functions of the precompile, which take no arguments.
+pub mod selectors {
+    /// `blockTimestamp() returns (uint64)`, in nanoseconds.
+    pub const BLOCK_TIMESTAMP: [u8; 4] = [0xad, 0xb6, 0x18, 0x32];
+    /// `blockHeight() returns (uint64)`
+    pub const BLOCK_HEIGHT: [u8; 4] = [0xf4, 0x4f, 0xf7, 0x12];
+    /// `epochHeight() returns (uint64)`
+    pub const EPOCH_HEIGHT: [u8; 4] = [0x0c, 0xaa, 0x4d, 0x39];
+    /// `signerAccountId() returns (string)`
+    pub const SIGNER_ACCOUNT_ID: [u8; 4] = [0xce, 0x8c, 0xa2, 0x99];
+    /// `attachedDeposit() returns (uint128)`, in yoctoNEAR.
+    pub const ATTACHED_DEPOSIT: [u8; 4] = [0x0a, 0xee, 0xfa, 0x47];
+}
+
+mod consts {
+    pub(super) const ERR_INVALID_SELECTOR: &str = "ERR_BLOCK_CONTEXT_INVALID_SELECTOR";
+}
+
+/// Exposes the fields of the NEAR block and receipt the transaction executes in.
+pub struct BlockContext<'a, E> {
+    env: &'a E,
+}
+
+impl<'a, E> BlockContext<'a, E> {
+    pub fn new(env: &'a E) -> Self {
+        Self { env }
+    }
+}
+
+impl<'a, E: Env> Precompile for BlockContext<'a, E> {
+    fn required_gas(_input: &[u8]) -> Result<EthGas, ExitError> {
+        Ok(costs::BLOCK_CONTEXT_GAS)
+    }
+
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        let selector: [u8; 4] = input
+            .try_into()
+            .map_err(|_| ExitError::Other(Borrowed(consts::ERR_INVALID_SELECTOR)))?;
+        let token = match selector {
+            selectors::BLOCK_TIMESTAMP => {
+                ethabi::Token::Uint(self.env.block_timestamp().nanos().into())
+            }
+            selectors::BLOCK_HEIGHT => ethabi::Token::Uint(self.env.block_height().into()),
+            selectors::EPOCH_HEIGHT => ethabi::Token::Uint(self.env.epoch_height().into()),
+            selectors::SIGNER_ACCOUNT_ID => {
+                ethabi::Token::String(self.env.signer_account_id().to_string())
+            }
+            selectors::ATTACHED_DEPOSIT => {
+                ethabi::Token::Uint(U256::from(self.env.attached_deposit()))
+            }
+            _ => return Err(ExitError::Other(Borrowed(consts::ERR_INVALID_SELECTOR))),
+        };
+
+        Ok(PrecompileOutput::without_logs(
+            cost,
+            ethabi::encode(&[token]),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::sdk::types::near_account_to_evm_address;
+    use crate::prelude::{String, Vec, H256};
+    use crate::utils::new_context;
+    use aurora_engine_sdk::env::{Fixed, Timestamp};
+    use aurora_engine_types::types::NearGas;
+
+    fn run(env: &Fixed, input: &[u8]) -> Result<Vec<u8>, ExitError> {
+        BlockContext::new(env)
+            .run(input, None, &new_context(), false)
+            .map(|output| output.output)
+    }
+
+    #[test]
+    fn test_block_context_precompile_id() {
+        assert_eq!(
+            ADDRESS,
+            near_account_to_evm_address("nearBlockContext".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_selectors() {
+        let selector = |signature: &str| -> [u8; 4] {
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(&aurora_engine_sdk::keccak(signature.as_bytes())[0..4]);
+            buf
+        };
+        assert_eq!(selectors::BLOCK_TIMESTAMP, selector("blockTimestamp()"));
+        assert_eq!(selectors::BLOCK_HEIGHT, selector("blockHeight()"));
+        assert_eq!(selectors::EPOCH_HEIGHT, selector("epochHeight()"));
+        assert_eq!(selectors::SIGNER_ACCOUNT_ID, selector("signerAccountId()"));
+        assert_eq!(selectors::ATTACHED_DEPOSIT, selector("attachedDeposit()"));
+    }
+
+    #[test]
+    fn test_block_context() {
+        let env = Fixed {
+            signer_account_id: "alice.near".parse().unwrap(),
+            current_account_id: "aurora".parse().unwrap(),
+            predecessor_account_id: "bob.near".parse().unwrap(),
+            block_height: 81_000_000,
+            block_timestamp: Timestamp::new(1_670_000_000_123_456_789),
+            epoch_height: 1_500,
+            attached_deposit: 10u128.pow(24),
+            random_seed: H256::zero(),
+            prepaid_gas: NearGas::new(300_000_000_000_000),
+        };
+        let uint = |x: U256| ethabi::encode(&[ethabi::Token::Uint(x)]);
+
+        assert_eq!(
+            run(&env, &selectors::BLOCK_TIMESTAMP).unwrap(),
+            uint(1_670_000_000_123_456_789u64.into())
+        );
+        assert_eq!(
+            run(&env, &selectors::BLOCK_HEIGHT).unwrap(),
+            uint(81_000_000u64.into())
+        );
+        assert_eq!(
+            run(&env, &selectors::EPOCH_HEIGHT).unwrap(),
+            uint(1_500u64.into())
+        );
+        assert_eq!(
+            run(&env, &selectors::ATTACHED_DEPOSIT).unwrap(),
+            uint(U256::from(10u128.pow(24)))
+        );
+
+        let output = run(&env, &selectors::SIGNER_ACCOUNT_ID).unwrap();
+        let decoded = ethabi::decode(&[ethabi::ParamType::String], &output).unwrap();
+        assert_eq!(
+            decoded,
+            vec![ethabi::Token::String(String::from("alice.near"))]
+        );
+
+        assert!(run(&env, &[0u8; 4]).is_err());
+        assert!(run(&env, &selectors::BLOCK_HEIGHT[0..3]).is_err());
+    }
+}
@@ -0,0 +1,706 @@
+use crate::prelude::types::{Address, EthGas};
+use crate::prelude::Vec;
+use crate::{utils, EvmPrecompileResult, Precompile, PrecompileOutput};
+use bls12_381::hash_to_curve::{HashToField, MapToCurve};
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar};
+use evm::{Context, ExitError};
+
+/// BLS12-381 costs, see the gas schedule of EIP-2537.
+mod costs {
+    use crate::prelude::types::EthGas;
+
+    /// Cost of the G1 addition operation.
+    pub(super) const G1_ADD: EthGas = EthGas::new(375);
+
+    /// Cost of a single G1 multiplication, before the multi-scalar-multiplication discount.
+    pub(super) const G1_MUL: EthGas = EthGas::new(12_000);
+
+    /// Cost of the G2 addition operation.
+    pub(super) const G2_ADD: EthGas = EthGas::new(600);
+
+    /// Cost of a single G2 multiplication, before the multi-scalar-multiplication discount.
+    pub(super) const G2_MUL: EthGas = EthGas::new(22_500);
+
+    /// Cost of the pairing check per pair.
+    pub(super) const PAIRING_PER_PAIR: EthGas = EthGas::new(32_600);
+
+    /// Cost of the pairing check operation.
+    pub(super) const PAIRING_BASE: EthGas = EthGas::new(37_700);
+
+    /// Cost of mapping a field element to G1.
+    pub(super) const MAP_FP_TO_G1: EthGas = EthGas::new(5_500);
+
+    /// Cost of mapping a quadratic extension field element to G2.
+    pub(super) const MAP_FP2_TO_G2: EthGas = EthGas::new(23_800);
+
+    /// The discounts are given in thousandths.
+    pub(super) const MSM_MULTIPLIER: u64 = 1_000;
+
+    /// Discount for a G1 multi-scalar-multiplication of `k` pairs is at index `k - 1`.
+    /// Larger multiplications get the last discount.
+    pub(super) const G1_MSM_DISCOUNTS: [u16; 128] = [
+        1000, 949, 848, 797, 764, 750, 738, 728, 719, 712, 705, 698, 692, 687, 682, 677, 673, 669,
+        665, 661, 658, 654, 651, 648, 645, 642, 640, 637, 635, 632, 630, 627, 625, 623, 621, 619,
+        617, 615, 613, 611, 609, 608, 606, 604, 603, 601, 599, 598, 596, 595, 593, 592, 591, 589,
+        588, 586, 585, 584, 582, 581, 580, 579, 577, 576, 575, 574, 573, 572, 570, 569, 568, 567,
+        566, 565, 564, 563, 562, 561, 560, 559, 558, 557, 556, 555, 554, 553, 552, 551, 550, 549,
+        548, 547, 547, 546, 545, 544, 543, 542, 541, 540, 540, 539, 538, 537, 536, 536, 535, 534,
+        533, 532, 532, 531, 530, 529, 528, 528, 527, 526, 525, 525, 524, 523, 522, 522, 521, 520,
+        520, 519,
+    ];
+
+    /// Discount for a G2 multi-scalar-multiplication of `k` pairs is at index `k - 1`.
+    /// Larger multiplications get the last discount.
+    pub(super) const G2_MSM_DISCOUNTS: [u16; 128] = [
+        1000, 1000, 923, 884, 855, 832, 812, 796, 782, 770, 759, 749, 740, 732, 724, 717, 711, 704,
+        699, 693, 688, 683, 679, 674, 670, 666, 663, 659, 655, 652, 649, 646, 643, 640, 637, 634,
+        632, 629, 627, 624, 622, 620, 618, 615, 613, 611, 609, 607, 606, 604, 602, 600, 598, 597,
+        595, 593, 592, 590, 589, 587, 586, 584, 583, 582, 580, 579, 578, 576, 575, 574, 573, 571,
+        570, 569, 568, 567, 566, 565, 563, 562, 561, 560, 559, 558, 557, 556, 555, 554, 553, 552,
+        552, 551, 550, 549, 548, 547, 546, 545, 545, 544, 543, 542, 541, 541, 540, 539, 538, 537,
+        537, 536, 535, 535, 534, 533, 532, 532, 531, 530, 530, 529, 528, 528, 527, 526, 526, 525,
+        524, 524,
+    ];
+}
+
+/// BLS12-381 constants.
+mod consts {
+    use crate::prelude::Borrowed;
+    use evm::ExitError;
+
+    /// Length of an encoded base field element; the value is left padded to 64 bytes.
+    pub(super) const FP_LEN: usize = 64;
+
+    /// Length of the zero padding of an encoded base field element.
+    pub(super) const FP_PADDING_LEN: usize = 16;
+
+    /// Length of a base field element without padding.
+    pub(super) const FP_VALUE_LEN: usize = FP_LEN - FP_PADDING_LEN;
+
+    /// Length of an encoded element of the quadratic extension field.
+    pub(super) const FP2_LEN: usize = 2 * FP_LEN;
+
+    /// Length of an encoded G1 point.
+    pub(super) const G1_LEN: usize = 2 * FP_LEN;
+
+    /// Length of an encoded G2 point.
+    pub(super) const G2_LEN: usize = 2 * FP2_LEN;
+
+    /// Length of an encoded scalar.
+    pub(super) const SCALAR_LEN: usize = 32;
+
+    /// Length of a G1 point and scalar pair in a multi-scalar-multiplication input.
+    pub(super) const G1_MSM_PAIR_LEN: usize = G1_LEN + SCALAR_LEN;
+
+    /// Length of a G2 point and scalar pair in a multi-scalar-multiplication input.
+    pub(super) const G2_MSM_PAIR_LEN: usize = G2_LEN + SCALAR_LEN;
+
+    /// Length of a G1 and G2 point pair in a pairing check input.
+    pub(super) const PAIRING_PAIR_LEN: usize = G1_LEN + G2_LEN;
+
+    /// Output length of the pairing check.
+    pub(super) const PAIRING_OUTPUT_LEN: usize = 32;
+
+    /// Big-endian modulus of the base field.
+    pub(super) const MODULUS: [u8; FP_VALUE_LEN] = [
+        0x1a, 0x01, 0x11, 0xea, 0x39, 0x7f, 0xe6, 0x9a, 0x4b, 0x1b, 0xa7, 0xb6, 0x43, 0x4b, 0xac,
+        0xd7, 0x64, 0x77, 0x4b, 0x84, 0xf3, 0x85, 0x12, 0xbf, 0x67, 0x30, 0xd2, 0xa0, 0xf6, 0xb0,
+        0xf6, 0x24, 0x1e, 0xab, 0xff, 0xfe, 0xb1, 0x53, 0xff, 0xff, 0xb9, 0xfe, 0xff, 0xff, 0xff,
+        0xff, 0xaa, 0xab,
+    ];
+
+    pub(super) const ERR_INPUT_LEN: ExitError = ExitError::Other(Borrowed("ERR_BLS12_INPUT_LEN"));
+    pub(super) const ERR_FP_PADDING: ExitError = ExitError::Other(Borrowed("ERR_BLS12_FP_PADDING"));
+    pub(super) const ERR_FP_NOT_IN_FIELD: ExitError =
+        ExitError::Other(Borrowed("ERR_BLS12_FP_NOT_IN_FIELD"));
+    pub(super) const ERR_POINT_NOT_ON_CURVE: ExitError =
+        ExitError::Other(Borrowed("ERR_BLS12_POINT_NOT_ON_CURVE"));
+    pub(super) const ERR_POINT_NOT_IN_SUBGROUP: ExitError =
+        ExitError::Other(Borrowed("ERR_BLS12_POINT_NOT_IN_SUBGROUP"));
+}
+
+/// Strips the padding of an encoded base field element, checking it is less than the modulus.
+fn read_fp(input: &[u8]) -> Result<[u8; consts::FP_VALUE_LEN], ExitError> {
+    let (padding, value) = input.split_at(consts::FP_PADDING_LEN);
+    if padding.iter().any(|b| *b != 0) {
+        return Err(consts::ERR_FP_PADDING);
+    }
+    // Lexicographic order on big-endian bytes of equal length is numeric order.
+    if value >= consts::MODULUS.as_slice() {
+        return Err(consts::ERR_FP_NOT_IN_FIELD);
+    }
+    let mut buf = [0u8; consts::FP_VALUE_LEN];
+    buf.copy_from_slice(value);
+    Ok(buf)
+}
+
+/// Reads a G1 point, checking it is on the curve. All zeros encodes the point at infinity.
+fn read_g1(input: &[u8], subgroup_check: bool) -> Result<G1Affine, ExitError> {
+    let x = read_fp(&input[0..consts::FP_LEN])?;
+    let y = read_fp(&input[consts::FP_LEN..consts::G1_LEN])?;
+
+    let mut uncompressed = [0u8; 2 * consts::FP_VALUE_LEN];
+    uncompressed[0..consts::FP_VALUE_LEN].copy_from_slice(&x);
+    uncompressed[consts::FP_VALUE_LEN..].copy_from_slice(&y);
+    if uncompressed.iter().all(|b| *b == 0) {
+        return Ok(G1Affine::identity());
+    }
+
+    let point: G1Affine = Option::from(G1Affine::from_uncompressed_unchecked(&uncompressed))
+        .ok_or(consts::ERR_FP_NOT_IN_FIELD)?;
+    if !bool::from(point.is_on_curve()) {
+        return Err(consts::ERR_POINT_NOT_ON_CURVE);
+    }
+    if subgroup_check && !bool::from(point.is_torsion_free()) {
+        return Err(consts::ERR_POINT_NOT_IN_SUBGROUP);
+    }
+    Ok(point)
+}
+
+/// Reads a G2 point, checking it is on the curve. All zeros encodes the point at infinity.
+fn read_g2(input: &[u8], subgroup_check: bool) -> Result<G2Affine, ExitError> {
+    let mut coordinates = [[0u8; consts::FP_VALUE_LEN]; 4];
+    for (i, coordinate) in coordinates.iter_mut().enumerate() {
+        *coordinate = read_fp(&input[(i * consts::FP_LEN)..((i + 1) * consts::FP_LEN)])?;
+    }
+    let [x_c0, x_c1, y_c0, y_c1] = coordinates;
+
+    // The zcash serialization puts the imaginary part of each coordinate first.
+    let mut uncompressed = [0u8; 4 * consts::FP_VALUE_LEN];
+    for (i, part) in [x_c1, x_c0, y_c1, y_c0].iter().enumerate() {
+        uncompressed[(i * consts::FP_VALUE_LEN)..((i + 1) * consts::FP_VALUE_LEN)]
+            .copy_from_slice(part);
+    }
+    if uncompressed.iter().all(|b| *b == 0) {
+        return Ok(G2Affine::identity());
+    }
+
+    let point: G2Affine = Option::from(G2Affine::from_uncompressed_unchecked(&uncompressed))
+        .ok_or(consts::ERR_FP_NOT_IN_FIELD)?;
+    if !bool::from(point.is_on_curve()) {
+        return Err(consts::ERR_POINT_NOT_ON_CURVE);
+    }
+    if subgroup_check && !bool::from(point.is_torsion_free()) {
+        return Err(consts::ERR_POINT_NOT_IN_SUBGROUP);
+    }
+    Ok(point)
+}
+
+/// Reads a big-endian scalar. Scalars are not required to be less than the group order;
+/// they are reduced since all points they multiply are in the prime order subgroup.
+fn read_scalar(input: &[u8]) -> Scalar {
+    let mut wide = [0u8; 64];
+    for (dst, src) in wide
+        .iter_mut()
+        .zip(input[0..consts::SCALAR_LEN].iter().rev())
+    {
+        *dst = *src;
+    }
+    Scalar::from_bytes_wide(&wide)
+}
+
+fn pad_fp(output: &mut Vec<u8>, value: &[u8]) {
+    output.extend_from_slice(&[0u8; consts::FP_PADDING_LEN]);
+    output.extend_from_slice(value);
+}
+
+fn encode_g1(point: G1Affine) -> Vec<u8> {
+    let mut output = Vec::with_capacity(consts::G1_LEN);
+    if bool::from(point.is_identity()) {
+        output.resize(consts::G1_LEN, 0);
+        return output;
+    }
+    let uncompressed = point.to_uncompressed();
+    pad_fp(&mut output, &uncompressed[0..consts::FP_VALUE_LEN]);
+    pad_fp(&mut output, &uncompressed[consts::FP_VALUE_LEN..]);
+    output
+}
+
+fn encode_g2(point: G2Affine) -> Vec<u8> {
+    let mut output = Vec::with_capacity(consts::G2_LEN);
+    if bool::from(point.is_identity()) {
+        output.resize(consts::G2_LEN, 0);
+        return output;
+    }
+    let uncompressed = point.to_uncompressed();
+    // Reorder from the zcash serialization (imaginary part first) to that of EIP-2537.
+    for i in [1, 0, 3, 2] {
+        pad_fp(
+            &mut output,
+            &uncompressed[(i * consts::FP_VALUE_LEN)..((i + 1) * consts::FP_VALUE_LEN)],
+        );
+    }
+    output
+}
+
+/// Cost of a multi-scalar-multiplication of `k` pairs.
+fn msm_cost(k: usize, mul_cost: EthGas, discounts: &[u16]) -> Result<EthGas, ExitError> {
+    if k == 0 {
+        return Ok(EthGas::new(0));
+    }
+    let discount = discounts
+        .get(k - 1)
+        .or_else(|| discounts.last())
+        .copied()
+        .unwrap_or(1000);
+    let k = u64::try_from(k).map_err(utils::err_usize_conv)?;
+    Ok(mul_cost * k * u64::from(discount) / costs::MSM_MULTIPLIER)
+}
+
+fn check_cost(cost: EthGas, target_gas: Option<EthGas>) -> Result<(), ExitError> {
+    if let Some(target_gas) = target_gas {
+        if cost > target_gas {
+            return Err(ExitError::OutOfGas);
+        }
+    }
+    Ok(())
+}
+
+pub struct Bls12G1Add;
+
+impl Bls12G1Add {
+    pub(super) const ADDRESS: Address = super::make_address(0, 0x0b);
+}
+
+impl Precompile for Bls12G1Add {
+    fn required_gas(_input: &[u8]) -> Result<EthGas, ExitError> {
+        Ok(costs::G1_ADD)
+    }
+
+    /// Adds two G1 points. Points are not required to be in the prime order subgroup.
+    ///
+    /// See: https://eips.ethereum.org/EIPS/eip-2537
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        check_cost(cost, target_gas)?;
+
+        if input.len() != 2 * consts::G1_LEN {
+            return Err(consts::ERR_INPUT_LEN);
+        }
+        let p1 = read_g1(&input[0..consts::G1_LEN], false)?;
+        let p2 = read_g1(&input[consts::G1_LEN..], false)?;
+        let sum = G1Affine::from(G1Projective::from(p1) + p2);
+
+        Ok(PrecompileOutput::without_logs(cost, encode_g1(sum)))
+    }
+}
+
+pub struct Bls12G1Msm;
+
+impl Bls12G1Msm {
+    pub(super) const ADDRESS: Address = super::make_address(0, 0x0c);
+}
+
+impl Precompile for Bls12G1Msm {
+    fn required_gas(input: &[u8]) -> Result<EthGas, ExitError> {
+        let k = input.len() / consts::G1_MSM_PAIR_LEN;
+        msm_cost(k, costs::G1_MUL, &costs::G1_MSM_DISCOUNTS)
+    }
+
+    /// Computes the sum of G1 points multiplied by their scalars.
+    ///
+    /// See: https://eips.ethereum.org/EIPS/eip-2537
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        check_cost(cost, target_gas)?;
+
+        if input.is_empty() || input.len() % consts::G1_MSM_PAIR_LEN != 0 {
+            return Err(consts::ERR_INPUT_LEN);
+        }
+        let mut sum = G1Projective::identity();
+        for pair in input.chunks(consts::G1_MSM_PAIR_LEN) {
+            let point = read_g1(&pair[0..consts::G1_LEN], true)?;
+            let scalar = read_scalar(&pair[consts::G1_LEN..]);
+            sum += point * scalar;
+        }
+
+        Ok(PrecompileOutput::without_logs(
+            cost,
+            encode_g1(G1Affine::from(sum)),
+        ))
+    }
+}
+
+pub struct Bls12G2Add;
+
+impl Bls12G2Add {
+    pub(super) const ADDRESS: Address = super::make_address(0, 0x0d);
+}
+
+impl Precompile for Bls12G2Add {
+    fn required_gas(_input: &[u8]) -> Result<EthGas, ExitError> {
+        Ok(costs::G2_ADD)
+    }
+
+    /// Adds two G2 points. Points are not required to be in the prime order subgroup.
+    ///
+    /// See: https://eips.ethereum.org/EIPS/eip-2537
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        check_cost(cost, target_gas)?;
+
+        if input.len() != 2 * consts::G2_LEN {
+            return Err(consts::ERR_INPUT_LEN);
+        }
+        let p1 = read_g2(&input[0..consts::G2_LEN], false)?;
+        let p2 = read_g2(&input[consts::G2_LEN..], false)?;
+        let sum = G2Affine::from(G2Projective::from(p1) + p2);
+
+        Ok(PrecompileOutput::without_logs(cost, encode_g2(sum)))
+    }
+}
+
+pub struct Bls12G2Msm;
+
+impl Bls12G2Msm {
+    pub(super) const ADDRESS: Address = super::make_address(0, 0x0e);
+}
+
+impl Precompile for Bls12G2Msm {
+    fn required_gas(input: &[u8]) -> Result<EthGas, ExitError> {
+        let k = input.len() / consts::G2_MSM_PAIR_LEN;
+        msm_cost(k, costs::G2_MUL, &costs::G2_MSM_DISCOUNTS)
+    }
+
+    /// Computes the sum of G2 points multiplied by their scalars.
+    ///
+    /// See: https://eips.ethereum.org/EIPS/eip-2537
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        check_cost(cost, target_gas)?;
+
+        if input.is_empty() || input.len() % consts::G2_MSM_PAIR_LEN != 0 {
+            return Err(consts::ERR_INPUT_LEN);
+        }
+        let mut sum = G2Projective::identity();
+        for pair in input.chunks(consts::G2_MSM_PAIR_LEN) {
+            let point = read_g2(&pair[0..consts::G2_LEN], true)?;
+            let scalar = read_scalar(&pair[consts::G2_LEN..]);
+            sum += point * scalar;
+        }
+
+        Ok(PrecompileOutput::without_logs(
+            cost,
+            encode_g2(G2Affine::from(sum)),
+        ))
+    }
+}
+
+pub struct Bls12PairingCheck;
+
+impl Bls12PairingCheck {
+    pub(super) const ADDRESS: Address = super::make_address(0, 0x0f);
+}
+
+impl Precompile for Bls12PairingCheck {
+    fn required_gas(input: &[u8]) -> Result<EthGas, ExitError> {
+        let k =
+            u64::try_from(input.len() / consts::PAIRING_PAIR_LEN).map_err(utils::err_usize_conv)?;
+        Ok(costs::PAIRING_PER_PAIR * k + costs::PAIRING_BASE)
+    }
+
+    /// Checks that the product of the pairings of the given G1 and G2 points is one.
+    /// Returns 32 bytes, the last of which is `1` if so and `0` otherwise.
+    ///
+    /// See: https://eips.ethereum.org/EIPS/eip-2537
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        check_cost(cost, target_gas)?;
+
+        if input.is_empty() || input.len() % consts::PAIRING_PAIR_LEN != 0 {
+            return Err(consts::ERR_INPUT_LEN);
+        }
+        let mut pairs = Vec::with_capacity(input.len() / consts::PAIRING_PAIR_LEN);
+        for pair in input.chunks(consts::PAIRING_PAIR_LEN) {
+            let p = read_g1(&pair[0..consts::G1_LEN], true)?;
+            let q = read_g2(&pair[consts::G1_LEN..], true)?;
+            pairs.push((p, G2Prepared::from(q)));
+        }
+        let terms: Vec<(&G1Affine, &G2Prepared)> = pairs.iter().map(|(p, q)| (p, q)).collect();
+        let product = bls12_381::multi_miller_loop(&terms).final_exponentiation();
+
+        let mut output = [0u8; consts::PAIRING_OUTPUT_LEN];
+        if product == Gt::identity() {
+            output[consts::PAIRING_OUTPUT_LEN - 1] = 1;
+        }
+        Ok(PrecompileOutput::without_logs(cost, output.to_vec()))
+    }
+}
+
+pub struct Bls12MapFpToG1;
+
+impl Bls12MapFpToG1 {
+    pub(super) const ADDRESS: Address = super::make_address(0, 0x10);
+}
+
+impl Precompile for Bls12MapFpToG1 {
+    fn required_gas(_input: &[u8]) -> Result<EthGas, ExitError> {
+        Ok(costs::MAP_FP_TO_G1)
+    }
+
+    /// Maps a base field element to a G1 point, using the simplified SWU map and
+    /// clearing the cofactor.
+    ///
+    /// See: https://eips.ethereum.org/EIPS/eip-2537
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        check_cost(cost, target_gas)?;
+
+        if input.len() != consts::FP_LEN {
+            return Err(consts::ERR_INPUT_LEN);
+        }
+        read_fp(input)?;
+        // The element is already reduced, so interpreting it as a wide element is exact.
+        let element = <G1Projective as MapToCurve>::Field::from_okm(input.into());
+        let point = G1Projective::map_to_curve(&element).clear_h();
+
+        Ok(PrecompileOutput::without_logs(
+            cost,
+            encode_g1(G1Affine::from(point)),
+        ))
+    }
+}
+
+pub struct Bls12MapFp2ToG2;
+
+impl Bls12MapFp2ToG2 {
+    pub(super) const ADDRESS: Address = super::make_address(0, 0x11);
+}
+
+impl Precompile for Bls12MapFp2ToG2 {
+    fn required_gas(_input: &[u8]) -> Result<EthGas, ExitError> {
+        Ok(costs::MAP_FP2_TO_G2)
+    }
+
+    /// Maps an element of the quadratic extension field to a G2 point, using the simplified
+    /// SWU map and clearing the cofactor.
+    ///
+    /// See: https://eips.ethereum.org/EIPS/eip-2537
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        check_cost(cost, target_gas)?;
+
+        if input.len() != consts::FP2_LEN {
+            return Err(consts::ERR_INPUT_LEN);
+        }
+        read_fp(&input[0..consts::FP_LEN])?;
+        read_fp(&input[consts::FP_LEN..])?;
+        // Both parts are already reduced, so interpreting them as wide elements is exact.
+        let element = <G2Projective as MapToCurve>::Field::from_okm(input.into());
+        let point = G2Projective::map_to_curve(&element).clear_h();
+
+        Ok(PrecompileOutput::without_logs(
+            cost,
+            encode_g2(G2Affine::from(point)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::new_context;
+
+    use super::*;
+
+    fn g1_generator() -> Vec<u8> {
+        encode_g1(G1Affine::generator())
+    }
+
+    fn g2_generator() -> Vec<u8> {
+        encode_g2(G2Affine::generator())
+    }
+
+    fn scalar(x: u8) -> Vec<u8> {
+        let mut buf = vec![0u8; consts::SCALAR_LEN];
+        buf[consts::SCALAR_LEN - 1] = x;
+        buf
+    }
+
+    fn run<P: Precompile>(precompile: P, input: &[u8]) -> Result<Vec<u8>, ExitError> {
+        precompile
+            .run(input, None, &new_context(), false)
+            .map(|output| output.output)
+    }
+
+    #[test]
+    fn test_bls12_g1_add() {
+        let g = g1_generator();
+        let doubled = run(Bls12G1Add, &[g.clone(), g.clone()].concat()).unwrap();
+        let expected = encode_g1(G1Affine::from(G1Projective::generator().double()));
+        assert_eq!(doubled, expected);
+
+        // adding the point at infinity
+        let res = run(Bls12G1Add, &[g.clone(), vec![0u8; consts::G1_LEN]].concat()).unwrap();
+        assert_eq!(res, g);
+
+        // P + (-P) is the point at infinity
+        let minus_g = encode_g1(-G1Affine::generator());
+        let res = run(Bls12G1Add, &[g, minus_g].concat()).unwrap();
+        assert_eq!(res, vec![0u8; consts::G1_LEN]);
+
+        let res = run(Bls12G1Add, &[0u8; consts::G1_LEN]);
+        assert_eq!(res, Err(consts::ERR_INPUT_LEN));
+    }
+
+    #[test]
+    fn test_bls12_g1_msm() {
+        let g = g1_generator();
+        let tripled = run(
+            Bls12G1Add,
+            &[
+                g.clone(),
+                run(Bls12G1Add, &[g.clone(), g.clone()].concat()).unwrap(),
+            ]
+            .concat(),
+        )
+        .unwrap();
+
+        let res = run(Bls12G1Msm, &[g.clone(), scalar(3)].concat()).unwrap();
+        assert_eq!(res, tripled);
+        let res = run(
+            Bls12G1Msm,
+            &[g.clone(), scalar(1), g.clone(), scalar(2)].concat(),
+        )
+        .unwrap();
+        assert_eq!(res, tripled);
+
+        // multiplying by zero
+        let res = run(Bls12G1Msm, &[g, scalar(0)].concat()).unwrap();
+        assert_eq!(res, vec![0u8; consts::G1_LEN]);
+
+        let res = run(Bls12G1Msm, &[]);
+        assert_eq!(res, Err(consts::ERR_INPUT_LEN));
+    }
+
+    #[test]
+    fn test_bls12_g2_add_and_msm() {
+        let g = g2_generator();
+        let doubled = run(Bls12G2Add, &[g.clone(), g.clone()].concat()).unwrap();
+        let expected = encode_g2(G2Affine::from(G2Projective::generator().double()));
+        assert_eq!(doubled, expected);
+
+        let res = run(Bls12G2Msm, &[g, scalar(2)].concat()).unwrap();
+        assert_eq!(res, doubled);
+    }
+
+    #[test]
+    fn test_bls12_pairing_check() {
+        let g1 = g1_generator();
+        let minus_g1 = encode_g1(-G1Affine::generator());
+        let g2 = g2_generator();
+
+        let mut success = vec![0u8; consts::PAIRING_OUTPUT_LEN];
+        success[consts::PAIRING_OUTPUT_LEN - 1] = 1;
+        let failure = vec![0u8; consts::PAIRING_OUTPUT_LEN];
+
+        // e(P, Q) * e(-P, Q) = 1
+        let input = [g1.clone(), g2.clone(), minus_g1, g2.clone()].concat();
+        assert_eq!(run(Bls12PairingCheck, &input).unwrap(), success);
+
+        let input = [g1, g2].concat();
+        assert_eq!(run(Bls12PairingCheck, &input).unwrap(), failure);
+
+        let res = run(Bls12PairingCheck, &[]);
+        assert_eq!(res, Err(consts::ERR_INPUT_LEN));
+    }
+
+    #[test]
+    fn test_bls12_map_to_curve() {
+        let mut fp = vec![0u8; consts::FP_LEN];
+        fp[consts::FP_LEN - 1] = 7;
+        let res = run(Bls12MapFpToG1, &fp).unwrap();
+        assert!(read_g1(&res, true).is_ok());
+
+        let fp2 = [fp.clone(), fp].concat();
+        let res = run(Bls12MapFp2ToG2, &fp2).unwrap();
+        assert!(read_g2(&res, true).is_ok());
+    }
+
+    #[test]
+    fn test_bls12_invalid_points() {
+        // non-zero padding
+        let mut input = g1_generator();
+        input[0] = 1;
+        let res = run(Bls12G1Add, &[input, g1_generator()].concat());
+        assert_eq!(res, Err(consts::ERR_FP_PADDING));
+
+        // coordinate equal to the modulus
+        let mut input = vec![0u8; consts::FP_LEN];
+        input[consts::FP_PADDING_LEN..].copy_from_slice(&consts::MODULUS);
+        let res = run(Bls12MapFpToG1, &input);
+        assert_eq!(res, Err(consts::ERR_FP_NOT_IN_FIELD));
+
+        // (1, 1) is not on the curve
+        let mut input = vec![0u8; consts::G1_LEN];
+        input[consts::FP_LEN - 1] = 1;
+        input[consts::G1_LEN - 1] = 1;
+        let res = run(Bls12G1Add, &[input, g1_generator()].concat());
+        assert_eq!(res, Err(consts::ERR_POINT_NOT_ON_CURVE));
+    }
+
+    #[test]
+    fn test_bls12_msm_cost() {
+        let pair = vec![0u8; consts::G1_MSM_PAIR_LEN];
+        assert_eq!(
+            Bls12G1Msm::required_gas(&pair).unwrap(),
+            EthGas::new(12_000)
+        );
+        assert_eq!(
+            Bls12G1Msm::required_gas(&pair.repeat(2)).unwrap(),
+            EthGas::new(22_776)
+        );
+        // the discount is capped past the end of the table
+        assert_eq!(
+            Bls12G1Msm::required_gas(&pair.repeat(200)).unwrap(),
+            EthGas::new(200 * 12_000 * 519 / 1000)
+        );
+
+        let pairs = vec![0u8; 2 * consts::PAIRING_PAIR_LEN];
+        assert_eq!(
+            Bls12PairingCheck::required_gas(&pairs).unwrap(),
+            EthGas::new(2 * 32_600 + 37_700)
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use crate::prelude::types::{Address, EthGas};
+use crate::prelude::Borrowed;
+use crate::{utils, EvmPrecompileResult, Precompile, PrecompileOutput};
+use ed25519_dalek::Verifier;
+use evm::{Context, ExitError};
+
+pub mod costs {
+    use crate::prelude::types::EthGas;
+
+    /// Base EVM gas cost for calling this precompile.
+    /// The verification runs inside the engine's wasm, so it is priced by its NEAR gas cost:
+    /// the NEAR gas of a verification is measured for messages of different lengths and a
+    /// line `NEAR_gas = ED25519_VERIFY_BASE + (message_words) * (ED25519_VERIFY_WORD)` is
+    /// fitted, then converted to EVM gas with the same ratio as the cross contract call
+    /// precompile (`xcc::costs::CROSS_CONTRACT_CALL_NEAR_GAS`).
+    ///
+    /// This process is done in the `test_ed25519_verify_eth_gas_cost` test in
+    /// `engine-tests/src/tests/ed25519_verify.rs`.
+    pub const ED25519_VERIFY_BASE: EthGas = EthGas::new(34_000);
+    /// Additional EVM gas cost per 32-byte word of the message.
+    /// See `ED25519_VERIFY_BASE` for estimation methodology.
+    pub const ED25519_VERIFY_WORD: EthGas = EthGas::new(10);
+}
+
+mod consts {
+    pub(super) const PUBLIC_KEY_LEN: usize = 32;
+    pub(super) const SIGNATURE_LEN: usize = 64;
+    pub(super) const OUTPUT_LEN: usize = 32;
+    pub(super) const ERR_INPUT_LEN: &str = "ERR_ED25519_INPUT_LEN";
+}
+
+pub struct Ed25519Verify;
+
+impl Ed25519Verify {
+    /// Ed25519 signature verification precompile address.
+    /// Lets contracts check messages signed by NEAR access keys.
+    ///
+    /// Address: `0xd9c4d955847ab144f000772accf7deaf0a55c036`
+    /// This address is computed as: `&keccak("ed25519Verify")[12..]`
+    pub const ADDRESS: Address =
+        super::make_address(0xd9c4d955, 0x847ab144f000772accf7deaf0a55c036);
+}
+
+impl Precompile for Ed25519Verify {
+    fn required_gas(input: &[u8]) -> Result<EthGas, ExitError> {
+        let message_len = input
+            .len()
+            .saturating_sub(consts::PUBLIC_KEY_LEN + consts::SIGNATURE_LEN);
+        let message_words =
+            u64::try_from((message_len + 31) / 32).map_err(utils::err_usize_conv)?;
+        Ok(costs::ED25519_VERIFY_BASE + costs::ED25519_VERIFY_WORD * message_words)
+    }
+
+    /// Takes the public key (32 bytes), the signature (64 bytes) and the message (remaining
+    /// bytes). Returns a 32-byte word which is `1` if the signature is valid and `0` otherwise.
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        if input.len() < consts::PUBLIC_KEY_LEN + consts::SIGNATURE_LEN {
+            return Err(ExitError::Other(Borrowed(consts::ERR_INPUT_LEN)));
+        }
+        let (public_key, rest) = input.split_at(consts::PUBLIC_KEY_LEN);
+        let (signature, message) = rest.split_at(consts::SIGNATURE_LEN);
+
+        let is_valid = match (
+            ed25519_dalek::PublicKey::from_bytes(public_key),
+            ed25519_dalek::Signature::try_from(signature),
+        ) {
+            (Ok(public_key), Ok(signature)) => public_key.verify(message, &signature).is_ok(),
+            _ => false,
+        };
+
+        let mut output = [0u8; consts::OUTPUT_LEN];
+        if is_valid {
+            output[consts::OUTPUT_LEN - 1] = 1;
+        }
+        Ok(PrecompileOutput::without_logs(cost, output.to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::sdk::types::near_account_to_evm_address;
+    use crate::utils::new_context;
+
+    // Test 2 of RFC 8032, section 7.1
+    const PUBLIC_KEY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+    const SIGNATURE: &str = "\
+        92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+        085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00";
+    const MESSAGE: &str = "72";
+
+    fn input(public_key: &str, signature: &str, message: &str) -> Vec<u8> {
+        [
+            hex::decode(public_key).unwrap(),
+            hex::decode(signature).unwrap(),
+            hex::decode(message).unwrap(),
+        ]
+        .concat()
+    }
+
+    fn run(input: &[u8]) -> Result<Vec<u8>, ExitError> {
+        Ed25519Verify
+            .run(input, None, &new_context(), false)
+            .map(|output| output.output)
+    }
+
+    #[test]
+    fn test_precompile_id() {
+        assert_eq!(
+            Ed25519Verify::ADDRESS,
+            near_account_to_evm_address("ed25519Verify".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_ed25519_verify() {
+        let mut expected = vec![0u8; 32];
+        expected[31] = 1;
+        assert_eq!(
+            run(&input(PUBLIC_KEY, SIGNATURE, MESSAGE)).unwrap(),
+            expected
+        );
+
+        // wrong message
+        assert_eq!(
+            run(&input(PUBLIC_KEY, SIGNATURE, "73")).unwrap(),
+            vec![0u8; 32]
+        );
+
+        // invalid signature encoding
+        let mut signature = hex::decode(SIGNATURE).unwrap();
+        signature[63] = 0xff;
+        let input = [
+            hex::decode(PUBLIC_KEY).unwrap(),
+            signature,
+            hex::decode(MESSAGE).unwrap(),
+        ]
+        .concat();
+        assert_eq!(run(&input).unwrap(), vec![0u8; 32]);
+
+        // too short
+        assert!(run(&[0u8; 95]).is_err());
+    }
+
+    #[test]
+    fn test_ed25519_verify_cost() {
+        assert_eq!(
+            Ed25519Verify::required_gas(&[0u8; 96]).unwrap(),
+            costs::ED25519_VERIFY_BASE
+        );
+        assert_eq!(
+            Ed25519Verify::required_gas(&[0u8; 97]).unwrap(),
+            costs::ED25519_VERIFY_BASE + costs::ED25519_VERIFY_WORD
+        );
+        assert_eq!(
+            Ed25519Verify::required_gas(&[0u8; 96 + 64]).unwrap(),
+            costs::ED25519_VERIFY_BASE + costs::ED25519_VERIFY_WORD * 2u64
+        );
+    }
+}
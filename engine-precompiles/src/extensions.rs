@@ -0,0 +1,64 @@
+//! Precompiles supplied by projects embedding the engine, e.g. a standalone simulator.
+//!
+//! Extensions are installed on top of the precompiles of the hard fork, after the
+//! precompile registry is applied, and replace whatever is installed at their address.
+
+use crate::prelude::types::Address;
+use crate::prelude::BTreeMap;
+use crate::{HandleBasedPrecompile, Precompile};
+use aurora_engine_types::Box;
+
+pub enum ExtensionPrecompile {
+    Generic(Box<dyn Precompile>),
+    HandleBased(Box<dyn HandleBasedPrecompile>),
+}
+
+#[derive(Default)]
+pub struct PrecompileExtensions {
+    precompiles: BTreeMap<Address, ExtensionPrecompile>,
+}
+
+impl PrecompileExtensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_precompile(mut self, address: Address, precompile: Box<dyn Precompile>) -> Self {
+        self.insert(address, ExtensionPrecompile::Generic(precompile));
+        self
+    }
+
+    pub fn with_handle_based_precompile(
+        mut self,
+        address: Address,
+        precompile: Box<dyn HandleBasedPrecompile>,
+    ) -> Self {
+        self.insert(address, ExtensionPrecompile::HandleBased(precompile));
+        self
+    }
+
+    /// Installs the precompile at the given address, returning the one it replaces.
+    pub fn insert(
+        &mut self,
+        address: Address,
+        precompile: ExtensionPrecompile,
+    ) -> Option<ExtensionPrecompile> {
+        self.precompiles.insert(address, precompile)
+    }
+
+    pub fn remove(&mut self, address: &Address) -> Option<ExtensionPrecompile> {
+        self.precompiles.remove(address)
+    }
+
+    pub fn contains(&self, address: &Address) -> bool {
+        self.precompiles.contains_key(address)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.precompiles.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Address, &ExtensionPrecompile)> {
+        self.precompiles.iter()
+    }
+}
@@ -0,0 +1,233 @@
+use crate::prelude::types::{Address, EthGas};
+use crate::prelude::{sdk, Borrowed};
+use crate::{EvmPrecompileResult, Precompile, PrecompileOutput};
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar};
+use evm::{Context, ExitError};
+
+mod costs {
+    use crate::prelude::types::EthGas;
+
+    pub(super) const POINT_EVALUATION: EthGas = EthGas::new(50_000);
+}
+
+mod consts {
+    /// Versioned hash (32 bytes), `z` (32 bytes), `y` (32 bytes), commitment (48 bytes)
+    /// and proof (48 bytes).
+    pub(super) const INPUT_LEN: usize = 192;
+
+    pub(super) const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+    /// `FIELD_ELEMENTS_PER_BLOB` followed by `BLS_MODULUS`, both as 32-byte big-endian words.
+    pub(super) const RETURN_VALUE: [u8; 64] = [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x10, 0x00, 0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09,
+        0xa1, 0xd8, 0x05, 0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff,
+        0x00, 0x00, 0x00, 0x01,
+    ];
+
+    /// `[τ]₂` of the Ethereum KZG ceremony, as a compressed G2 point. The point evaluation
+    /// only needs this element of the trusted setup.
+    ///
+    /// See: https://github.com/ethereum/c-kzg-4844/blob/main/src/trusted_setup.txt
+    pub(super) const TRUSTED_SETUP_TAU_G2: [u8; 96] = [
+        0xb5, 0xbf, 0xd7, 0xdd, 0x8c, 0xde, 0xb1, 0x28, 0x84, 0x3b, 0xc2, 0x87, 0x23, 0x0a, 0xf3,
+        0x89, 0x26, 0x18, 0x70, 0x75, 0xcb, 0xfb, 0xef, 0xa8, 0x10, 0x09, 0xa2, 0xce, 0x61, 0x5a,
+        0xc5, 0x3d, 0x29, 0x14, 0xe5, 0x87, 0x0c, 0xb4, 0x52, 0xd2, 0xaf, 0xaa, 0xab, 0x24, 0xf3,
+        0x49, 0x9f, 0x72, 0x18, 0x5c, 0xbf, 0xee, 0x53, 0x49, 0x27, 0x14, 0x73, 0x44, 0x29, 0xb7,
+        0xb3, 0x86, 0x08, 0xe2, 0x39, 0x26, 0xc9, 0x11, 0xcc, 0xec, 0xea, 0xc9, 0xa3, 0x68, 0x51,
+        0x47, 0x7b, 0xa4, 0xc6, 0x0b, 0x08, 0x70, 0x41, 0xde, 0x62, 0x10, 0x00, 0xed, 0xc9, 0x8e,
+        0xda, 0xda, 0x20, 0xc1, 0xde, 0xf2,
+    ];
+
+    pub(super) const ERR_INPUT_LEN: &str = "ERR_KZG_INPUT_LEN";
+    pub(super) const ERR_VERSIONED_HASH: &str = "ERR_KZG_VERSIONED_HASH";
+    pub(super) const ERR_FIELD_ELEMENT: &str = "ERR_KZG_INVALID_FIELD_ELEMENT";
+    pub(super) const ERR_POINT: &str = "ERR_KZG_INVALID_POINT";
+    pub(super) const ERR_PROOF: &str = "ERR_KZG_INVALID_PROOF";
+}
+
+/// Versioned hash of a KZG commitment: its SHA-256 hash with the first byte replaced
+/// by the version.
+fn kzg_to_versioned_hash(commitment: &[u8]) -> [u8; 32] {
+    let mut hash = sdk::sha256(commitment).0;
+    hash[0] = consts::VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+/// Reads a big-endian scalar, which must be less than the modulus.
+fn read_scalar(input: &[u8]) -> Result<Scalar, ExitError> {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(input);
+    bytes.reverse();
+    Option::from(Scalar::from_bytes(&bytes))
+        .ok_or(ExitError::Other(Borrowed(consts::ERR_FIELD_ELEMENT)))
+}
+
+/// Reads a compressed G1 point, which must be in the prime order subgroup.
+fn read_g1(input: &[u8]) -> Result<G1Affine, ExitError> {
+    let mut bytes = [0u8; 48];
+    bytes.copy_from_slice(input);
+    Option::from(G1Affine::from_compressed(&bytes))
+        .ok_or(ExitError::Other(Borrowed(consts::ERR_POINT)))
+}
+
+/// Checks that `proof` shows the polynomial committed to by `commitment` evaluates to `y`
+/// at `z`, i.e. that `e(proof, [τ - z]₂) = e(commitment - [y]₁, [1]₂)`.
+fn verify_kzg_proof(commitment: G1Affine, z: Scalar, y: Scalar, proof: G1Affine) -> bool {
+    let tau_g2: G2Affine =
+        match Option::from(G2Affine::from_compressed(&consts::TRUSTED_SETUP_TAU_G2)) {
+            Some(point) => point,
+            None => return false,
+        };
+    let x_minus_z = G2Affine::from(G2Projective::from(tau_g2) - G2Affine::generator() * z);
+    let p_minus_y = G1Affine::from(G1Projective::from(commitment) - G1Affine::generator() * y);
+
+    let product = bls12_381::multi_miller_loop(&[
+        (&proof, &G2Prepared::from(x_minus_z)),
+        (&-p_minus_y, &G2Prepared::from(G2Affine::generator())),
+    ])
+    .final_exponentiation();
+    product == Gt::identity()
+}
+
+/// Point evaluation precompile of EIP-4844.
+pub struct KzgPointEvaluation;
+
+impl KzgPointEvaluation {
+    pub const ADDRESS: Address = super::make_address(0, 0x0a);
+}
+
+impl Precompile for KzgPointEvaluation {
+    fn required_gas(_input: &[u8]) -> Result<EthGas, ExitError> {
+        Ok(costs::POINT_EVALUATION)
+    }
+
+    /// Verifies that the blob with the given versioned hash evaluates to `y` at `z`.
+    /// Returns the number of field elements per blob and the modulus of the field.
+    ///
+    /// See: https://eips.ethereum.org/EIPS/eip-4844#point-evaluation-precompile
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        if input.len() != consts::INPUT_LEN {
+            return Err(ExitError::Other(Borrowed(consts::ERR_INPUT_LEN)));
+        }
+        let versioned_hash = &input[0..32];
+        let commitment = &input[96..144];
+        if kzg_to_versioned_hash(commitment) != versioned_hash {
+            return Err(ExitError::Other(Borrowed(consts::ERR_VERSIONED_HASH)));
+        }
+
+        let z = read_scalar(&input[32..64])?;
+        let y = read_scalar(&input[64..96])?;
+        let commitment = read_g1(commitment)?;
+        let proof = read_g1(&input[144..192])?;
+        if !verify_kzg_proof(commitment, z, y, proof) {
+            return Err(ExitError::Other(Borrowed(consts::ERR_PROOF)));
+        }
+
+        Ok(PrecompileOutput::without_logs(
+            cost,
+            consts::RETURN_VALUE.to_vec(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::U256;
+    use crate::utils::new_context;
+
+    /// `[τ]₁` of the Ethereum KZG ceremony, used to commit to a linear polynomial.
+    const TAU_G1: &str = "\
+        ad3eb50121139aa34db1d545093ac9374ab7bca2c0f3bf28\
+        e27c8dcd8fc7cb42d25926fc0c97b336e9f0fb35e5a04c81";
+
+    fn scalar_bytes(x: Scalar) -> Vec<u8> {
+        let mut bytes = x.to_bytes();
+        bytes.reverse();
+        bytes.to_vec()
+    }
+
+    /// Input proving that `p(x) = a + b * x` evaluates to `y` at `z`.
+    fn linear_polynomial_input(a: u64, b: u64, z: u64, y: u64) -> Vec<u8> {
+        let mut tau_g1 = [0u8; 48];
+        tau_g1.copy_from_slice(&hex::decode(TAU_G1).unwrap());
+        let tau_g1 = G1Affine::from_compressed(&tau_g1).unwrap();
+
+        let commitment =
+            G1Affine::from(G1Affine::generator() * Scalar::from(a) + tau_g1 * Scalar::from(b))
+                .to_compressed();
+        // The quotient (p(x) - p(z)) / (x - z) is the constant `b`.
+        let proof = G1Affine::from(G1Affine::generator() * Scalar::from(b)).to_compressed();
+
+        [
+            kzg_to_versioned_hash(&commitment).to_vec(),
+            scalar_bytes(Scalar::from(z)),
+            scalar_bytes(Scalar::from(y)),
+            commitment.to_vec(),
+            proof.to_vec(),
+        ]
+        .concat()
+    }
+
+    fn run(input: &[u8]) -> Result<Vec<u8>, ExitError> {
+        KzgPointEvaluation
+            .run(input, Some(EthGas::new(50_000)), &new_context(), false)
+            .map(|output| output.output)
+    }
+
+    #[test]
+    fn test_kzg_point_evaluation() {
+        // p(x) = 3 + 5x, p(7) = 38
+        let input = linear_polynomial_input(3, 5, 7, 38);
+        assert_eq!(run(&input).unwrap(), consts::RETURN_VALUE.to_vec());
+
+        let output = U256::from_big_endian(&consts::RETURN_VALUE[0..32]);
+        assert_eq!(output, U256::from(4096));
+    }
+
+    #[test]
+    fn test_kzg_point_evaluation_invalid() {
+        // wrong evaluation
+        let input = linear_polynomial_input(3, 5, 7, 39);
+        assert_eq!(
+            run(&input),
+            Err(ExitError::Other(Borrowed(consts::ERR_PROOF)))
+        );
+
+        // wrong versioned hash
+        let mut input = linear_polynomial_input(3, 5, 7, 38);
+        input[0] = 0x02;
+        assert_eq!(
+            run(&input),
+            Err(ExitError::Other(Borrowed(consts::ERR_VERSIONED_HASH)))
+        );
+
+        // z not less than the modulus
+        let mut input = linear_polynomial_input(3, 5, 7, 38);
+        input[32..64].copy_from_slice(&consts::RETURN_VALUE[32..64]);
+        assert_eq!(
+            run(&input),
+            Err(ExitError::Other(Borrowed(consts::ERR_FIELD_ELEMENT)))
+        );
+
+        assert_eq!(
+            run(&input[..191]),
+            Err(ExitError::Other(Borrowed(consts::ERR_INPUT_LEN)))
+        );
+    }
+}
@@ -4,32 +4,53 @@
 #![deny(clippy::as_conversions)]
 
 pub mod account_ids;
+pub mod address_resolver;
 pub mod alt_bn256;
 pub mod blake2;
+pub mod block_context;
+pub mod bls12_381;
+pub mod ed25519;
+pub mod extensions;
 pub mod hash;
 pub mod identity;
+pub mod kzg;
 pub mod modexp;
 pub mod native;
 mod prelude;
 pub mod prepaid_gas;
 pub mod promise_result;
 pub mod random;
+pub mod registry;
 pub mod secp256k1;
+pub mod secp256r1;
 mod utils;
+pub mod wrapped_eth;
 pub mod xcc;
 
 use crate::account_ids::{predecessor_account, CurrentAccount, PredecessorAccount};
+use crate::address_resolver::AddressResolver;
 use crate::alt_bn256::{Bn256Add, Bn256Mul, Bn256Pair};
 use crate::blake2::Blake2F;
+use crate::block_context::BlockContext;
+use crate::bls12_381::{
+    Bls12G1Add, Bls12G1Msm, Bls12G2Add, Bls12G2Msm, Bls12MapFp2ToG2, Bls12MapFpToG1,
+    Bls12PairingCheck,
+};
+use crate::ed25519::Ed25519Verify;
+use crate::extensions::{ExtensionPrecompile, PrecompileExtensions};
 use crate::hash::{RIPEMD160, SHA256};
 use crate::identity::Identity;
+use crate::kzg::KzgPointEvaluation;
 use crate::modexp::ModExp;
 use crate::native::{exit_to_ethereum, exit_to_near, ExitToEthereum, ExitToNear};
 use crate::prelude::types::EthGas;
-use crate::prelude::{Vec, H160, H256};
+use crate::prelude::{Vec, H160, H256, U256};
 use crate::prepaid_gas::PrepaidGas;
-use crate::random::RandomSeed;
+use crate::random::{RandomSeed, TransactionRandomSeed};
+use crate::registry::PrecompileRegistry;
 use crate::secp256k1::ECRecover;
+use crate::secp256r1::P256Verify;
+use crate::wrapped_eth::WrappedEth;
 use crate::xcc::CrossContractCall;
 use aurora_engine_sdk::env::Env;
 use aurora_engine_sdk::io::IO;
@@ -83,7 +104,7 @@ pub trait Precompile {
 pub trait HandleBasedPrecompile {
     fn run_with_handle(
         &self,
-        handle: &mut impl PrecompileHandle,
+        handle: &mut dyn PrecompileHandle,
     ) -> Result<PrecompileOutput, PrecompileFailure>;
 }
 
@@ -102,6 +123,15 @@ pub struct Istanbul;
 /// Homestead hard fork marker.
 pub struct Berlin;
 
+/// Cancun hard fork marker.
+pub struct Cancun;
+
+/// Prague hard fork marker.
+pub struct Prague;
+
+/// Osaka hard fork marker.
+pub struct Osaka;
+
 impl HardFork for Homestead {}
 
 impl HardFork for Byzantium {}
@@ -110,6 +140,12 @@ impl HardFork for Istanbul {}
 
 impl HardFork for Berlin {}
 
+impl HardFork for Cancun {}
+
+impl HardFork for Prague {}
+
+impl HardFork for Osaka {}
+
 pub struct Precompiles<'a, I, E, H> {
     pub all_precompiles: prelude::BTreeMap<Address, AllPrecompiles<'a, I, E, H>>,
 }
@@ -128,9 +164,18 @@ impl<'a, I: IO + Copy, E: Env, H: ReadOnlyPromiseHandler> executor::stack::Preco
             AllPrecompiles::ExitToEthereum(p) => process_precompile(p, handle),
             AllPrecompiles::PredecessorAccount(p) => process_precompile(p, handle),
             AllPrecompiles::PrepaidGas(p) => process_precompile(p, handle),
+            AllPrecompiles::BlockContext(p) => process_precompile(p, handle),
+            AllPrecompiles::AddressResolver(p) => process_precompile(p, handle),
             AllPrecompiles::PromiseResult(p) => process_precompile(p, handle),
             AllPrecompiles::CrossContractCall(p) => process_handle_based_precompile(p, handle),
+            AllPrecompiles::WrappedEth(p) => process_handle_based_precompile(p, handle),
             AllPrecompiles::Generic(p) => process_precompile(p.as_ref(), handle),
+            AllPrecompiles::Extension(ExtensionPrecompile::Generic(p)) => {
+                process_precompile(p.as_ref(), handle)
+            }
+            AllPrecompiles::Extension(ExtensionPrecompile::HandleBased(p)) => {
+                process_handle_based_precompile(p.as_ref(), handle)
+            }
         };
         Some(result.and_then(|output| post_process(output, handle)))
     }
@@ -154,7 +199,7 @@ fn process_precompile(
 }
 
 fn process_handle_based_precompile(
-    p: &impl HandleBasedPrecompile,
+    p: &dyn HandleBasedPrecompile,
     handle: &mut impl PrecompileHandle,
 ) -> Result<PrecompileOutput, PrecompileFailure> {
     p.run_with_handle(handle)
@@ -176,10 +221,20 @@ fn post_process(
 
 pub struct PrecompileConstructorContext<'a, I, E, H> {
     pub current_account_id: AccountId,
+    /// Chain id of the engine, according to EIP-155.
+    pub chain_id: U256,
     pub random_seed: H256,
+    /// Hash of the transaction being executed, mixed into the per-transaction random seed.
+    /// Outside of `submit` this is a hash of the receipt input, predecessor and random seed.
+    pub transaction_hash: H256,
     pub io: I,
     pub env: &'a E,
     pub promise_handler: H,
+    /// Overrides applied on top of the precompiles of the hard fork.
+    pub registry: PrecompileRegistry,
+    /// Whether the block context, address resolver, transaction random seed and wrapped ETH
+    /// precompiles are installed, which is the case from their activation height.
+    pub extended: bool,
 }
 
 impl<'a, I: IO + Copy, E: Env, H: ReadOnlyPromiseHandler> Precompiles<'a, I, E, H> {
@@ -319,13 +374,164 @@ impl<'a, I: IO + Copy, E: Env, H: ReadOnlyPromiseHandler> Precompiles<'a, I, E,
         Self::new_berlin(ctx)
     }
 
+    pub fn new_cancun(ctx: PrecompileConstructorContext<'a, I, E, H>) -> Self {
+        let addresses = vec![
+            ECRecover::ADDRESS,
+            SHA256::ADDRESS,
+            RIPEMD160::ADDRESS,
+            Identity::ADDRESS,
+            ModExp::<Berlin>::ADDRESS,
+            Bn256Add::<Istanbul>::ADDRESS,
+            Bn256Mul::<Istanbul>::ADDRESS,
+            Bn256Pair::<Istanbul>::ADDRESS,
+            Blake2F::ADDRESS,
+            KzgPointEvaluation::ADDRESS,
+            RandomSeed::ADDRESS,
+            CurrentAccount::ADDRESS,
+        ];
+        let fun: prelude::Vec<Box<dyn Precompile>> = vec![
+            Box::new(ECRecover),
+            Box::new(SHA256),
+            Box::new(RIPEMD160),
+            Box::new(Identity),
+            Box::new(ModExp::<Berlin>::new()),
+            Box::new(Bn256Add::<Istanbul>::new()),
+            Box::new(Bn256Mul::<Istanbul>::new()),
+            Box::new(Bn256Pair::<Istanbul>::new()),
+            Box::new(Blake2F),
+            Box::new(KzgPointEvaluation),
+            Box::new(RandomSeed::new(ctx.random_seed)),
+            Box::new(CurrentAccount::new(ctx.current_account_id.clone())),
+        ];
+        let map = addresses
+            .into_iter()
+            .zip(fun)
+            .map(|(a, f)| (a, AllPrecompiles::Generic(f)))
+            .collect();
+
+        Self::with_generic_precompiles(map, ctx)
+    }
+
+    pub fn new_prague(ctx: PrecompileConstructorContext<'a, I, E, H>) -> Self {
+        let addresses = vec![
+            ECRecover::ADDRESS,
+            SHA256::ADDRESS,
+            RIPEMD160::ADDRESS,
+            Identity::ADDRESS,
+            ModExp::<Berlin>::ADDRESS,
+            Bn256Add::<Istanbul>::ADDRESS,
+            Bn256Mul::<Istanbul>::ADDRESS,
+            Bn256Pair::<Istanbul>::ADDRESS,
+            Blake2F::ADDRESS,
+            KzgPointEvaluation::ADDRESS,
+            Bls12G1Add::ADDRESS,
+            Bls12G1Msm::ADDRESS,
+            Bls12G2Add::ADDRESS,
+            Bls12G2Msm::ADDRESS,
+            Bls12PairingCheck::ADDRESS,
+            Bls12MapFpToG1::ADDRESS,
+            Bls12MapFp2ToG2::ADDRESS,
+            P256Verify::ADDRESS,
+            Ed25519Verify::ADDRESS,
+            RandomSeed::ADDRESS,
+            CurrentAccount::ADDRESS,
+        ];
+        let fun: prelude::Vec<Box<dyn Precompile>> = vec![
+            Box::new(ECRecover),
+            Box::new(SHA256),
+            Box::new(RIPEMD160),
+            Box::new(Identity),
+            Box::new(ModExp::<Berlin>::new()),
+            Box::new(Bn256Add::<Istanbul>::new()),
+            Box::new(Bn256Mul::<Istanbul>::new()),
+            Box::new(Bn256Pair::<Istanbul>::new()),
+            Box::new(Blake2F),
+            Box::new(KzgPointEvaluation),
+            Box::new(Bls12G1Add),
+            Box::new(Bls12G1Msm),
+            Box::new(Bls12G2Add),
+            Box::new(Bls12G2Msm),
+            Box::new(Bls12PairingCheck),
+            Box::new(Bls12MapFpToG1),
+            Box::new(Bls12MapFp2ToG2),
+            Box::new(P256Verify),
+            Box::new(Ed25519Verify),
+            Box::new(RandomSeed::new(ctx.random_seed)),
+            Box::new(CurrentAccount::new(ctx.current_account_id.clone())),
+        ];
+        let map = addresses
+            .into_iter()
+            .zip(fun)
+            .map(|(a, f)| (a, AllPrecompiles::Generic(f)))
+            .collect();
+
+        Self::with_generic_precompiles(map, ctx)
+    }
+
+    pub fn new_osaka(ctx: PrecompileConstructorContext<'a, I, E, H>) -> Self {
+        let addresses = vec![
+            ECRecover::ADDRESS,
+            SHA256::ADDRESS,
+            RIPEMD160::ADDRESS,
+            Identity::ADDRESS,
+            ModExp::<Osaka>::ADDRESS,
+            Bn256Add::<Istanbul>::ADDRESS,
+            Bn256Mul::<Istanbul>::ADDRESS,
+            Bn256Pair::<Istanbul>::ADDRESS,
+            Blake2F::ADDRESS,
+            KzgPointEvaluation::ADDRESS,
+            Bls12G1Add::ADDRESS,
+            Bls12G1Msm::ADDRESS,
+            Bls12G2Add::ADDRESS,
+            Bls12G2Msm::ADDRESS,
+            Bls12PairingCheck::ADDRESS,
+            Bls12MapFpToG1::ADDRESS,
+            Bls12MapFp2ToG2::ADDRESS,
+            P256Verify::ADDRESS,
+            Ed25519Verify::ADDRESS,
+            RandomSeed::ADDRESS,
+            CurrentAccount::ADDRESS,
+        ];
+        let fun: prelude::Vec<Box<dyn Precompile>> = vec![
+            Box::new(ECRecover),
+            Box::new(SHA256),
+            Box::new(RIPEMD160),
+            Box::new(Identity),
+            Box::new(ModExp::<Osaka>::new()),
+            Box::new(Bn256Add::<Istanbul>::new()),
+            Box::new(Bn256Mul::<Istanbul>::new()),
+            Box::new(Bn256Pair::<Istanbul>::new()),
+            Box::new(Blake2F),
+            Box::new(KzgPointEvaluation),
+            Box::new(Bls12G1Add),
+            Box::new(Bls12G1Msm),
+            Box::new(Bls12G2Add),
+            Box::new(Bls12G2Msm),
+            Box::new(Bls12PairingCheck),
+            Box::new(Bls12MapFpToG1),
+            Box::new(Bls12MapFp2ToG2),
+            Box::new(P256Verify),
+            Box::new(Ed25519Verify),
+            Box::new(RandomSeed::new(ctx.random_seed)),
+            Box::new(CurrentAccount::new(ctx.current_account_id.clone())),
+        ];
+        let map = addresses
+            .into_iter()
+            .zip(fun)
+            .map(|(a, f)| (a, AllPrecompiles::Generic(f)))
+            .collect();
+
+        Self::with_generic_precompiles(map, ctx)
+    }
+
     fn with_generic_precompiles(
         mut generic_precompiles: BTreeMap<Address, AllPrecompiles<'a, I, E, H>>,
         ctx: PrecompileConstructorContext<'a, I, E, H>,
     ) -> Self {
         let near_exit = ExitToNear::new(ctx.current_account_id.clone(), ctx.io);
         let ethereum_exit = ExitToEthereum::new(ctx.current_account_id.clone(), ctx.io);
-        let cross_contract_call = CrossContractCall::new(ctx.current_account_id, ctx.io);
+        let cross_contract_call =
+            CrossContractCall::new(ctx.current_account_id, ctx.io, ctx.env, ctx.chain_id);
         let predecessor_account_id = PredecessorAccount::new(ctx.env);
         let prepaid_gas = PrepaidGas::new(ctx.env);
         let promise_results = PromiseResult::new(ctx.promise_handler);
@@ -351,21 +557,57 @@ impl<'a, I: IO + Copy, E: Env, H: ReadOnlyPromiseHandler> Precompiles<'a, I, E,
             promise_result::ADDRESS,
             AllPrecompiles::PromiseResult(promise_results),
         );
+        if ctx.extended {
+            let block_context = BlockContext::new(ctx.env);
+            let address_resolver = AddressResolver::new(ctx.io);
+            let transaction_random_seed =
+                TransactionRandomSeed::new(ctx.random_seed, ctx.transaction_hash);
+
+            generic_precompiles.insert(
+                block_context::ADDRESS,
+                AllPrecompiles::BlockContext(block_context),
+            );
+            generic_precompiles.insert(
+                address_resolver::ADDRESS,
+                AllPrecompiles::AddressResolver(address_resolver),
+            );
+            generic_precompiles
+                .insert(wrapped_eth::ADDRESS, AllPrecompiles::WrappedEth(WrappedEth));
+            generic_precompiles.insert(
+                TransactionRandomSeed::ADDRESS,
+                AllPrecompiles::Generic(Box::new(transaction_random_seed)),
+            );
+        }
+
+        ctx.registry.apply(&mut generic_precompiles);
 
         Self {
             all_precompiles: generic_precompiles,
         }
     }
+
+    /// Installs all the given extensions, replacing whatever is installed at their addresses.
+    pub fn with_extensions(mut self, extensions: &'a PrecompileExtensions) -> Self {
+        for (address, precompile) in extensions.iter() {
+            self.all_precompiles
+                .insert(*address, AllPrecompiles::Extension(precompile));
+        }
+        self
+    }
 }
 
 pub enum AllPrecompiles<'a, I, E, H> {
     ExitToNear(ExitToNear<I>),
     ExitToEthereum(ExitToEthereum<I>),
-    CrossContractCall(CrossContractCall<I>),
+    CrossContractCall(CrossContractCall<'a, I, E>),
     PredecessorAccount(PredecessorAccount<'a, E>),
     PrepaidGas(PrepaidGas<'a, E>),
+    BlockContext(BlockContext<'a, E>),
+    AddressResolver(AddressResolver<I>),
+    WrappedEth(WrappedEth),
     PromiseResult(PromiseResult<H>),
     Generic(Box<dyn Precompile>),
+    Extension(&'a ExtensionPrecompile),
 }
 
 /// fn for making an address by concatenating the bytes from two given numbers,
@@ -455,6 +697,15 @@ mod tests {
         assert_eq!(super::Bn256Mul::<Istanbul>::ADDRESS, u8_to_address(7));
         assert_eq!(super::Bn256Pair::<Istanbul>::ADDRESS, u8_to_address(8));
         assert_eq!(super::blake2::Blake2F::ADDRESS, u8_to_address(9));
+        assert_eq!(super::KzgPointEvaluation::ADDRESS, u8_to_address(0x0a));
+        assert_eq!(super::Bls12G1Add::ADDRESS, u8_to_address(0x0b));
+        assert_eq!(super::Bls12G1Msm::ADDRESS, u8_to_address(0x0c));
+        assert_eq!(super::Bls12G2Add::ADDRESS, u8_to_address(0x0d));
+        assert_eq!(super::Bls12G2Msm::ADDRESS, u8_to_address(0x0e));
+        assert_eq!(super::Bls12PairingCheck::ADDRESS, u8_to_address(0x0f));
+        assert_eq!(super::Bls12MapFpToG1::ADDRESS, u8_to_address(0x10));
+        assert_eq!(super::Bls12MapFp2ToG2::ADDRESS, u8_to_address(0x11));
+        assert_eq!(super::P256Verify::ADDRESS, super::make_address(0, 0x100));
     }
 
     #[test]
@@ -1,24 +1,126 @@
 use crate::prelude::types::{Address, EthGas};
-use crate::prelude::{PhantomData, Vec, U256};
+use crate::prelude::{Borrowed, PhantomData, Vec, U256};
 use crate::{
-    utils, Berlin, Byzantium, EvmPrecompileResult, HardFork, Precompile, PrecompileOutput,
+    utils, Berlin, Byzantium, EvmPrecompileResult, HardFork, Osaka, Precompile, PrecompileOutput,
 };
 use evm::{Context, ExitError};
-use num::{BigUint, Integer};
+use num::{BigUint, Integer, One, Zero};
 
-pub(super) struct ModExp<HF: HardFork>(PhantomData<HF>);
+mod consts {
+    /// Upper bound of the length of each of the base, exponent and modulus (EIP-7823).
+    pub(super) const MAX_INPUT_LEN: u64 = 1_024;
 
-impl<HF: HardFork> ModExp<HF> {
+    pub(super) const ERR_INPUT_TOO_LARGE: &str = "ERR_MODEXP_INPUT_TOO_LARGE";
+}
+
+/// Big integer implementation computing the modular exponentiation.
+///
+/// Only `AuroraModExp` is provided. It does not speed up large odd moduli, so heavy RSA
+/// verifications are still bounded by the NEAR gas limit of a receipt.
+pub trait ModExpAlgorithm: 'static {
+    /// Computes `(base ^ exp) % modulus`, with all the numbers in big-endian.
+    /// A zero modulus gives an empty output.
+    fn modexp(base: &[u8], exp: &[u8], modulus: &[u8]) -> Vec<u8>;
+}
+
+/// Default implementation, based on the `num` crate.
+///
+/// `num` only uses Montgomery multiplication for odd moduli, so even moduli are split into an
+/// odd factor and a power of two, whose results are combined with the Chinese remainder theorem.
+/// Reductions modulo a power of two are just truncations, which makes large even moduli about
+/// as cheap as odd ones. Odd moduli, which include all RSA moduli, still use `num`'s Montgomery
+/// multiplication unchanged, so this does not make RSA verifications any cheaper.
+pub struct AuroraModExp;
+
+impl ModExpAlgorithm for AuroraModExp {
+    fn modexp(base: &[u8], exp: &[u8], modulus: &[u8]) -> Vec<u8> {
+        let base = BigUint::from_bytes_be(base);
+        let exponent = BigUint::from_bytes_be(exp);
+        let modulus = BigUint::from_bytes_be(modulus);
+        if modulus.is_zero() {
+            return Vec::new();
+        }
+        modpow(&base, &exponent, &modulus).to_bytes_be()
+    }
+}
+
+fn modpow(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+    if modulus.is_one() {
+        return BigUint::zero();
+    } else if modulus.is_odd() {
+        return base.modpow(exponent, modulus);
+    }
+
+    // modulus = odd * 2^k, with k > 0 since the modulus is even and non-zero.
+    let k = modulus.trailing_zeros().unwrap_or_default();
+    let odd = modulus >> k;
+    let low = modpow_power_of_two(base, exponent, k);
+    if odd.is_one() {
+        return low;
+    }
+    let high = base.modpow(exponent, &odd);
+
+    // The result is `high + odd * y`, where `y = (low - high) / odd (mod 2^k)`.
+    let power_of_two = BigUint::one() << k;
+    let mask = &power_of_two - 1u32;
+    let diff = (low + &power_of_two - (&high & &mask)) & &mask;
+    let y = (diff * inverse_mod_power_of_two(&odd, k)) & &mask;
+    high + odd * y
+}
+
+/// Computes `(base ^ exponent) % 2^k`.
+fn modpow_power_of_two(base: &BigUint, exponent: &BigUint, k: u64) -> BigUint {
+    let mask = (BigUint::one() << k) - 1u32;
+    let base = base & &mask;
+    let mut result = BigUint::one() & &mask;
+    for i in (0..exponent.bits()).rev() {
+        result = (&result * &result) & &mask;
+        if exponent.bit(i) {
+            result = (&result * &base) & &mask;
+        }
+    }
+    result
+}
+
+/// Computes the inverse of `odd` modulo `2^k`, with Newton's iteration (each step doubles the
+/// number of correct low bits).
+fn inverse_mod_power_of_two(odd: &BigUint, k: u64) -> BigUint {
+    let power_of_two = BigUint::one() << k;
+    let mask = &power_of_two - 1u32;
+    let mut inverse = BigUint::one();
+    let mut correct_bits = 1;
+    while correct_bits < k {
+        let product = (odd * &inverse) & &mask;
+        let correction = (BigUint::from(2u32) + &power_of_two - product) & &mask;
+        inverse = (inverse * correction) & &mask;
+        correct_bits *= 2;
+    }
+    inverse
+}
+
+pub(super) struct ModExp<HF: HardFork, M: ModExpAlgorithm = AuroraModExp>(
+    PhantomData<HF>,
+    PhantomData<M>,
+);
+
+impl<HF: HardFork, M: ModExpAlgorithm> ModExp<HF, M> {
     pub(super) const ADDRESS: Address = super::make_address(0, 5);
 
     pub fn new() -> Self {
-        Self(Default::default())
+        Self(Default::default(), Default::default())
     }
 }
 
-impl<HF: HardFork> ModExp<HF> {
-    // Note: the output of this function is bounded by 2^67
-    fn calc_iter_count(exp_len: u64, base_len: u64, bytes: &[u8]) -> Result<U256, ExitError> {
+impl<HF: HardFork, M: ModExpAlgorithm> ModExp<HF, M> {
+    /// `exp_multiplier` is the number of iterations charged per byte of the exponent after its
+    /// first 32 bytes.
+    // Note: the output of this function is bounded by 2^69
+    fn calc_iter_count(
+        exp_len: u64,
+        base_len: u64,
+        bytes: &[u8],
+        exp_multiplier: u64,
+    ) -> Result<U256, ExitError> {
         let start = usize::try_from(base_len).map_err(utils::err_usize_conv)?;
         let exp_len = usize::try_from(exp_len).map_err(utils::err_usize_conv)?;
         // #[allow(clippy::redundant_closure)]
@@ -36,7 +138,10 @@ impl<HF: HardFork> ModExp<HF> {
             Ok(U256::from(exp.bits()) - U256::from(1))
         } else {
             // else > 32
-            Ok(U256::from(8) * U256::from(exp_len - 32) + U256::from(exp.bits()) - U256::from(1))
+            Ok(
+                U256::from(exp_multiplier) * U256::from(exp_len - 32) + U256::from(exp.bits())
+                    - U256::from(1),
+            )
         }
     }
 
@@ -54,16 +159,12 @@ impl<HF: HardFork> ModExp<HF> {
 
         let mod_start = exp_end;
 
-        let base = parse_bytes(input, base_start, base_len, BigUint::from_bytes_be);
-        let exponent = parse_bytes(input, exp_start, exp_len, BigUint::from_bytes_be);
-        let modulus = parse_bytes(input, mod_start, mod_len, BigUint::from_bytes_be);
+        let base = parse_bytes(input, base_start, base_len, <[u8]>::to_vec);
+        let exponent = parse_bytes(input, exp_start, exp_len, <[u8]>::to_vec);
+        let modulus = parse_bytes(input, mod_start, mod_len, <[u8]>::to_vec);
 
         let output = {
-            let computed_result = if modulus == BigUint::from(0u32) {
-                Vec::new()
-            } else {
-                base.modpow(&exponent, &modulus).to_bytes_be()
-            };
+            let computed_result = M::modexp(&base, &exponent, &modulus);
             // The result must be the same length as the input modulus.
             // To ensure this we pad on the left with zeros.
             if mod_len > computed_result.len() {
@@ -81,7 +182,7 @@ impl<HF: HardFork> ModExp<HF> {
     }
 }
 
-impl ModExp<Byzantium> {
+impl<M: ModExpAlgorithm> ModExp<Byzantium, M> {
     // ouput of this function is bounded by 2^128
     fn mul_complexity(x: u64) -> U256 {
         if x <= 64 {
@@ -97,12 +198,12 @@ impl ModExp<Byzantium> {
     }
 }
 
-impl Precompile for ModExp<Byzantium> {
+impl<M: ModExpAlgorithm> Precompile for ModExp<Byzantium, M> {
     fn required_gas(input: &[u8]) -> Result<EthGas, ExitError> {
         let (base_len, exp_len, mod_len) = parse_lengths(input);
 
         let mul = Self::mul_complexity(core::cmp::max(mod_len, base_len));
-        let iter_count = Self::calc_iter_count(exp_len, base_len, input)?;
+        let iter_count = Self::calc_iter_count(exp_len, base_len, input, 8)?;
         // mul * iter_count bounded by 2^195 < 2^256 (no overflow)
         let gas = mul * core::cmp::max(iter_count, U256::one()) / U256::from(20);
 
@@ -130,7 +231,7 @@ impl Precompile for ModExp<Byzantium> {
     }
 }
 
-impl ModExp<Berlin> {
+impl<M: ModExpAlgorithm> ModExp<Berlin, M> {
     // output bounded by 2^122
     fn mul_complexity(base_len: u64, mod_len: u64) -> U256 {
         let max_len = core::cmp::max(mod_len, base_len);
@@ -139,12 +240,12 @@ impl ModExp<Berlin> {
     }
 }
 
-impl Precompile for ModExp<Berlin> {
+impl<M: ModExpAlgorithm> Precompile for ModExp<Berlin, M> {
     fn required_gas(input: &[u8]) -> Result<EthGas, ExitError> {
         let (base_len, exp_len, mod_len) = parse_lengths(input);
 
         let mul = Self::mul_complexity(base_len, mod_len);
-        let iter_count = Self::calc_iter_count(exp_len, base_len, input)?;
+        let iter_count = Self::calc_iter_count(exp_len, base_len, input, 8)?;
         // mul * iter_count bounded by 2^189 (so no overflow)
         let gas = mul * iter_count / U256::from(3);
 
@@ -170,6 +271,60 @@ impl Precompile for ModExp<Berlin> {
     }
 }
 
+impl<M: ModExpAlgorithm> ModExp<Osaka, M> {
+    // output bounded by 2^123
+    fn mul_complexity(base_len: u64, mod_len: u64) -> U256 {
+        let max_len = core::cmp::max(mod_len, base_len);
+        if max_len <= 32 {
+            U256::from(16)
+        } else {
+            let words = U256::from(Integer::div_ceil(&max_len, &8));
+            U256::from(2) * words * words
+        }
+    }
+}
+
+impl<M: ModExpAlgorithm> Precompile for ModExp<Osaka, M> {
+    fn required_gas(input: &[u8]) -> Result<EthGas, ExitError> {
+        let (base_len, exp_len, mod_len) = parse_lengths(input);
+
+        let mul = Self::mul_complexity(base_len, mod_len);
+        let iter_count = Self::calc_iter_count(exp_len, base_len, input, 16)?;
+        // mul * iter_count bounded by 2^192 (so no overflow)
+        let gas = mul * core::cmp::max(iter_count, U256::one());
+
+        Ok(EthGas::new(core::cmp::max(500, saturating_round(gas))))
+    }
+
+    /// See: https://eips.ethereum.org/EIPS/eip-7823
+    /// See: https://eips.ethereum.org/EIPS/eip-7883
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        let (base_len, exp_len, mod_len) = parse_lengths(input);
+        if base_len > consts::MAX_INPUT_LEN
+            || exp_len > consts::MAX_INPUT_LEN
+            || mod_len > consts::MAX_INPUT_LEN
+        {
+            return Err(ExitError::Other(Borrowed(consts::ERR_INPUT_TOO_LARGE)));
+        }
+
+        let output = Self::run_inner(input)?;
+        Ok(PrecompileOutput::without_logs(cost, output))
+    }
+}
+
 fn parse_bytes<T, F: FnOnce(&[u8]) -> T>(input: &[u8], start: usize, size: usize, f: F) -> T {
     let len = input.len();
     if start >= len {
@@ -415,6 +570,27 @@ mod tests {
         EthGas::new(87_381),
     ];
 
+    const OSAKA_GAS: [EthGas; 18] = [
+        EthGas::new(4_080),
+        EthGas::new(4_080),
+        EthGas::new(4_080),
+        EthGas::new(500),
+        EthGas::new(500),
+        EthGas::new(2_048),
+        EthGas::new(512),
+        EthGas::new(512),
+        EthGas::new(8_192),
+        EthGas::new(2_048),
+        EthGas::new(2_048),
+        EthGas::new(32_768),
+        EthGas::new(8_192),
+        EthGas::new(8_192),
+        EthGas::new(131_072),
+        EthGas::new(32_768),
+        EthGas::new(32_768),
+        EthGas::new(524_288),
+    ];
+
     #[test]
     fn test_modexp() {
         for (test, test_gas) in TESTS.iter().zip(BYZANTIUM_GAS.iter()) {
@@ -495,4 +671,72 @@ mod tests {
         let expected: Vec<u8> = Vec::new();
         assert_eq!(res.output, expected)
     }
+
+    #[test]
+    fn test_osaka_modexp_gas() {
+        for (test, test_gas) in TESTS.iter().zip(OSAKA_GAS.iter()) {
+            let input = hex::decode(&test.input).unwrap();
+
+            let gas = ModExp::<Osaka>::required_gas(&input).unwrap();
+            assert_eq!(gas, *test_gas, "{} gas", test.name);
+
+            let res = ModExp::<Osaka>::new()
+                .run(&input, Some(*test_gas), &new_context(), false)
+                .unwrap()
+                .output;
+            let expected = hex::decode(&test.expected).unwrap();
+            assert_eq!(res, expected, "{}", test.name);
+        }
+    }
+
+    #[test]
+    fn test_osaka_modexp_input_limit() {
+        let input = |base_len: u64, exp_len: u64, mod_len: u64| -> Vec<u8> {
+            let mut input: Vec<u8> = Vec::new();
+            input.extend_from_slice(&u256_to_arr(&U256::from(base_len)));
+            input.extend_from_slice(&u256_to_arr(&U256::from(exp_len)));
+            input.extend_from_slice(&u256_to_arr(&U256::from(mod_len)));
+            input.extend(core::iter::repeat(0xff).take(2_048));
+            input
+        };
+        let run = |input: &[u8]| ModExp::<Osaka>::new().run(input, None, &new_context(), false);
+
+        let output = run(&input(1_024, 1, 1_024)).unwrap().output;
+        assert_eq!(output.len(), 1_024);
+        for (base_len, exp_len, mod_len) in [(1_025, 1, 1), (1, 1_025, 1), (1, 1, 1_025)] {
+            assert_eq!(
+                run(&input(base_len, exp_len, mod_len)).unwrap_err(),
+                ExitError::Other(Borrowed(consts::ERR_INPUT_TOO_LARGE))
+            );
+        }
+    }
+
+    #[test]
+    fn test_modexp_even_modulus() {
+        let plain_modpow = |base: &BigUint, exponent: &BigUint, modulus: &BigUint| {
+            if modulus.is_zero() {
+                Vec::new()
+            } else {
+                base.modpow(exponent, modulus).to_bytes_be()
+            }
+        };
+        let base = BigUint::from_bytes_be(&[0xab; 100]);
+        let exponent = BigUint::from_bytes_be(&[0xcd; 40]);
+        let odd = BigUint::from_bytes_be(&[0x5b; 64]);
+        for k in [0u32, 1, 7, 64, 255, 1_000] {
+            for modulus in [odd.clone() << k, BigUint::one() << k] {
+                assert_eq!(
+                    AuroraModExp::modexp(
+                        &base.to_bytes_be(),
+                        &exponent.to_bytes_be(),
+                        &modulus.to_bytes_be()
+                    ),
+                    plain_modpow(&base, &exponent, &modulus),
+                    "modulus = {}",
+                    modulus
+                );
+            }
+        }
+        assert!(AuroraModExp::modexp(&[2], &[3], &[]).is_empty());
+    }
 }
@@ -1,17 +1,14 @@
 use super::{EvmPrecompileResult, Precompile};
 use crate::prelude::{
     format,
-    parameters::{PromiseArgs, PromiseCreateArgs, WithdrawCallArgs},
+    parameters::{
+        PromiseArgs, PromiseCreateArgs, PromiseWithCallbackArgs, RefundCallArgs, WithdrawCallArgs,
+    },
     sdk::io::{StorageIntermediate, IO},
     storage::{bytes_to_key, KeyPrefix},
-    types::{Address, Yocto},
+    types::{self, Address, Fee, NearGas, Yocto},
     vec, BorshSerialize, Cow, String, ToString, Vec, U256,
 };
-#[cfg(feature = "error_refund")]
-use crate::prelude::{
-    parameters::{PromiseWithCallbackArgs, RefundCallArgs},
-    types,
-};
 
 use crate::prelude::types::EthGas;
 use crate::PrecompileOutput;
@@ -32,7 +29,9 @@ mod costs {
 
     pub(super) const FT_TRANSFER_GAS: NearGas = NearGas::new(10_000_000_000_000);
 
-    #[cfg(feature = "error_refund")]
+    // TODO(#332): Determine the correct amount of gas
+    pub(super) const FT_TRANSFER_CALL_GAS: NearGas = NearGas::new(50_000_000_000_000);
+
     pub(super) const REFUND_ON_ERROR_GAS: NearGas = NearGas::new(5_000_000_000_000);
 
     // TODO(#332): Determine the correct amount of gas
@@ -40,7 +39,10 @@ mod costs {
 }
 
 pub mod events {
-    use crate::prelude::{types::Address, vec, String, ToString, H160, H256, U256};
+    use crate::prelude::{
+        types::{Address, Fee},
+        vec, String, ToString, H160, H256, U256,
+    };
 
     /// Derived from event signature (see tests::test_exit_signatures)
     pub const EXIT_TO_NEAR_SIGNATURE: H256 = crate::make_h256(
@@ -52,6 +54,11 @@ pub mod events {
         0xd046c2bb01a5622bc4b9696332391d87,
         0x491373762eeac0831c48400e2d5a5f07,
     );
+    /// Derived from event signature (see tests::test_exit_signatures)
+    pub const EXIT_TO_ETH_WITH_FEE_SIGNATURE: H256 = crate::make_h256(
+        0xd75c94ca1baf08e50dec2ccce1588da0,
+        0x4196bd33a22804f4a15103a35af5dc9c,
+    );
 
     /// The exit precompile events have an `erc20_address` field to indicate
     /// which ERC-20 token is being withdrawn. However, ETH is not an ERC-20 token
@@ -121,6 +128,41 @@ pub mod events {
         }
     }
 
+    /// ExitToEthWithFee(
+    ///    Address indexed sender,
+    ///    Address indexed erc20_address,
+    ///    string indexed dest,
+    ///    uint amount,
+    ///    uint fee
+    /// )
+    /// Emitted instead of `ExitToEth` by ETH exits which offer a relayer fee. The `fee` is
+    /// advisory: the bridge still withdraws the whole `amount` to `dest`, and relayers are
+    /// expected to settle the `fee` with the recipient on Ethereum.
+    pub struct ExitToEthWithFee {
+        pub sender: Address,
+        pub erc20_address: Address,
+        pub dest: Address,
+        pub amount: U256,
+        pub fee: Fee,
+    }
+
+    impl ExitToEthWithFee {
+        pub fn encode(self) -> ethabi::RawLog {
+            let data = ethabi::encode(&[
+                ethabi::Token::Int(self.amount),
+                ethabi::Token::Uint(self.fee.as_u128().into()),
+            ]);
+            let topics = vec![
+                EXIT_TO_ETH_WITH_FEE_SIGNATURE,
+                encode_address(self.sender),
+                encode_address(self.erc20_address),
+                encode_address(self.dest),
+            ];
+
+            ethabi::RawLog { topics, data }
+        }
+    }
+
     fn encode_address(a: Address) -> H256 {
         let mut result = [0u8; 32];
         result[12..].copy_from_slice(a.as_bytes());
@@ -184,6 +226,40 @@ pub mod events {
             anonymous: false,
         }
     }
+
+    pub fn exit_to_eth_with_fee_schema() -> ethabi::Event {
+        ethabi::Event {
+            name: "ExitToEthWithFee".to_string(),
+            inputs: vec![
+                ethabi::EventParam {
+                    name: "sender".to_string(),
+                    kind: ethabi::ParamType::Address,
+                    indexed: true,
+                },
+                ethabi::EventParam {
+                    name: "erc20_address".to_string(),
+                    kind: ethabi::ParamType::Address,
+                    indexed: true,
+                },
+                ethabi::EventParam {
+                    name: "dest".to_string(),
+                    kind: ethabi::ParamType::Address,
+                    indexed: true,
+                },
+                ethabi::EventParam {
+                    name: "amount".to_string(),
+                    kind: ethabi::ParamType::Uint(256),
+                    indexed: false,
+                },
+                ethabi::EventParam {
+                    name: "fee".to_string(),
+                    kind: ethabi::ParamType::Uint(256),
+                    indexed: false,
+                },
+            ],
+            anonymous: false,
+        }
+    }
 }
 
 //TransferEthToNear
@@ -221,6 +297,115 @@ fn get_nep141_from_erc20<I: IO>(erc20_token: &[u8], io: &I) -> Result<AccountId,
     .map_err(|_| ExitError::Other(Cow::Borrowed("ERR_INVALID_NEP141_ACCOUNT")))
 }
 
+/// Transfer of NEP-141 tokens made by the exit to NEAR precompile.
+struct FtTransfer {
+    receiver_id: AccountId,
+    /// Message passed to `ft_on_transfer` of the receiver. If it is set the transfer is
+    /// made with `ft_transfer_call`, otherwise with `ft_transfer`.
+    msg: Option<String>,
+    memo: Option<String>,
+}
+
+impl FtTransfer {
+    /// Parses the receiver of the transfer. For a plain transfer the input is the receiver
+    /// account id, while for a transfer call it is the ABI encoding of
+    /// `(string receiver_id, string msg, string memo)`, where an empty memo is omitted.
+    fn parse(input: &[u8], with_call: bool) -> Result<Self, ExitError> {
+        if !with_call {
+            return Ok(Self {
+                receiver_id: parse_receiver_id(input)?,
+                msg: None,
+                memo: None,
+            });
+        }
+
+        let mut tokens = ethabi::decode(
+            &[
+                ethabi::ParamType::String,
+                ethabi::ParamType::String,
+                ethabi::ParamType::String,
+            ],
+            input,
+        )
+        .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_INPUT")))?
+        .into_iter();
+        let mut next_string = || match tokens.next() {
+            Some(ethabi::Token::String(s)) => Ok(s),
+            _ => Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT"))),
+        };
+        let receiver_id = parse_receiver_id(next_string()?.as_bytes())?;
+        let msg = next_string()?;
+        let memo = next_string()?;
+
+        Ok(Self {
+            receiver_id,
+            msg: Some(msg),
+            memo: if memo.is_empty() { None } else { Some(memo) },
+        })
+    }
+
+    fn method(&self) -> &'static str {
+        if self.msg.is_some() {
+            "ft_transfer_call"
+        } else {
+            "ft_transfer"
+        }
+    }
+
+    fn gas(&self) -> NearGas {
+        if self.msg.is_some() {
+            costs::FT_TRANSFER_CALL_GAS
+        } else {
+            costs::FT_TRANSFER_GAS
+        }
+    }
+
+    fn args(&self, amount: u128) -> String {
+        // There is no way to inject json through the receiver and the amount, given their
+        // encoding as valid account id and decimal respectively. The memo and the message
+        // are arbitrary strings, so they are escaped.
+        let memo = match &self.memo {
+            Some(memo) => format!(r#""{}""#, json_escape(memo)),
+            None => "null".to_string(),
+        };
+        match &self.msg {
+            Some(msg) => format!(
+                r#"{{"receiver_id": "{}", "amount": "{}", "memo": {}, "msg": "{}"}}"#,
+                self.receiver_id,
+                amount,
+                memo,
+                json_escape(msg)
+            ),
+            None => format!(
+                r#"{{"receiver_id": "{}", "amount": "{}", "memo": {}}}"#,
+                self.receiver_id, amount, memo
+            ),
+        }
+    }
+}
+
+fn parse_receiver_id(input: &[u8]) -> Result<AccountId, ExitError> {
+    AccountId::try_from(input)
+        .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_RECEIVER_ACCOUNT_ID")))
+}
+
+/// Escapes a string so it can be embedded in a JSON string literal.
+fn json_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c < ' ' => escaped.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 impl<I: IO> Precompile for ExitToNear<I> {
     fn required_gas(_input: &[u8]) -> Result<EthGas, ExitError> {
         Ok(costs::EXIT_TO_NEAR_GAS)
@@ -233,17 +418,25 @@ impl<I: IO> Precompile for ExitToNear<I> {
         context: &Context,
         is_static: bool,
     ) -> EvmPrecompileResult {
-        #[cfg(feature = "error_refund")]
-        fn parse_input(input: &[u8]) -> Result<(Address, &[u8]), ExitError> {
+        fn parse_refund_address(input: &[u8]) -> Result<(Option<Address>, &[u8]), ExitError> {
             if input.len() < 21 {
                 return Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT")));
             }
             let refund_address = Address::try_from_slice(&input[1..21]).unwrap();
-            Ok((refund_address, &input[21..]))
+            Ok((Some(refund_address), &input[21..]))
+        }
+        #[cfg(feature = "error_refund")]
+        fn parse_input(input: &[u8]) -> Result<(Option<Address>, &[u8]), ExitError> {
+            parse_refund_address(input)
         }
         #[cfg(not(feature = "error_refund"))]
-        fn parse_input(input: &[u8]) -> &[u8] {
-            &input[1..]
+        fn parse_input(input: &[u8]) -> Result<(Option<Address>, &[u8]), ExitError> {
+            // Transfers made with `ft_transfer_call` always carry the address which gets
+            // the tokens not used by the receiver back.
+            match input[0] {
+                0x2 => parse_refund_address(input),
+                _ => Ok((None, &input[1..])),
+            }
         }
 
         if let Some(target_gas) = target_gas {
@@ -262,44 +455,30 @@ impl<I: IO> Precompile for ExitToNear<I> {
         // First byte of the input is a flag, selecting the behavior to be triggered:
         //      0x0 -> Eth transfer
         //      0x1 -> Erc20 transfer
+        //      0x2 -> Eth transfer with `ft_transfer_call`
+        // It is followed by the 20 bytes refund address if the `error_refund` feature is
+        // enabled, or if the transfer is made with `ft_transfer_call`.
         let flag = input[0];
-        #[cfg(feature = "error_refund")]
         let (refund_address, mut input) = parse_input(input)?;
-        #[cfg(not(feature = "error_refund"))]
-        let mut input = parse_input(input);
         let current_account_id = self.current_account_id.clone();
-        #[cfg(feature = "error_refund")]
         let refund_on_error_target = current_account_id.clone();
 
-        let (nep141_address, args, exit_event) = match flag {
-            0x0 => {
+        let (nep141_address, transfer, exit_event) = match flag {
+            0x0 | 0x2 => {
                 // ETH transfer
                 //
                 // Input slice format:
-                //      recipient_account_id (bytes) - the NEAR recipient account which will receive NEP-141 ETH tokens
-
-                if let Ok(dest_account) = AccountId::try_from(input) {
-                    (
-                        current_account_id,
-                        // There is no way to inject json, given the encoding of both arguments
-                        // as decimal and valid account id respectively.
-                        format!(
-                            r#"{{"receiver_id": "{}", "amount": "{}", "memo": null}}"#,
-                            dest_account,
-                            context.apparent_value.as_u128()
-                        ),
-                        events::ExitToNear {
-                            sender: Address::new(context.caller),
-                            erc20_address: events::ETH_ADDRESS,
-                            dest: dest_account.to_string(),
-                            amount: context.apparent_value,
-                        },
-                    )
-                } else {
-                    return Err(ExitError::Other(Cow::from(
-                        "ERR_INVALID_RECEIVER_ACCOUNT_ID",
-                    )));
-                }
+                //      0x0: recipient_account_id (bytes) - the NEAR recipient account which will receive NEP-141 ETH tokens
+                //      0x2: ABI encoded (string recipient_account_id, string msg, string memo)
+
+                let transfer = FtTransfer::parse(input, flag == 0x2)?;
+                let exit_event = events::ExitToNear {
+                    sender: Address::new(context.caller),
+                    erc20_address: events::ETH_ADDRESS,
+                    dest: transfer.receiver_id.to_string(),
+                    amount: context.apparent_value,
+                };
+                (current_account_id, transfer, exit_event)
             }
             0x1 => {
                 // ERC20 transfer
@@ -319,70 +498,60 @@ impl<I: IO> Precompile for ExitToNear<I> {
                 let erc20_address = context.caller;
                 let nep141_address = get_nep141_from_erc20(erc20_address.as_bytes(), &self.io)?;
 
+                if input.len() < 32 {
+                    return Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT")));
+                }
                 let amount = U256::from_big_endian(&input[..32]);
                 input = &input[32..];
 
-                if let Ok(receiver_account_id) = AccountId::try_from(input) {
-                    (
-                        nep141_address,
-                        // There is no way to inject json, given the encoding of both arguments
-                        // as decimal and valid account id respectively.
-                        format!(
-                            r#"{{"receiver_id": "{}", "amount": "{}", "memo": null}}"#,
-                            receiver_account_id,
-                            amount.as_u128()
-                        ),
-                        events::ExitToNear {
-                            sender: Address::new(erc20_address),
-                            erc20_address: Address::new(erc20_address),
-                            dest: receiver_account_id.to_string(),
-                            amount,
-                        },
-                    )
-                } else {
-                    return Err(ExitError::Other(Cow::from(
-                        "ERR_INVALID_RECEIVER_ACCOUNT_ID",
-                    )));
-                }
+                let transfer = FtTransfer::parse(input, false)?;
+                let exit_event = events::ExitToNear {
+                    sender: Address::new(erc20_address),
+                    erc20_address: Address::new(erc20_address),
+                    dest: transfer.receiver_id.to_string(),
+                    amount,
+                };
+                (nep141_address, transfer, exit_event)
             }
             _ => return Err(ExitError::Other(Cow::from("ERR_INVALID_FLAG"))),
         };
 
-        #[cfg(feature = "error_refund")]
-        let erc20_address = if flag == 0 {
+        let erc20_address = if flag == 0x0 || flag == 0x2 {
             None
         } else {
             Some(exit_event.erc20_address)
         };
-        #[cfg(feature = "error_refund")]
-        let refund_args = RefundCallArgs {
-            recipient_address: refund_address,
-            erc20_address,
-            amount: types::u256_to_arr(&exit_event.amount),
-        };
-        #[cfg(feature = "error_refund")]
-        let refund_promise = PromiseCreateArgs {
-            target_account_id: refund_on_error_target,
-            method: "refund_on_error".to_string(),
-            args: refund_args.try_to_vec().unwrap(),
-            attached_balance: Yocto::new(0),
-            attached_gas: costs::REFUND_ON_ERROR_GAS,
-        };
+        // The callback refunds the tokens if the transfer fails, as well as the tokens not used
+        // by the receiver of an `ft_transfer_call`.
+        let refund_promise = refund_address.map(|recipient_address| {
+            let refund_args = RefundCallArgs {
+                recipient_address,
+                erc20_address,
+                amount: types::u256_to_arr(&exit_event.amount),
+            };
+            PromiseCreateArgs {
+                target_account_id: refund_on_error_target,
+                method: "refund_on_error".to_string(),
+                args: refund_args.try_to_vec().unwrap(),
+                attached_balance: Yocto::new(0),
+                attached_gas: costs::REFUND_ON_ERROR_GAS,
+            }
+        });
         let transfer_promise = PromiseCreateArgs {
             target_account_id: nep141_address,
-            method: "ft_transfer".to_string(),
-            args: args.as_bytes().to_vec(),
+            method: transfer.method().to_string(),
+            args: transfer.args(exit_event.amount.as_u128()).into_bytes(),
             attached_balance: Yocto::new(1),
-            attached_gas: costs::FT_TRANSFER_GAS,
+            attached_gas: transfer.gas(),
         };
 
-        #[cfg(feature = "error_refund")]
-        let promise = PromiseArgs::Callback(PromiseWithCallbackArgs {
-            base: transfer_promise,
-            callback: refund_promise,
-        });
-        #[cfg(not(feature = "error_refund"))]
-        let promise = PromiseArgs::Create(transfer_promise);
+        let promise = match refund_promise {
+            Some(callback) => PromiseArgs::Callback(PromiseWithCallbackArgs {
+                base: transfer_promise,
+                callback,
+            }),
+            None => PromiseArgs::Create(transfer_promise),
+        };
 
         let promise_log = Log {
             address: exit_to_near::ADDRESS.raw(),
@@ -428,6 +597,29 @@ impl<I> ExitToEthereum<I> {
     }
 }
 
+/// Splits the relayer fee off the end of the input of an exit to Ethereum, if the flag
+/// of the exit says it has one. The fee is only reported in the exit event, but as it is
+/// offered out of the exited amount it cannot exceed it.
+fn split_relayer_fee(
+    input: &[u8],
+    with_fee: bool,
+    amount: U256,
+) -> Result<(&[u8], Option<Fee>), ExitError> {
+    if !with_fee {
+        return Ok((input, None));
+    }
+    if input.len() < 32 {
+        return Err(ExitError::Other(Cow::from("ERR_INVALID_FEE")));
+    }
+
+    let (input, fee) = input.split_at(input.len() - 32);
+    let fee = U256::from_big_endian(fee);
+    if fee > amount || fee > U256::from(u128::MAX) {
+        return Err(ExitError::Other(Cow::from("ERR_INVALID_FEE")));
+    }
+    Ok((input, Some(Fee::from(fee.as_u128()))))
+}
+
 impl<I: IO> Precompile for ExitToEthereum<I> {
     fn required_gas(_input: &[u8]) -> Result<EthGas, ExitError> {
         Ok(costs::EXIT_TO_ETHEREUM_GAS)
@@ -457,16 +649,19 @@ impl<I: IO> Precompile for ExitToEthereum<I> {
         // First byte of the input is a flag, selecting the behavior to be triggered:
         //      0x0 -> Eth transfer
         //      0x1 -> Erc20 transfer
+        //      0x2 -> Eth transfer with (advisory) relayer fee
         let mut input = input;
         let flag = input[0];
         input = &input[1..];
 
-        let (nep141_address, serialized_args, exit_event) = match flag {
-            0x0 => {
+        let (nep141_address, serialized_args, exit_event, fee) = match flag {
+            0x0 | 0x2 => {
                 // ETH transfer
                 //
                 // Input slice format:
                 //      eth_recipient (20 bytes) - the address of recipient which will receive ETH on Ethereum
+                //      fee (U256 big-endian bytes, 0x2 only) - the relayer fee, out of the attached ETH
+                let (input, fee) = split_relayer_fee(input, flag == 0x2, context.apparent_value)?;
                 let recipient_address: Address = input
                     .try_into()
                     .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_RECIPIENT_ADDRESS")))?;
@@ -486,6 +681,7 @@ impl<I: IO> Precompile for ExitToEthereum<I> {
                         dest: recipient_address,
                         amount: context.apparent_value,
                     },
+                    fee,
                 )
             }
             0x1 => {
@@ -507,8 +703,11 @@ impl<I: IO> Precompile for ExitToEthereum<I> {
                 let erc20_address = context.caller;
                 let nep141_address = get_nep141_from_erc20(erc20_address.as_bytes(), &self.io)?;
 
+                if input.len() < 32 {
+                    return Err(ExitError::Other(Cow::from("ERR_INVALID_AMOUNT")));
+                }
                 let amount = U256::from_big_endian(&input[..32]);
-                input = &input[32..];
+                let input = &input[32..];
 
                 if input.len() == 20 {
                     // Parse ethereum address in hex
@@ -534,6 +733,7 @@ impl<I: IO> Precompile for ExitToEthereum<I> {
                             dest: recipient_address,
                             amount,
                         },
+                        None,
                     )
                 } else {
                     return Err(ExitError::Other(Cow::from("ERR_INVALID_RECIPIENT_ADDRESS")));
@@ -560,7 +760,17 @@ impl<I: IO> Precompile for ExitToEthereum<I> {
             topics: Vec::new(),
             data: promise,
         };
-        let exit_event_log = exit_event.encode();
+        let exit_event_log = match fee {
+            Some(fee) => events::ExitToEthWithFee {
+                sender: exit_event.sender,
+                erc20_address: exit_event.erc20_address,
+                dest: exit_event.dest,
+                amount: exit_event.amount,
+                fee,
+            }
+            .encode(),
+            None => exit_event.encode(),
+        };
         let exit_event_log = Log {
             address: exit_to_ethereum::ADDRESS.raw(),
             topics: exit_event_log.topics,
@@ -576,8 +786,10 @@ impl<I: IO> Precompile for ExitToEthereum<I> {
 
 #[cfg(test)]
 mod tests {
-    use super::{exit_to_ethereum, exit_to_near};
+    use super::{exit_to_ethereum, exit_to_near, json_escape, split_relayer_fee, FtTransfer};
     use crate::prelude::sdk::types::near_account_to_evm_address;
+    use crate::prelude::types::Fee;
+    use crate::prelude::{String, Vec, U256};
 
     #[test]
     fn test_precompile_id() {
@@ -595,6 +807,7 @@ mod tests {
     fn test_exit_signatures() {
         let exit_to_near = super::events::exit_to_near_schema();
         let exit_to_eth = super::events::exit_to_eth_schema();
+        let exit_to_eth_with_fee = super::events::exit_to_eth_with_fee_schema();
 
         assert_eq!(
             exit_to_near.signature(),
@@ -604,5 +817,83 @@ mod tests {
             exit_to_eth.signature(),
             super::events::EXIT_TO_ETH_SIGNATURE
         );
+        assert_eq!(
+            exit_to_eth_with_fee.signature(),
+            super::events::EXIT_TO_ETH_WITH_FEE_SIGNATURE
+        );
+    }
+
+    #[test]
+    fn test_json_escape() {
+        assert_eq!(json_escape("plain"), "plain");
+        assert_eq!(json_escape(r#"{"a": "b\c"}"#), r#"{\"a\": \"b\\c\"}"#);
+        assert_eq!(json_escape("a\nb\u{1}"), "a\\nb\\u0001");
+    }
+
+    #[test]
+    fn test_ft_transfer_args() {
+        let transfer = FtTransfer::parse(b"bob.near", false).unwrap();
+        assert_eq!(transfer.method(), "ft_transfer");
+        assert_eq!(
+            transfer.args(100),
+            r#"{"receiver_id": "bob.near", "amount": "100", "memo": null}"#
+        );
+
+        let input = ethabi::encode(&[
+            ethabi::Token::String("pool.near".into()),
+            ethabi::Token::String(r#"{"action": "swap"}"#.into()),
+            ethabi::Token::String(String::new()),
+        ]);
+        let transfer = FtTransfer::parse(&input, true).unwrap();
+        assert_eq!(transfer.method(), "ft_transfer_call");
+        assert_eq!(
+            transfer.args(100),
+            r#"{"receiver_id": "pool.near", "amount": "100", "memo": null, "msg": "{\"action\": \"swap\"}"}"#
+        );
+
+        let input = ethabi::encode(&[
+            ethabi::Token::String("pool.near".into()),
+            ethabi::Token::String(String::new()),
+            ethabi::Token::String(r#"", "amount": "1000"#.into()),
+        ]);
+        let transfer = FtTransfer::parse(&input, true).unwrap();
+        assert_eq!(
+            transfer.args(100),
+            r#"{"receiver_id": "pool.near", "amount": "100", "memo": "\", \"amount\": \"1000", "msg": ""}"#
+        );
+
+        let input = ethabi::encode(&[
+            ethabi::Token::String("Invalid Account".into()),
+            ethabi::Token::String(String::new()),
+            ethabi::Token::String(String::new()),
+        ]);
+        assert!(FtTransfer::parse(&input, true).is_err());
+        assert!(FtTransfer::parse(b"pool.near", true).is_err());
+    }
+
+    #[test]
+    fn test_split_relayer_fee() {
+        let recipient = [0x11u8; 20];
+        let amount = U256::from(1_000);
+        let fee = |fee: u64| -> Vec<u8> {
+            let mut bytes = [0u8; 32];
+            U256::from(fee).to_big_endian(&mut bytes);
+            [&recipient[..], &bytes].concat()
+        };
+
+        assert_eq!(
+            split_relayer_fee(&recipient, false, amount).unwrap(),
+            (&recipient[..], None)
+        );
+        assert_eq!(
+            split_relayer_fee(&fee(25), true, amount).unwrap(),
+            (&recipient[..], Some(Fee::from(25)))
+        );
+        assert_eq!(
+            split_relayer_fee(&fee(1_000), true, amount).unwrap(),
+            (&recipient[..], Some(Fee::from(1_000)))
+        );
+        assert!(split_relayer_fee(&fee(1_001), true, amount).is_err());
+        assert!(split_relayer_fee(&recipient, true, amount).is_err());
     }
 }
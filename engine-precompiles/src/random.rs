@@ -1,7 +1,8 @@
 use super::{EvmPrecompileResult, Precompile};
 use crate::prelude::types::{Address, EthGas};
-use crate::prelude::H256;
+use crate::prelude::{sdk, H256};
 use crate::PrecompileOutput;
+use core::cell::Cell;
 use evm::{Context, ExitError};
 
 mod costs {
@@ -9,6 +10,9 @@ mod costs {
 
     // TODO(#483): Determine the correct amount of gas
     pub(super) const RANDOM_BYTES_GAS: EthGas = EthGas::new(0);
+
+    /// Priced like `KECCAK256` of the 92 bytes being hashed.
+    pub(super) const TRANSACTION_RANDOM_BYTES_GAS: EthGas = EthGas::new(48);
 }
 
 pub struct RandomSeed {
@@ -56,10 +60,82 @@ impl Precompile for RandomSeed {
     }
 }
 
+pub struct TransactionRandomSeed {
+    random_seed: H256,
+    transaction_hash: H256,
+    counter: Cell<u64>,
+}
+
+impl TransactionRandomSeed {
+    /// Per-transaction random bytes precompile address
+    /// Unlike `RandomSeed`, every call returns a different value: the block entropy source
+    /// is hashed together with the transaction hash, the caller and the number of previous
+    /// calls to this precompile in the transaction. Receipts without an Ethereum transaction
+    /// use a hash of their input, predecessor and the NEAR random seed as transaction hash.
+    /// Values remain unpredictable until the transaction is included in a block.
+    ///
+    /// Address: `0x8bb64ec9405c02f7398a1a5f85175288d3dbdd46`
+    /// This address is computed as: `&keccak("transactionRandomSeed")[12..]`
+    pub const ADDRESS: Address =
+        super::make_address(0x8bb64ec9, 0x405c02f7398a1a5f85175288d3dbdd46);
+
+    pub fn new(random_seed: H256, transaction_hash: H256) -> Self {
+        Self {
+            random_seed,
+            transaction_hash,
+            counter: Cell::new(0),
+        }
+    }
+
+    fn derive(&self, caller: &[u8], counter: u64) -> H256 {
+        let data = [
+            self.random_seed.as_bytes(),
+            self.transaction_hash.as_bytes(),
+            caller,
+            &counter.to_be_bytes(),
+        ]
+        .concat();
+        sdk::keccak(&data)
+    }
+}
+
+impl Precompile for TransactionRandomSeed {
+    fn required_gas(_input: &[u8]) -> Result<EthGas, ExitError> {
+        Ok(costs::TRANSACTION_RANDOM_BYTES_GAS)
+    }
+
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        let counter = self.counter.get();
+        self.counter.set(counter + 1);
+        let random_bytes = self.derive(context.caller.as_bytes(), counter);
+
+        Ok(PrecompileOutput::without_logs(
+            cost,
+            random_bytes.as_bytes().to_vec(),
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::prelude::sdk::types::near_account_to_evm_address;
-    use crate::random::RandomSeed;
+    use crate::prelude::{H160, H256, U256};
+    use crate::random::{RandomSeed, TransactionRandomSeed};
+    use crate::Precompile;
+    use evm::Context;
 
     #[test]
     fn test_precompile_id() {
@@ -67,5 +143,45 @@ mod tests {
             RandomSeed::ADDRESS,
             near_account_to_evm_address("randomSeed".as_bytes())
         );
+        assert_eq!(
+            TransactionRandomSeed::ADDRESS,
+            near_account_to_evm_address("transactionRandomSeed".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_transaction_random_seed() {
+        let run = |precompile: &TransactionRandomSeed, caller: H160| -> Vec<u8> {
+            let context = Context {
+                address: TransactionRandomSeed::ADDRESS.raw(),
+                caller,
+                apparent_value: U256::zero(),
+            };
+            precompile.run(&[], None, &context, false).unwrap().output
+        };
+        let random_seed = H256([7; 32]);
+        let alice = H160([1; 20]);
+        let bob = H160([2; 20]);
+
+        let precompile = TransactionRandomSeed::new(random_seed, H256([3; 32]));
+        let first = run(&precompile, alice);
+        let second = run(&precompile, alice);
+        let third = run(&precompile, bob);
+        assert_ne!(first, second);
+        assert_ne!(second, third);
+        assert_ne!(first, random_seed.as_bytes());
+
+        let expected = [
+            random_seed.as_bytes(),
+            &[3; 32],
+            bob.as_bytes(),
+            &2u64.to_be_bytes(),
+        ]
+        .concat();
+        assert_eq!(third, crate::prelude::sdk::keccak(&expected).as_bytes());
+
+        // Another transaction in the same block gets different values
+        let precompile = TransactionRandomSeed::new(random_seed, H256([4; 32]));
+        assert_ne!(run(&precompile, alice), first);
     }
 }
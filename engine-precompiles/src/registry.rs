@@ -0,0 +1,159 @@
+//! Per-deployment overrides of the precompiles installed by `Precompiles`.
+//!
+//! The registry is owner-managed and stored by the engine. Precompiles are identified by
+//! their default address; precompiles without an entry keep the default behaviour.
+
+use crate::native::{exit_to_ethereum, exit_to_near};
+use crate::prelude::types::Address;
+use crate::prelude::{BTreeMap, BorshDeserialize, BorshSerialize};
+use crate::xcc::cross_contract_call;
+
+pub mod error {
+    pub const ERR_PRECOMPILE_NOT_REMAPPABLE: &[u8; 29] = b"ERR_PRECOMPILE_NOT_REMAPPABLE";
+    pub const ERR_CORRUPTED_STORAGE: &str = "ERR_CORRUPTED_REGISTRY_STORAGE";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum PrecompileStatus {
+    /// Installed at its default address.
+    Enabled,
+    /// Not installed at all.
+    Disabled,
+    /// Installed at the given address instead of its default one.
+    Remapped(Address),
+}
+
+impl Default for PrecompileStatus {
+    fn default() -> Self {
+        Self::Enabled
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistryError {
+    /// The precompile checks its own address or holds funds at it, so it can only be
+    /// enabled or disabled.
+    NotRemappable(Address),
+}
+
+impl AsRef<[u8]> for RegistryError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::NotRemappable(_) => error::ERR_PRECOMPILE_NOT_REMAPPABLE,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct PrecompileRegistry {
+    overrides: BTreeMap<Address, PrecompileStatus>,
+}
+
+impl PrecompileRegistry {
+    /// Status of the precompile with the given default address.
+    pub fn status(&self, address: &Address) -> PrecompileStatus {
+        self.overrides.get(address).copied().unwrap_or_default()
+    }
+
+    pub fn set_status(
+        &mut self,
+        address: Address,
+        status: PrecompileStatus,
+    ) -> Result<(), RegistryError> {
+        match status {
+            PrecompileStatus::Enabled => {
+                self.overrides.remove(&address);
+            }
+            PrecompileStatus::Remapped(_) if !is_remappable(&address) => {
+                return Err(RegistryError::NotRemappable(address));
+            }
+            PrecompileStatus::Disabled | PrecompileStatus::Remapped(_) => {
+                self.overrides.insert(address, status);
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the overrides to a map of precompiles keyed by their default address.
+    /// All precompiles are removed before any is reinserted, so remapped precompiles can
+    /// swap addresses. A remapped precompile replaces whatever is installed at its new address.
+    pub(crate) fn apply<P>(&self, precompiles: &mut BTreeMap<Address, P>) {
+        let mut remapped = crate::prelude::Vec::new();
+        for (address, status) in self.overrides.iter() {
+            match status {
+                PrecompileStatus::Enabled => (),
+                PrecompileStatus::Disabled => {
+                    precompiles.remove(address);
+                }
+                PrecompileStatus::Remapped(new_address) => {
+                    if let Some(precompile) = precompiles.remove(address) {
+                        remapped.push((*new_address, precompile));
+                    }
+                }
+            }
+        }
+        precompiles.extend(remapped);
+    }
+}
+
+/// The exit and cross-contract call precompiles reject calls where the context address
+/// is not their default one, and the engine identifies their logs by that address.
+fn is_remappable(address: &Address) -> bool {
+    *address != exit_to_near::ADDRESS
+        && *address != exit_to_ethereum::ADDRESS
+        && *address != cross_contract_call::ADDRESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PrecompileRegistry, PrecompileStatus, RegistryError};
+    use crate::native::exit_to_near;
+    use crate::prelude::types::Address;
+    use crate::prelude::BTreeMap;
+
+    #[test]
+    fn test_apply_registry() {
+        let a = Address::from_array([1; 20]);
+        let b = Address::from_array([2; 20]);
+        let c = Address::from_array([3; 20]);
+        let mut precompiles: BTreeMap<Address, &str> =
+            [(a, "a"), (b, "b"), (c, "c")].into_iter().collect();
+
+        let mut registry = PrecompileRegistry::default();
+        registry.set_status(a, PrecompileStatus::Disabled).unwrap();
+        registry
+            .set_status(b, PrecompileStatus::Remapped(c))
+            .unwrap();
+        registry
+            .set_status(c, PrecompileStatus::Remapped(b))
+            .unwrap();
+        registry.apply(&mut precompiles);
+
+        let expected: BTreeMap<Address, &str> = [(b, "c"), (c, "b")].into_iter().collect();
+        assert_eq!(precompiles, expected);
+
+        // Re-enabling restores the default
+        registry.set_status(a, PrecompileStatus::Enabled).unwrap();
+        assert_eq!(registry.status(&a), PrecompileStatus::Enabled);
+    }
+
+    #[test]
+    fn test_exit_precompiles_not_remappable() {
+        let mut registry = PrecompileRegistry::default();
+        let new_address = Address::from_array([1; 20]);
+        assert_eq!(
+            registry.set_status(
+                exit_to_near::ADDRESS,
+                PrecompileStatus::Remapped(new_address)
+            ),
+            Err(RegistryError::NotRemappable(exit_to_near::ADDRESS))
+        );
+        registry
+            .set_status(exit_to_near::ADDRESS, PrecompileStatus::Disabled)
+            .unwrap();
+        assert_eq!(
+            registry.status(&exit_to_near::ADDRESS),
+            PrecompileStatus::Disabled
+        );
+    }
+}
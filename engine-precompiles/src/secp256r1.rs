@@ -0,0 +1,146 @@
+use crate::prelude::types::{Address, EthGas};
+use crate::prelude::Vec;
+use crate::{EvmPrecompileResult, Precompile, PrecompileOutput};
+use evm::{Context, ExitError};
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use p256::{EncodedPoint, FieldBytes};
+
+mod costs {
+    use crate::prelude::types::EthGas;
+
+    pub(super) const P256VERIFY_BASE: EthGas = EthGas::new(3_450);
+}
+
+mod consts {
+    /// Message hash, signature `(r, s)` and public key `(x, y)`, 32 bytes each.
+    pub(super) const INPUT_LEN: usize = 160;
+    pub(super) const OUTPUT_LEN: usize = 32;
+}
+
+/// Verifies a P-256 signature of a message hash. Returns `false` for malformed
+/// signatures and public keys which are not on the curve.
+fn verify(hash: &[u8], r: &[u8], s: &[u8], x: &[u8], y: &[u8]) -> bool {
+    let point = EncodedPoint::from_affine_coordinates(x.into(), y.into(), false);
+    let verifying_key = match VerifyingKey::from_encoded_point(&point) {
+        Ok(key) => key,
+        Err(_) => return false,
+    };
+    let signature =
+        match Signature::from_scalars(*FieldBytes::from_slice(r), *FieldBytes::from_slice(s)) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+
+    verifying_key.verify_prehash(hash, &signature).is_ok()
+}
+
+pub struct P256Verify;
+
+impl P256Verify {
+    pub(super) const ADDRESS: Address = super::make_address(0, 0x100);
+}
+
+impl Precompile for P256Verify {
+    fn required_gas(_input: &[u8]) -> Result<EthGas, ExitError> {
+        Ok(costs::P256VERIFY_BASE)
+    }
+
+    /// Returns 32 bytes with the last one set to `1` for a valid signature. Invalid
+    /// signatures and inputs of the wrong length return no data rather than failing.
+    ///
+    /// See: https://github.com/ethereum/RIPs/blob/master/RIPS/rip-7212.md
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        if input.len() != consts::INPUT_LEN {
+            return Ok(PrecompileOutput::without_logs(cost, Vec::new()));
+        }
+
+        let output = if verify(
+            &input[0..32],
+            &input[32..64],
+            &input[64..96],
+            &input[96..128],
+            &input[128..160],
+        ) {
+            let mut output = [0u8; consts::OUTPUT_LEN];
+            output[consts::OUTPUT_LEN - 1] = 1;
+            output.to_vec()
+        } else {
+            Vec::new()
+        };
+
+        Ok(PrecompileOutput::without_logs(cost, output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::new_context;
+
+    const VALID_INPUT: &str = "\
+        4cee90eb86eaa050036147a12d49004b6b9c72bd725d39d4785011fe190f0b4d\
+        a73bd4903f0ce3b639bbbf6e8e80d16931ff4bcf5993d58468e8fb19086e8cac\
+        36dbcd03009df8c59286b162af3bd7fcc0450c9aa81be5d10d312af6c66b1d60\
+        4aebd3099c618202fcfe16ae7770b0c49ab5eadf74b754204a3bb6060e44eff3\
+        7618b065f9832de4ca6ca971a7a1adc826d0f7c00181a5fb2ddf79ae00b4e10e";
+
+    fn run(input: &[u8]) -> Vec<u8> {
+        P256Verify
+            .run(input, Some(EthGas::new(3_450)), &new_context(), false)
+            .unwrap()
+            .output
+    }
+
+    #[test]
+    fn test_p256_verify() {
+        let input = hex::decode(VALID_INPUT).unwrap();
+        let mut expected = vec![0u8; 32];
+        expected[31] = 1;
+        assert_eq!(run(&input), expected);
+    }
+
+    #[test]
+    fn test_p256_verify_invalid() {
+        let input = hex::decode(VALID_INPUT).unwrap();
+
+        // wrong message hash
+        let mut wrong_hash = input.clone();
+        wrong_hash[0] ^= 1;
+        assert!(run(&wrong_hash).is_empty());
+
+        // zero signature
+        let mut zero_signature = input.clone();
+        zero_signature[32..96].copy_from_slice(&[0u8; 64]);
+        assert!(run(&zero_signature).is_empty());
+
+        // public key not on the curve
+        let mut invalid_key = input.clone();
+        invalid_key[159] ^= 1;
+        assert!(run(&invalid_key).is_empty());
+
+        // wrong input length
+        assert!(run(&input[..159]).is_empty());
+        assert!(run(&[input.clone(), vec![0]].concat()).is_empty());
+    }
+
+    #[test]
+    fn test_p256_verify_out_of_gas() {
+        let input = hex::decode(VALID_INPUT).unwrap();
+        let res = P256Verify.run(&input, Some(EthGas::new(3_449)), &new_context(), false);
+        assert!(matches!(res, Err(ExitError::OutOfGas)));
+    }
+}
@@ -0,0 +1,448 @@
+//! ERC-20 interface over the native ETH balances of the engine, so contracts can handle ETH
+//! like any other token without wrapping it first.
+//!
+//! Balances are the native balances, and transfers move native ETH through the EVM (the
+//! precompile calls itself with a value transfer, which does nothing else). Allowances are
+//! the EVM storage of the precompile, which the engine keeps under `KeyPrefix::EthAllowance`.
+//!
+//! The precompile has no access to the state of the EVM, so it reads balances and reads or
+//! writes allowances by running the code at `STATE_ADDRESS` in its own context. The state is
+//! then that of the current transaction, and allowance changes are reverted along with the
+//! call which made them.
+
+use crate::prelude::types::{Address, EthGas};
+use crate::prelude::{
+    storage::{bytes_to_key, KeyPrefix},
+    vec, Borrowed, String, Vec, H160, H256, U256,
+};
+use crate::{HandleBasedPrecompile, PrecompileOutput};
+use ethabi::{ParamType, Token};
+use evm::backend::Log;
+use evm::executor::stack::{PrecompileFailure, PrecompileHandle};
+use evm::{Context, ExitError, ExitRevert, Transfer};
+
+/// wrapped_eth precompile address
+///
+/// Address: `0x1a9b67b4e38565d272c13698efaa3bfcf1e0881b`
+/// This address is computed as: `&keccak("wrappedEth")[12..]`
+pub const ADDRESS: Address = crate::make_address(0x1a9b67b4, 0xe38565d272c13698efaa3bfcf1e0881b);
+
+/// Address of the code the precompile runs to access the EVM state (see `STATE_CODE`). The
+/// engine serves the code for this address, it is not stored.
+///
+/// Address: `0xc470ab25f372ae6ad62015876f3a530f63997534`
+/// This address is computed as: `&keccak("wrappedEthState")[12..]`
+pub const STATE_ADDRESS: Address =
+    crate::make_address(0xc470ab25, 0xf372ae6ad62015876f3a530f63997534);
+
+/// Code run by the precompile in its own context to access the EVM state. The input is three
+/// words `(op, arg, value)`:
+/// - op 0 returns `BALANCE(arg)`,
+/// - op 1 returns `SLOAD(arg)`,
+/// - any other op does `SSTORE(arg, value)`.
+///
+/// It only ever touches the storage of the context it runs in, so calling it directly is harmless.
+pub const STATE_CODE: [u8; 44] = [
+    // PUSH1 0x20 CALLDATALOAD PUSH1 0x00 CALLDATALOAD (stack: op, arg)
+    0x60, 0x20, 0x35, 0x60, 0x00, 0x35,
+    // DUP1 ISZERO PUSH1 0x17 JUMPI PUSH1 0x01 EQ PUSH1 0x22 JUMPI
+    0x80, 0x15, 0x60, 0x17, 0x57, 0x60, 0x01, 0x14, 0x60, 0x22, 0x57,
+    // PUSH1 0x40 CALLDATALOAD SWAP1 SSTORE STOP
+    0x60, 0x40, 0x35, 0x90, 0x55, 0x00,
+    // 0x17: JUMPDEST POP BALANCE PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+    0x5b, 0x50, 0x31, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+    // 0x22: JUMPDEST SLOAD PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+    0x5b, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+];
+
+mod costs {
+    use crate::prelude::types::EthGas;
+
+    /// Constant values, priced like a warm `SLOAD`. Also charged for the functions reading or
+    /// writing the state, on top of the gas used by `STATE_CODE`.
+    pub(super) const METADATA: EthGas = EthGas::new(100);
+    /// Priced like a `CALL` transferring value.
+    pub(super) const VALUE_TRANSFER: EthGas = EthGas::new(9_000);
+    /// A `LOG3` with one word of data.
+    pub(super) const EVENT: EthGas = EthGas::new(1_756);
+}
+
+/// Selectors of the ERC-20 functions of the precompile.
+pub mod selectors {
+    /// `name() returns (string)`
+    pub const NAME: [u8; 4] = [0x06, 0xfd, 0xde, 0x03];
+    /// `symbol() returns (string)`
+    pub const SYMBOL: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+    /// `decimals() returns (uint8)`
+    pub const DECIMALS: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+    /// `balanceOf(address) returns (uint256)`
+    pub const BALANCE_OF: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+    /// `allowance(address,address) returns (uint256)`
+    pub const ALLOWANCE: [u8; 4] = [0xdd, 0x62, 0xed, 0x3e];
+    /// `transfer(address,uint256) returns (bool)`
+    pub const TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+    /// `approve(address,uint256) returns (bool)`
+    pub const APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+    /// `transferFrom(address,address,uint256) returns (bool)`
+    pub const TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+}
+
+/// Topics of the ERC-20 events emitted by the precompile.
+pub mod events {
+    use crate::prelude::H256;
+
+    /// `Transfer(address indexed from, address indexed to, uint256 value)`
+    pub const TRANSFER: H256 = crate::make_h256(
+        0xddf252ad1be2c89b69c2b068fc378daa,
+        0x952ba7f163c4a11628f55a4df523b3ef,
+    );
+    /// `Approval(address indexed owner, address indexed spender, uint256 value)`
+    pub const APPROVAL: H256 = crate::make_h256(
+        0x8c5be1e5ebec7d5bd14f71427d1e84f3,
+        0xdd0314c0f7b2291e5b200ac8c7c3b925,
+    );
+}
+
+mod consts {
+    pub(super) const NAME: &str = "Ether";
+    pub(super) const SYMBOL: &str = "ETH";
+    pub(super) const DECIMALS: u8 = 18;
+
+    pub(super) const ERR_INVALID_SELECTOR: &str = "ERR_WRAPPED_ETH_INVALID_SELECTOR";
+    pub(super) const ERR_INVALID_INPUT: &str = "ERR_WRAPPED_ETH_INVALID_INPUT";
+    pub(super) const ERR_STATIC: &str = "ERR_WRAPPED_ETH_STATIC";
+    pub(super) const ERR_DELEGATE: &str = "ERR_WRAPPED_ETH_DELEGATE";
+    pub(super) const ERR_ATTACHED_VALUE: &str = "ERR_WRAPPED_ETH_ATTACHED_VALUE";
+    pub(super) const ERR_INSUFFICIENT_BALANCE: &str = "ERR_WRAPPED_ETH_INSUFFICIENT_BALANCE";
+    pub(super) const ERR_INSUFFICIENT_ALLOWANCE: &str = "ERR_WRAPPED_ETH_INSUFFICIENT_ALLOWANCE";
+
+    /// Operations of `STATE_CODE`.
+    pub(super) const OP_BALANCE: u8 = 0;
+    pub(super) const OP_SLOAD: u8 = 1;
+    pub(super) const OP_SSTORE: u8 = 2;
+}
+
+/// ERC-20 token whose balances are the native ETH balances.
+pub struct WrappedEth;
+
+impl WrappedEth {
+    fn balance_of(
+        handle: &mut dyn PrecompileHandle,
+        address: &Address,
+    ) -> Result<U256, PrecompileFailure> {
+        let arg = H256::from(address.raw());
+        access_state(handle, consts::OP_BALANCE, arg, U256::zero())
+    }
+
+    fn allowance(
+        handle: &mut dyn PrecompileHandle,
+        owner: &Address,
+        spender: &Address,
+    ) -> Result<U256, PrecompileFailure> {
+        let slot = allowance_slot(owner, spender);
+        access_state(handle, consts::OP_SLOAD, slot, U256::zero())
+    }
+
+    fn set_allowance(
+        handle: &mut dyn PrecompileHandle,
+        owner: &Address,
+        spender: &Address,
+        amount: U256,
+    ) -> Result<(), PrecompileFailure> {
+        let slot = allowance_slot(owner, spender);
+        access_state(handle, consts::OP_SSTORE, slot, amount)?;
+        Ok(())
+    }
+}
+
+impl HandleBasedPrecompile for WrappedEth {
+    fn run_with_handle(
+        &self,
+        handle: &mut dyn PrecompileHandle,
+    ) -> Result<PrecompileOutput, PrecompileFailure> {
+        let input = handle.input().to_vec();
+        let context = handle.context().clone();
+        let address = handle.code_address();
+
+        // The value transfer of `transfer` and `transferFrom` is a call to the precompile in
+        // the context of the recipient, with no input.
+        if input.is_empty() && context.address != address {
+            return Ok(PrecompileOutput::default());
+        }
+
+        if input.len() < 4 {
+            return Err(ExitError::Other(Borrowed(consts::ERR_INVALID_SELECTOR)).into());
+        }
+        let (selector, args) = input.split_at(4);
+        let cost = match selector {
+            s if s == selectors::NAME
+                || s == selectors::SYMBOL
+                || s == selectors::DECIMALS
+                || s == selectors::BALANCE_OF
+                || s == selectors::ALLOWANCE =>
+            {
+                costs::METADATA
+            }
+            s if s == selectors::TRANSFER => costs::VALUE_TRANSFER + costs::EVENT,
+            s if s == selectors::APPROVE => costs::METADATA + costs::EVENT,
+            s if s == selectors::TRANSFER_FROM => {
+                costs::METADATA + costs::VALUE_TRANSFER + costs::EVENT
+            }
+            _ => return Err(ExitError::Other(Borrowed(consts::ERR_INVALID_SELECTOR)).into()),
+        };
+        if let Some(target_gas) = handle.gas_limit().map(EthGas::new) {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas.into());
+            }
+        }
+
+        if context.address != address {
+            return Err(revert_with_message(consts::ERR_DELEGATE));
+        } else if !context.apparent_value.is_zero() {
+            // ETH sent to the precompile itself could never be moved again.
+            return Err(revert_with_message(consts::ERR_ATTACHED_VALUE));
+        }
+
+        let caller = context.caller;
+        let (token, logs) = if selector == selectors::NAME {
+            (Token::String(String::from(consts::NAME)), Vec::new())
+        } else if selector == selectors::SYMBOL {
+            (Token::String(String::from(consts::SYMBOL)), Vec::new())
+        } else if selector == selectors::DECIMALS {
+            (Token::Uint(consts::DECIMALS.into()), Vec::new())
+        } else if selector == selectors::BALANCE_OF {
+            let owner = match decode_args(&[ParamType::Address], args)?.as_slice() {
+                [Token::Address(owner)] => Address::new(*owner),
+                _ => return Err(invalid_input()),
+            };
+            (Token::Uint(Self::balance_of(handle, &owner)?), Vec::new())
+        } else if selector == selectors::ALLOWANCE {
+            let types = [ParamType::Address, ParamType::Address];
+            let (owner, spender) = match decode_args(&types, args)?.as_slice() {
+                [Token::Address(owner), Token::Address(spender)] => {
+                    (Address::new(*owner), Address::new(*spender))
+                }
+                _ => return Err(invalid_input()),
+            };
+            (
+                Token::Uint(Self::allowance(handle, &owner, &spender)?),
+                Vec::new(),
+            )
+        } else {
+            if handle.is_static() {
+                return Err(revert_with_message(consts::ERR_STATIC));
+            }
+            let log = if selector == selectors::TRANSFER {
+                let types = [ParamType::Address, ParamType::Uint(256)];
+                let (to, amount) = match decode_args(&types, args)?.as_slice() {
+                    [Token::Address(to), Token::Uint(amount)] => (*to, *amount),
+                    _ => return Err(invalid_input()),
+                };
+                transfer_value(handle, address, caller, to, amount)?;
+                event(address, events::TRANSFER, caller, to, amount)
+            } else if selector == selectors::APPROVE {
+                let types = [ParamType::Address, ParamType::Uint(256)];
+                let (spender, amount) = match decode_args(&types, args)?.as_slice() {
+                    [Token::Address(spender), Token::Uint(amount)] => (*spender, *amount),
+                    _ => return Err(invalid_input()),
+                };
+                Self::set_allowance(
+                    handle,
+                    &Address::new(caller),
+                    &Address::new(spender),
+                    amount,
+                )?;
+                event(address, events::APPROVAL, caller, spender, amount)
+            } else {
+                let types = [ParamType::Address, ParamType::Address, ParamType::Uint(256)];
+                let (from, to, amount) = match decode_args(&types, args)?.as_slice() {
+                    [Token::Address(from), Token::Address(to), Token::Uint(amount)] => {
+                        (*from, *to, *amount)
+                    }
+                    _ => return Err(invalid_input()),
+                };
+                let (owner, spender) = (Address::new(from), Address::new(caller));
+                // Spending one's own balance needs no allowance.
+                let allowance = if from == caller {
+                    U256::MAX
+                } else {
+                    Self::allowance(handle, &owner, &spender)?
+                };
+                if allowance < amount {
+                    return Err(revert_with_message(consts::ERR_INSUFFICIENT_ALLOWANCE));
+                }
+                transfer_value(handle, address, from, to, amount)?;
+                // An allowance of `U256::MAX` is never used up.
+                if allowance != U256::MAX {
+                    Self::set_allowance(handle, &owner, &spender, allowance - amount)?;
+                }
+                event(address, events::TRANSFER, from, to, amount)
+            };
+            (Token::Bool(true), vec![log])
+        };
+
+        Ok(PrecompileOutput {
+            cost,
+            output: ethabi::encode(&[token]),
+            logs,
+        })
+    }
+}
+
+/// Moves `amount` of native ETH from `from` to `to`, by calling the precompile with the value
+/// transfer attached.
+fn transfer_value(
+    handle: &mut dyn PrecompileHandle,
+    address: H160,
+    from: H160,
+    to: H160,
+    amount: U256,
+) -> Result<(), PrecompileFailure> {
+    let transfer = Transfer {
+        source: from,
+        target: to,
+        value: amount,
+    };
+    let context = Context {
+        address: to,
+        caller: from,
+        apparent_value: amount,
+    };
+    let (exit_reason, return_value) =
+        handle.call(address, Some(transfer), Vec::new(), None, false, &context);
+    match exit_reason {
+        evm::ExitReason::Succeed(_) => Ok(()),
+        evm::ExitReason::Revert(r) => Err(PrecompileFailure::Revert {
+            exit_status: r,
+            output: return_value,
+        }),
+        evm::ExitReason::Error(ExitError::OutOfFund) => {
+            Err(revert_with_message(consts::ERR_INSUFFICIENT_BALANCE))
+        }
+        evm::ExitReason::Error(e) => Err(PrecompileFailure::Error { exit_status: e }),
+        evm::ExitReason::Fatal(f) => Err(PrecompileFailure::Fatal { exit_status: f }),
+    }
+}
+
+/// Runs `STATE_CODE` in the context of the precompile, and returns the word it returned (if any).
+fn access_state(
+    handle: &mut dyn PrecompileHandle,
+    op: u8,
+    arg: H256,
+    value: U256,
+) -> Result<U256, PrecompileFailure> {
+    let input = ethabi::encode(&[
+        Token::Uint(op.into()),
+        Token::FixedBytes(arg.as_bytes().to_vec()),
+        Token::Uint(value),
+    ]);
+    let context = Context {
+        address: ADDRESS.raw(),
+        caller: ADDRESS.raw(),
+        apparent_value: U256::zero(),
+    };
+    let is_static = op != consts::OP_SSTORE;
+    let (exit_reason, return_value) =
+        handle.call(STATE_ADDRESS.raw(), None, input, None, is_static, &context);
+    match exit_reason {
+        evm::ExitReason::Succeed(_) if return_value.len() == 32 => {
+            Ok(U256::from_big_endian(&return_value))
+        }
+        evm::ExitReason::Succeed(_) => Ok(U256::zero()),
+        evm::ExitReason::Revert(r) => Err(PrecompileFailure::Revert {
+            exit_status: r,
+            output: return_value,
+        }),
+        evm::ExitReason::Error(e) => Err(PrecompileFailure::Error { exit_status: e }),
+        evm::ExitReason::Fatal(f) => Err(PrecompileFailure::Fatal { exit_status: f }),
+    }
+}
+
+fn event(address: H160, topic: H256, from: H160, to: H160, amount: U256) -> Log {
+    Log {
+        address,
+        topics: vec![topic, H256::from(from), H256::from(to)],
+        data: ethabi::encode(&[Token::Uint(amount)]),
+    }
+}
+
+/// The slot of the precompile storage holding the allowance of `spender` over the balance of
+/// `owner`.
+fn allowance_slot(owner: &Address, spender: &Address) -> H256 {
+    aurora_engine_sdk::keccak(&[owner.as_bytes(), spender.as_bytes()].concat())
+}
+
+/// The engine storage key of the given slot of the precompile storage.
+pub fn allowance_key(slot: &H256) -> Vec<u8> {
+    bytes_to_key(KeyPrefix::EthAllowance, slot.as_bytes())
+}
+
+fn decode_args(types: &[ParamType], args: &[u8]) -> Result<Vec<Token>, PrecompileFailure> {
+    ethabi::decode(types, args).map_err(|_| invalid_input())
+}
+
+fn invalid_input() -> PrecompileFailure {
+    ExitError::Other(Borrowed(consts::ERR_INVALID_INPUT)).into()
+}
+
+fn revert_with_message(message: &str) -> PrecompileFailure {
+    PrecompileFailure::Revert {
+        exit_status: ExitRevert::Reverted,
+        output: message.as_bytes().to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{events, selectors, ADDRESS, STATE_ADDRESS};
+    use crate::prelude::sdk::types::near_account_to_evm_address;
+    use crate::prelude::H256;
+
+    fn keccak(signature: &str) -> H256 {
+        aurora_engine_sdk::keccak(signature.as_bytes())
+    }
+
+    #[test]
+    fn test_wrapped_eth_precompile_id() {
+        assert_eq!(
+            ADDRESS,
+            near_account_to_evm_address("wrappedEth".as_bytes())
+        );
+        assert_eq!(
+            STATE_ADDRESS,
+            near_account_to_evm_address("wrappedEthState".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_selectors() {
+        let selector = |signature: &str| -> [u8; 4] {
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(&keccak(signature)[0..4]);
+            buf
+        };
+        assert_eq!(selectors::NAME, selector("name()"));
+        assert_eq!(selectors::SYMBOL, selector("symbol()"));
+        assert_eq!(selectors::DECIMALS, selector("decimals()"));
+        assert_eq!(selectors::BALANCE_OF, selector("balanceOf(address)"));
+        assert_eq!(selectors::ALLOWANCE, selector("allowance(address,address)"));
+        assert_eq!(selectors::TRANSFER, selector("transfer(address,uint256)"));
+        assert_eq!(selectors::APPROVE, selector("approve(address,uint256)"));
+        assert_eq!(
+            selectors::TRANSFER_FROM,
+            selector("transferFrom(address,address,uint256)")
+        );
+    }
+
+    #[test]
+    fn test_event_topics() {
+        assert_eq!(
+            events::TRANSFER,
+            keccak("Transfer(address,address,uint256)")
+        );
+        assert_eq!(
+            events::APPROVAL,
+            keccak("Approval(address,address,uint256)")
+        );
+    }
+}
@@ -4,15 +4,20 @@
 //! TODO: How they work (low level explanation with examples)
 
 use crate::{HandleBasedPrecompile, PrecompileOutput};
+use aurora_engine_sdk::env::Env;
 use aurora_engine_sdk::io::IO;
 use aurora_engine_types::{
     account_id::AccountId,
     format,
-    parameters::{CrossContractCallArgs, PromiseCreateArgs},
+    parameters::{
+        CrossContractCallArgs, ExecuteWithCallbackArgs, NearPromise, PromiseArgs,
+        PromiseCreateArgs, ScheduleWithExpiryArgs, XccPermit,
+    },
     types::{balance::ZERO_YOCTO, Address, EthGas},
-    vec, Cow, Vec, H160, H256, U256,
+    vec, BTreeMap, Cow, Vec, H160, H256, U256,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
+use core::cell::RefCell;
 use evm::backend::Log;
 use evm::executor::stack::{PrecompileFailure, PrecompileHandle};
 use evm::ExitError;
@@ -43,7 +48,11 @@ pub mod costs {
     pub const CROSS_CONTRACT_CALL_NEAR_GAS: u64 = 175_000_000;
 
     pub const ROUTER_EXEC: NearGas = NearGas::new(7_000_000_000_000);
+    /// Executing with a callback additionally schedules the callback to the engine.
+    pub const ROUTER_EXEC_WITH_CALLBACK: NearGas = NearGas::new(8_000_000_000_000);
     pub const ROUTER_SCHEDULE: NearGas = NearGas::new(5_000_000_000_000);
+    /// Cancelling also covers wrapping the refunded NEAR and sending it back to the engine.
+    pub const ROUTER_CANCEL: NearGas = NearGas::new(85_000_000_000_000);
 }
 
 mod consts {
@@ -51,27 +60,105 @@ mod consts {
     pub(super) const ERR_SERIALIZE: &str = "ERR_XCC_CALL_SERIALIZE";
     pub(super) const ERR_STATIC: &str = "ERR_INVALID_IN_STATIC";
     pub(super) const ERR_DELEGATE: &str = "ERR_INVALID_IN_DELEGATE";
+    pub(super) const ERR_NO_ROUTER: &str = "ERR_XCC_NO_ROUTER";
+    pub(super) const ERR_ROUTER_TAKEOVER: &str = "ERR_XCC_ROUTER_TAKEOVER";
+    pub(super) const ERR_CALLBACK_AND: &str = "ERR_XCC_CALLBACK_AND";
+    pub(super) const ERR_PERMIT_VALUE: &str = "ERR_XCC_PERMIT_VALUE";
+    pub(super) const ERR_PERMIT_EXPIRED: &str = "ERR_XCC_PERMIT_EXPIRED";
+    pub(super) const ERR_PERMIT_SIGNATURE: &str = "ERR_XCC_PERMIT_SIGNATURE";
     pub(super) const ROUTER_EXEC_NAME: &str = "execute";
+    pub(super) const ROUTER_EXEC_WITH_CALLBACK_NAME: &str = "execute_with_callback";
     pub(super) const ROUTER_SCHEDULE_NAME: &str = "schedule";
+    pub(super) const ROUTER_SCHEDULE_WITH_EXPIRY_NAME: &str = "schedule_with_expiry";
+    pub(super) const ROUTER_CANCEL_NAME: &str = "cancel_scheduled";
     /// Solidity selector for the ERC-20 transferFrom function
     /// https://www.4byte.directory/signatures/?bytes4_signature=0x23b872dd
     pub(super) const TRANSFER_FROM_SELECTOR: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+    /// Solidity selector for the ERC-20 transfer function
+    /// https://www.4byte.directory/signatures/?bytes4_signature=0xa9059cbb
+    pub(super) const TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
 }
 
-pub struct CrossContractCall<I> {
+pub struct CrossContractCall<'a, I, E> {
     io: I,
+    env: &'a E,
     engine_account_id: AccountId,
+    chain_id: U256,
+    /// Next permit nonce of the owners whose permits were used in the current transaction.
+    used_permit_nonces: RefCell<BTreeMap<Address, u64>>,
 }
 
-impl<I> CrossContractCall<I> {
-    pub fn new(engine_account_id: AccountId, io: I) -> Self {
+impl<'a, I, E> CrossContractCall<'a, I, E> {
+    pub fn new(engine_account_id: AccountId, io: I, env: &'a E, chain_id: U256) -> Self {
         Self {
             io,
+            env,
             engine_account_id,
+            chain_id,
+            used_permit_nonces: RefCell::new(BTreeMap::new()),
         }
     }
 }
 
+impl<'a, I: IO + Copy, E: Env> CrossContractCall<'a, I, E> {
+    /// Checks the permit allows `caller` to make the call with the given hash, taking `amount`
+    /// wNEAR from the owner of the permit. Returns the owner of the permit and the log which
+    /// consumes it.
+    ///
+    /// The nonce is only written to the engine storage once the transaction succeeds (see
+    /// `cross_contract_call::PERMIT_NONCE_TOPIC`), so the permit is consumed if and only if the
+    /// wNEAR transfer happens. Within a transaction a permit cannot be used twice, even if
+    /// its first use reverted.
+    fn use_permit(
+        &self,
+        permit: &XccPermit,
+        amount: U256,
+        caller: Address,
+        call_hash: H256,
+    ) -> Result<(Address, Log), PrecompileFailure> {
+        let value = U256::from_big_endian(&permit.value);
+        if amount > value {
+            return Err(revert_with_message(consts::ERR_PERMIT_VALUE));
+        }
+        if self.env.block_timestamp().secs() > permit.deadline {
+            return Err(revert_with_message(consts::ERR_PERMIT_EXPIRED));
+        }
+
+        let nonce = match self.used_permit_nonces.borrow().get(&permit.owner) {
+            Some(nonce) => *nonce,
+            None => state::get_permit_nonce(&self.io, &permit.owner),
+        };
+        // The signature covers the caller, so that no one else can use the permit.
+        let digest = permit::digest(
+            self.chain_id,
+            permit.owner,
+            caller,
+            value,
+            U256::from(nonce),
+            permit.deadline,
+            call_hash,
+        );
+        let signer = permit::recover_signer(digest, permit)
+            .ok_or_else(|| revert_with_message(consts::ERR_PERMIT_SIGNATURE))?;
+        if signer != permit.owner {
+            return Err(revert_with_message(consts::ERR_PERMIT_SIGNATURE));
+        }
+
+        self.used_permit_nonces
+            .borrow_mut()
+            .insert(permit.owner, nonce + 1);
+        let nonce_log = Log {
+            address: cross_contract_call::ADDRESS.raw(),
+            topics: vec![
+                cross_contract_call::PERMIT_NONCE_TOPIC,
+                H256::from(permit.owner.raw()),
+            ],
+            data: (nonce + 1).to_le_bytes().to_vec(),
+        };
+        Ok((permit.owner, nonce_log))
+    }
+}
+
 pub mod cross_contract_call {
     use aurora_engine_types::{types::Address, H256};
 
@@ -86,12 +173,18 @@ pub mod cross_contract_call {
     /// cross-contract call will require.
     pub const AMOUNT_TOPIC: H256 =
         crate::make_h256(0x72657175697265645f6e656172, 0x72657175697265645f6e656172);
+
+    /// Sentinel value used to indicate the following topic field is the owner of a permit
+    /// used by the cross-contract call, and the data is the next nonce of their permits
+    /// (a little-endian u64).
+    pub const PERMIT_NONCE_TOPIC: H256 =
+        crate::make_h256(0x7065726d69745f6e6f6e6365, 0x7065726d69745f6e6f6e6365);
 }
 
-impl<I: IO> HandleBasedPrecompile for CrossContractCall<I> {
+impl<'a, I: IO + Copy, E: Env> HandleBasedPrecompile for CrossContractCall<'a, I, E> {
     fn run_with_handle(
         &self,
-        handle: &mut impl PrecompileHandle,
+        handle: &mut dyn PrecompileHandle,
     ) -> Result<PrecompileOutput, PrecompileFailure> {
         let input = handle.input();
         let target_gas = handle.gas_limit().map(EthGas::new);
@@ -126,6 +219,45 @@ impl<I: IO> HandleBasedPrecompile for CrossContractCall<I> {
         let target_account_id = create_target_account_id(sender, self.engine_account_id.as_ref());
         let args = CrossContractCallArgs::try_from_slice(input)
             .map_err(|_| ExitError::Other(Cow::from(consts::ERR_INVALID_INPUT)))?;
+        let (args, permit) = match args {
+            CrossContractCallArgs::WithPermit(_, args)
+                if matches!(*args, CrossContractCallArgs::WithPermit(..)) =>
+            {
+                return Err(ExitError::Other(Cow::from(consts::ERR_INVALID_INPUT)).into());
+            }
+            CrossContractCallArgs::WithPermit(permit, args) => {
+                // The permit is only valid for the call it was signed for.
+                let call_hash = aurora_engine_sdk::keccak(
+                    &args
+                        .try_to_vec()
+                        .map_err(|_| ExitError::Other(Cow::from(consts::ERR_SERIALIZE)))?,
+                );
+                (*args, Some((permit, call_hash)))
+            }
+            args => (args, None),
+        };
+        match &args {
+            // The EVM callback is delivered the result of exactly one promise, so it cannot
+            // follow promises running in parallel.
+            CrossContractCallArgs::EagerWithCallback(
+                PromiseArgs::Recursive(NearPromise::And(_)),
+                _,
+            ) => {
+                return Err(revert_with_message(consts::ERR_CALLBACK_AND));
+            }
+            // The engine keeps track of the router, so neither of them can be deleted, handed
+            // over or have their code replaced.
+            CrossContractCallArgs::Eager(call)
+            | CrossContractCallArgs::EagerWithCallback(call, _)
+            | CrossContractCallArgs::Delayed(call)
+            | CrossContractCallArgs::DelayedWithExpiry(call, _)
+                if call.takes_over_account(&target_account_id)
+                    || call.takes_over_account(&self.engine_account_id) =>
+            {
+                return Err(revert_with_message(consts::ERR_ROUTER_TAKEOVER));
+            }
+            _ => (),
+        }
         let (promise, attached_near) = match args {
             CrossContractCallArgs::Eager(call) => {
                 let call_gas = call.total_gas();
@@ -141,6 +273,24 @@ impl<I: IO> HandleBasedPrecompile for CrossContractCall<I> {
                 };
                 (promise, attached_near)
             }
+            CrossContractCallArgs::EagerWithCallback(call, callback) => {
+                let call_gas = call.total_gas() + callback.gas;
+                let attached_near = call.total_near();
+                let args = ExecuteWithCallbackArgs {
+                    promise: call,
+                    callback,
+                };
+                let promise = PromiseCreateArgs {
+                    target_account_id,
+                    method: consts::ROUTER_EXEC_WITH_CALLBACK_NAME.into(),
+                    args: args
+                        .try_to_vec()
+                        .map_err(|_| ExitError::Other(Cow::from(consts::ERR_SERIALIZE)))?,
+                    attached_balance: ZERO_YOCTO,
+                    attached_gas: costs::ROUTER_EXEC_WITH_CALLBACK + call_gas,
+                };
+                (promise, attached_near)
+            }
             CrossContractCallArgs::Delayed(call) => {
                 let attached_near = call.total_near();
                 let promise = PromiseCreateArgs {
@@ -156,6 +306,38 @@ impl<I: IO> HandleBasedPrecompile for CrossContractCall<I> {
                 };
                 (promise, attached_near)
             }
+            CrossContractCallArgs::DelayedWithExpiry(call, expiry_block_height) => {
+                let attached_near = call.total_near();
+                let args = ScheduleWithExpiryArgs {
+                    promise: call,
+                    expiry_block_height,
+                };
+                let promise = PromiseCreateArgs {
+                    target_account_id,
+                    method: consts::ROUTER_SCHEDULE_WITH_EXPIRY_NAME.into(),
+                    args: args
+                        .try_to_vec()
+                        .map_err(|_| ExitError::Other(Cow::from(consts::ERR_SERIALIZE)))?,
+                    attached_balance: ZERO_YOCTO,
+                    attached_gas: costs::ROUTER_SCHEDULE,
+                };
+                (promise, attached_near)
+            }
+            CrossContractCallArgs::Cancel(nonce) => {
+                // Without a router there is nothing to cancel, and we must not create one.
+                if state::get_code_version_of_address(&self.io, &Address::new(sender)).is_none() {
+                    return Err(revert_with_message(consts::ERR_NO_ROUTER));
+                }
+                let promise = PromiseCreateArgs {
+                    target_account_id,
+                    method: consts::ROUTER_CANCEL_NAME.into(),
+                    args: format!(r#"{{"nonce": "{}"}}"#, nonce).into_bytes(),
+                    attached_balance: ZERO_YOCTO,
+                    attached_gas: costs::ROUTER_CANCEL,
+                };
+                (promise, ZERO_YOCTO)
+            }
+            CrossContractCallArgs::WithPermit(..) => unreachable!(),
         };
         cost += EthGas::new(promise.attached_gas.as_u64() / costs::CROSS_CONTRACT_CALL_NEAR_GAS);
         check_cost(cost)?;
@@ -166,20 +348,32 @@ impl<I: IO> HandleBasedPrecompile for CrossContractCall<I> {
                 None => attached_near + state::STORAGE_AMOUNT,
                 Some(_) => attached_near,
             };
-        // if some NEAR payment is needed, transfer it from the caller to the engine's implicit address
+        // if some NEAR payment is needed, transfer it from the caller (or the owner of the
+        // permit) to the engine's implicit address
+        let mut permit_nonce_log = None;
         if required_near != ZERO_YOCTO {
             let engine_implicit_address = aurora_engine_sdk::types::near_account_to_evm_address(
                 self.engine_account_id.as_bytes(),
             );
-            let tx_data = transfer_from_args(
-                sender,
-                engine_implicit_address.raw(),
-                U256::from(required_near.as_u128()),
-            );
+            let amount = U256::from(required_near.as_u128());
+            let (tx_data, caller) = match permit {
+                // The signature of the owner authorizes the transfer, so it is made on their behalf.
+                Some((permit, call_hash)) => {
+                    let (owner, nonce_log) =
+                        self.use_permit(&permit, amount, Address::new(sender), call_hash)?;
+                    permit_nonce_log = Some(nonce_log);
+                    let tx_data = transfer_args(engine_implicit_address.raw(), amount);
+                    (tx_data, owner.raw())
+                }
+                None => {
+                    let tx_data = transfer_from_args(sender, engine_implicit_address.raw(), amount);
+                    (tx_data, cross_contract_call::ADDRESS.raw())
+                }
+            };
             let wnear_address = state::get_wnear_address(&self.io);
             let context = evm::Context {
                 address: wnear_address.raw(),
-                caller: cross_contract_call::ADDRESS.raw(),
+                caller,
                 apparent_value: U256::zero(),
             };
             let (exit_reason, return_value) =
@@ -217,8 +411,11 @@ impl<I: IO> HandleBasedPrecompile for CrossContractCall<I> {
                 .map_err(|_| ExitError::Other(Cow::from(consts::ERR_SERIALIZE)))?,
         };
 
+        let mut logs = vec![promise_log];
+        logs.extend(permit_nonce_log);
+
         Ok(PrecompileOutput {
-            logs: vec![promise_log],
+            logs,
             cost,
             ..Default::default()
         })
@@ -232,12 +429,14 @@ pub mod state {
     use aurora_engine_sdk::io::{StorageIntermediate, IO};
     use aurora_engine_types::storage::{self, KeyPrefix};
     use aurora_engine_types::types::{Address, Yocto};
+    use aurora_engine_types::Vec;
     use borsh::{BorshDeserialize, BorshSerialize};
 
     pub const ERR_CORRUPTED_STORAGE: &str = "ERR_CORRUPTED_XCC_STORAGE";
     pub const ERR_MISSING_WNEAR_ADDRESS: &str = "ERR_MISSING_WNEAR_ADDRESS";
     pub const VERSION_KEY: &[u8] = b"version";
     pub const WNEAR_KEY: &[u8] = b"wnear";
+    pub const PERMIT_NONCE_KEY: &[u8] = b"permit_nonce";
     /// Amount of NEAR needed to cover storage for a router contract.
     pub const STORAGE_AMOUNT: Yocto = Yocto::new(2_000_000_000_000_000_000_000_000);
 
@@ -283,6 +482,25 @@ pub mod state {
         read_version(io, &key)
     }
 
+    /// Get the number of permits of the given address used so far.
+    pub fn get_permit_nonce<I: IO>(io: &I, address: &Address) -> u64 {
+        let key = permit_nonce_key(address);
+        match io.read_storage(&key) {
+            Some(bytes) => u64::try_from_slice(&bytes.to_vec()).expect(ERR_CORRUPTED_STORAGE),
+            None => 0,
+        }
+    }
+
+    pub fn set_permit_nonce<I: IO>(io: &mut I, address: &Address, nonce: u64) {
+        let key = permit_nonce_key(address);
+        io.write_borsh(&key, &nonce);
+    }
+
+    fn permit_nonce_key(address: &Address) -> Vec<u8> {
+        let suffix = [PERMIT_NONCE_KEY, address.as_bytes()].concat();
+        storage::bytes_to_key(KeyPrefix::CrossContractCall, &suffix)
+    }
+
     /// Private utility method for reading code version from storage.
     fn read_version<I: IO>(io: &I, key: &[u8]) -> Option<CodeVersion> {
         match io.read_u32(key) {
@@ -293,6 +511,81 @@ pub mod state {
     }
 }
 
+pub mod permit {
+    //! EIP-712 encoding of the permits accepted by the precompile (see `XccPermit`).
+
+    use super::cross_contract_call;
+    use aurora_engine_sdk::keccak;
+    use aurora_engine_types::parameters::XccPermit;
+    use aurora_engine_types::types::Address;
+    use aurora_engine_types::{H256, U256};
+
+    pub const DOMAIN_NAME: &str = "Aurora Cross Contract Call";
+    pub const DOMAIN_VERSION: &str = "1";
+    const DOMAIN_TYPE: &str =
+        "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+    const PERMIT_TYPE: &str = "CrossContractCallPermit(address owner,address caller,uint256 value,uint256 nonce,uint256 deadline,bytes32 callHash)";
+
+    /// The EIP-712 domain separator, with the precompile as the verifying contract.
+    pub fn domain_separator(chain_id: U256) -> H256 {
+        keccak(&ethabi::encode(&[
+            ethabi::Token::FixedBytes(keccak(DOMAIN_TYPE.as_bytes()).as_bytes().to_vec()),
+            ethabi::Token::FixedBytes(keccak(DOMAIN_NAME.as_bytes()).as_bytes().to_vec()),
+            ethabi::Token::FixedBytes(keccak(DOMAIN_VERSION.as_bytes()).as_bytes().to_vec()),
+            ethabi::Token::Uint(chain_id),
+            ethabi::Token::Address(cross_contract_call::ADDRESS.raw()),
+        ]))
+    }
+
+    /// The hash signed by the owner of a permit. It binds the permit to the address calling the
+    /// precompile (which also determines the router) and to the keccak hash of the
+    /// borsh-serialized `CrossContractCallArgs` wrapped by the permit.
+    pub fn digest(
+        chain_id: U256,
+        owner: Address,
+        caller: Address,
+        value: U256,
+        nonce: U256,
+        deadline: u64,
+        call_hash: H256,
+    ) -> H256 {
+        let struct_hash = keccak(&ethabi::encode(&[
+            ethabi::Token::FixedBytes(keccak(PERMIT_TYPE.as_bytes()).as_bytes().to_vec()),
+            ethabi::Token::Address(owner.raw()),
+            ethabi::Token::Address(caller.raw()),
+            ethabi::Token::Uint(value),
+            ethabi::Token::Uint(nonce),
+            ethabi::Token::Uint(deadline.into()),
+            ethabi::Token::FixedBytes(call_hash.as_bytes().to_vec()),
+        ]));
+        keccak(
+            &[
+                &[0x19, 0x01],
+                domain_separator(chain_id).as_bytes(),
+                struct_hash.as_bytes(),
+            ]
+            .concat(),
+        )
+    }
+
+    pub(super) fn recover_signer(digest: H256, permit: &XccPermit) -> Option<Address> {
+        let v = match permit.v {
+            27 | 28 => permit.v - 27,
+            _ => return None,
+        };
+        let mut signature = [0u8; 65];
+        signature[0..32].copy_from_slice(&permit.r);
+        signature[32..64].copy_from_slice(&permit.s);
+        signature[64] = v;
+        crate::secp256k1::ecrecover(digest, &signature).ok()
+    }
+}
+
+fn transfer_args(to: H160, amount: U256) -> Vec<u8> {
+    let args = ethabi::encode(&[ethabi::Token::Address(to), ethabi::Token::Uint(amount)]);
+    [&consts::TRANSFER_SELECTOR, args.as_slice()].concat()
+}
+
 fn transfer_from_args(from: H160, to: H160, amount: U256) -> Vec<u8> {
     let args = ethabi::encode(&[
         ethabi::Token::Address(from),
@@ -318,8 +611,10 @@ fn revert_with_message(message: &str) -> PrecompileFailure {
 #[cfg(test)]
 mod tests {
     use crate::prelude::sdk::types::near_account_to_evm_address;
-    use crate::xcc::cross_contract_call;
-    use aurora_engine_types::{vec, H160, U256};
+    use crate::xcc::{consts, cross_contract_call, permit};
+    use aurora_engine_types::parameters::XccPermit;
+    use aurora_engine_types::types::{u256_to_arr, Address};
+    use aurora_engine_types::{vec, H160, H256, U256};
     use rand::Rng;
 
     #[test]
@@ -330,6 +625,96 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_transfer_selector() {
+        assert_eq!(
+            consts::TRANSFER_SELECTOR,
+            aurora_engine_sdk::keccak(b"transfer(address,uint256)")[0..4]
+        );
+    }
+
+    #[test]
+    fn test_permit_signature() {
+        let secret_key = libsecp256k1::SecretKey::parse(&[7; 32]).unwrap();
+        let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+        let owner =
+            Address::try_from_slice(&aurora_engine_sdk::keccak(&public_key.serialize()[1..])[12..])
+                .unwrap();
+        let chain_id = U256::from(1_313_161_554);
+        let value = U256::from(10u128.pow(24));
+        let deadline = 1_700_000_000;
+
+        let caller = Address::from_array([1; 20]);
+        let call_hash = H256([2; 32]);
+
+        let digest = permit::digest(
+            chain_id,
+            owner,
+            caller,
+            value,
+            U256::zero(),
+            deadline,
+            call_hash,
+        );
+        let (signature, recovery_id) =
+            libsecp256k1::sign(&libsecp256k1::Message::parse(&digest.0), &secret_key);
+        let signature = signature.serialize();
+        let mut xcc_permit = XccPermit {
+            owner,
+            value: u256_to_arr(&value),
+            deadline,
+            v: recovery_id.serialize() + 27,
+            r: signature[0..32].try_into().unwrap(),
+            s: signature[32..64].try_into().unwrap(),
+        };
+        assert_eq!(permit::recover_signer(digest, &xcc_permit), Some(owner));
+
+        // The signature does not match a permit with another nonce, chain id, caller or call
+        let digest = permit::digest(
+            chain_id,
+            owner,
+            caller,
+            value,
+            U256::one(),
+            deadline,
+            call_hash,
+        );
+        assert_ne!(permit::recover_signer(digest, &xcc_permit), Some(owner));
+        let digest = permit::digest(
+            chain_id,
+            owner,
+            Address::from_array([3; 20]),
+            value,
+            U256::zero(),
+            deadline,
+            call_hash,
+        );
+        assert_ne!(permit::recover_signer(digest, &xcc_permit), Some(owner));
+        let digest = permit::digest(
+            chain_id,
+            owner,
+            caller,
+            value,
+            U256::zero(),
+            deadline,
+            H256([4; 32]),
+        );
+        assert_ne!(permit::recover_signer(digest, &xcc_permit), Some(owner));
+        let digest = permit::digest(
+            U256::one(),
+            owner,
+            caller,
+            value,
+            U256::zero(),
+            deadline,
+            call_hash,
+        );
+        assert_ne!(permit::recover_signer(digest, &xcc_permit), Some(owner));
+
+        xcc_permit.v = 1;
+        assert_eq!(permit::recover_signer(digest, &xcc_permit), None);
+    }
+
     #[test]
     fn test_transfer_from_encoding() {
         let mut rng = rand::thread_rng();
@@ -41,6 +41,8 @@ pub trait Env {
     fn block_height(&self) -> u64;
     /// Timestamp (in ns) of the current block.
     fn block_timestamp(&self) -> Timestamp;
+    /// Height of the current epoch.
+    fn epoch_height(&self) -> u64;
     /// Amount of NEAR attached to current call
     fn attached_deposit(&self) -> u128;
     /// Random seed generated for the current block
@@ -74,6 +76,7 @@ pub struct Fixed {
     pub predecessor_account_id: AccountId,
     pub block_height: u64,
     pub block_timestamp: Timestamp,
+    pub epoch_height: u64,
     pub attached_deposit: u128,
     pub random_seed: H256,
     pub prepaid_gas: NearGas,
@@ -100,6 +103,10 @@ impl Env for Fixed {
         self.block_timestamp
     }
 
+    fn epoch_height(&self) -> u64 {
+        self.epoch_height
+    }
+
     fn attached_deposit(&self) -> u128 {
         self.attached_deposit
     }
@@ -237,6 +237,10 @@ impl crate::env::Env for Runtime {
         crate::env::Timestamp::new(ns)
     }
 
+    fn epoch_height(&self) -> u64 {
+        unsafe { exports::epoch_height() }
+    }
+
     fn attached_deposit(&self) -> u128 {
         unsafe {
             let data = [0u8; core::mem::size_of::<u128>()];
@@ -373,6 +377,60 @@ impl crate::promise::PromiseHandler for Runtime {
                         gas.as_u64(),
                     )
                 },
+                PromiseAction::Stake { amount, public_key } => unsafe {
+                    let amount = amount.as_u128();
+                    let pk = public_key.to_bytes();
+                    exports::promise_batch_action_stake(
+                        id,
+                        &amount as *const u128 as _,
+                        pk.len() as _,
+                        pk.as_ptr() as _,
+                    );
+                },
+                PromiseAction::AddFullAccessKey { public_key, nonce } => unsafe {
+                    let pk = public_key.to_bytes();
+                    exports::promise_batch_action_add_key_with_full_access(
+                        id,
+                        pk.len() as _,
+                        pk.as_ptr() as _,
+                        *nonce,
+                    );
+                },
+                PromiseAction::AddFunctionCallKey {
+                    public_key,
+                    nonce,
+                    allowance,
+                    receiver_id,
+                    function_names,
+                } => unsafe {
+                    let pk = public_key.to_bytes();
+                    let allowance = allowance.as_u128();
+                    let receiver_id = receiver_id.as_bytes();
+                    let function_names = function_names.as_bytes();
+                    exports::promise_batch_action_add_key_with_function_call(
+                        id,
+                        pk.len() as _,
+                        pk.as_ptr() as _,
+                        *nonce,
+                        &allowance as *const u128 as _,
+                        receiver_id.len() as _,
+                        receiver_id.as_ptr() as _,
+                        function_names.len() as _,
+                        function_names.as_ptr() as _,
+                    );
+                },
+                PromiseAction::DeleteKey { public_key } => unsafe {
+                    let pk = public_key.to_bytes();
+                    exports::promise_batch_action_delete_key(id, pk.len() as _, pk.as_ptr() as _);
+                },
+                PromiseAction::DeleteAccount { beneficiary_id } => unsafe {
+                    let beneficiary_id = beneficiary_id.as_bytes();
+                    exports::promise_batch_action_delete_account(
+                        id,
+                        beneficiary_id.len() as _,
+                        beneficiary_id.as_ptr() as _,
+                    );
+                },
             }
         }
 
@@ -419,6 +477,10 @@ impl crate::env::Env for ViewEnv {
         crate::env::Timestamp::new(ns)
     }
 
+    fn epoch_height(&self) -> u64 {
+        unsafe { exports::epoch_height() }
+    }
+
     fn attached_deposit(&self) -> u128 {
         1
     }
@@ -456,7 +518,7 @@ pub(crate) mod exports {
         // TODO #1903 fn block_height() -> u64;
         pub(crate) fn block_index() -> u64;
         pub(crate) fn block_timestamp() -> u64;
-        fn epoch_height() -> u64;
+        pub(crate) fn epoch_height() -> u64;
         pub(crate) fn storage_usage() -> u64;
         // #################
         // # Economics API #
@@ -539,19 +601,19 @@ pub(crate) mod exports {
             gas: u64,
         );
         pub(crate) fn promise_batch_action_transfer(promise_index: u64, amount_ptr: u64);
-        fn promise_batch_action_stake(
+        pub(crate) fn promise_batch_action_stake(
             promise_index: u64,
             amount_ptr: u64,
             public_key_len: u64,
             public_key_ptr: u64,
         );
-        fn promise_batch_action_add_key_with_full_access(
+        pub(crate) fn promise_batch_action_add_key_with_full_access(
             promise_index: u64,
             public_key_len: u64,
             public_key_ptr: u64,
             nonce: u64,
         );
-        fn promise_batch_action_add_key_with_function_call(
+        pub(crate) fn promise_batch_action_add_key_with_function_call(
             promise_index: u64,
             public_key_len: u64,
             public_key_ptr: u64,
@@ -562,12 +624,12 @@ pub(crate) mod exports {
             method_names_len: u64,
             method_names_ptr: u64,
         );
-        fn promise_batch_action_delete_key(
+        pub(crate) fn promise_batch_action_delete_key(
             promise_index: u64,
             public_key_len: u64,
             public_key_ptr: u64,
         );
-        fn promise_batch_action_delete_account(
+        pub(crate) fn promise_batch_action_delete_account(
             promise_index: u64,
             beneficiary_id_len: u64,
             beneficiary_id_ptr: u64,
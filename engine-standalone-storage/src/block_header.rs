@@ -0,0 +1,255 @@
+//! Ethereum-style block headers for Aurora blocks.
+//!
+//! Aurora itself only exposes the synthetic hash computed by `aurora_engine::engine::compute_block_hash`.
+//! The types here let the standalone storage assemble a full (London) header for each block from the
+//! transactions and receipts it has processed, which is what JSON-RPC clients validating headers expect.
+
+use aurora_engine::parameters::ResultLog;
+use aurora_engine_types::{H160, H256, U256};
+use borsh::{BorshDeserialize, BorshSerialize};
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+
+use crate::BlockMetadata;
+
+/// Size (in bytes) of the logs bloom filter.
+pub const BLOOM_SIZE: usize = 256;
+/// Number of fields in a London (EIP-1559) header.
+const HEADER_FIELDS: usize = 16;
+
+/// 2048-bit bloom filter over log addresses and topics, as defined in the Ethereum yellow paper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bloom(pub [u8; BLOOM_SIZE]);
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Self([0u8; BLOOM_SIZE])
+    }
+}
+
+impl Bloom {
+    /// Sets the three bits selected by the keccak hash of `input`.
+    pub fn accrue(&mut self, input: &[u8]) {
+        let hash = aurora_engine_sdk::keccak(input);
+        let hash = hash.as_bytes();
+        for i in [0, 2, 4] {
+            let bit = ((usize::from(hash[i]) << 8) | usize::from(hash[i + 1])) & 2047;
+            self.0[BLOOM_SIZE - 1 - bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    pub fn accrue_log(&mut self, log: &ResultLog) {
+        self.accrue(log.address.raw().as_bytes());
+        for topic in log.topics.iter() {
+            self.accrue(topic);
+        }
+    }
+
+    pub fn accrue_bloom(&mut self, other: &Self) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a |= *b;
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// The data needed to reconstruct the Ethereum receipt of a transaction submitted to Aurora.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct TransactionReceipt {
+    /// EIP-2718 transaction type; `None` for legacy transactions.
+    pub transaction_type: Option<u8>,
+    pub status: bool,
+    pub gas_used: u64,
+    pub logs: Vec<ResultLog>,
+}
+
+impl TransactionReceipt {
+    pub fn bloom(&self) -> Bloom {
+        let mut bloom = Bloom::default();
+        for log in self.logs.iter() {
+            bloom.accrue_log(log);
+        }
+        bloom
+    }
+
+    /// EIP-2718 encoding of the receipt, as it appears in the receipts trie.
+    /// The cumulative gas is not known to the receipt itself since it depends on the
+    /// transactions preceding it in the block.
+    pub fn encode(&self, cumulative_gas_used: u64) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&self.status);
+        stream.append(&cumulative_gas_used);
+        stream.append(&self.bloom().as_bytes());
+        stream.begin_list(self.logs.len());
+        for log in self.logs.iter() {
+            stream.begin_list(3);
+            stream.append(&log.address.raw());
+            stream.begin_list(log.topics.len());
+            for topic in log.topics.iter() {
+                stream.append(&H256(*topic));
+            }
+            stream.append(&log.data);
+        }
+        let payload = stream.out().to_vec();
+
+        match self.transaction_type {
+            None => payload,
+            Some(type_byte) => [&[type_byte], payload.as_slice()].concat(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub parent_hash: H256,
+    pub ommers_hash: H256,
+    pub beneficiary: H160,
+    pub state_root: H256,
+    pub transactions_root: H256,
+    pub receipts_root: H256,
+    pub logs_bloom: Bloom,
+    pub difficulty: U256,
+    pub number: u64,
+    pub gas_limit: U256,
+    pub gas_used: u64,
+    pub timestamp: u64,
+    pub extra_data: Vec<u8>,
+    pub mix_hash: H256,
+    pub nonce: [u8; 8],
+    pub base_fee_per_gas: U256,
+}
+
+impl BlockHeader {
+    /// Assembles the header of the block at `block_height`.
+    /// `beneficiary` and `gas_limit` are the values of the `COINBASE` and `GASLIMIT` opcodes in the
+    /// block, `transactions` are the EIP-2718 encoded Ethereum transactions included in the block,
+    /// in order, and `receipts` are their corresponding receipts.
+    pub fn new(
+        parent_hash: H256,
+        block_height: u64,
+        block_metadata: &BlockMetadata,
+        beneficiary: H160,
+        gas_limit: U256,
+        transactions: &[Vec<u8>],
+        receipts: &[TransactionReceipt],
+    ) -> Self {
+        let mut logs_bloom = Bloom::default();
+        let mut gas_used: u64 = 0;
+        let mut encoded_receipts = Vec::with_capacity(receipts.len());
+        for receipt in receipts {
+            gas_used = gas_used.saturating_add(receipt.gas_used);
+            logs_bloom.accrue_bloom(&receipt.bloom());
+            encoded_receipts.push(receipt.encode(gas_used));
+        }
+
+        Self {
+            parent_hash,
+            ommers_hash: aurora_engine_sdk::keccak(&rlp::EMPTY_LIST_RLP),
+            beneficiary,
+            // Aurora does not maintain a Merkle-Patricia trie of its state.
+            state_root: H256::zero(),
+            transactions_root: ordered_trie_root(transactions),
+            receipts_root: ordered_trie_root(&encoded_receipts),
+            logs_bloom,
+            difficulty: U256::zero(),
+            number: block_height,
+            gas_limit,
+            gas_used,
+            timestamp: block_metadata.timestamp.secs(),
+            extra_data: Vec::new(),
+            mix_hash: block_metadata.random_seed,
+            nonce: [0u8; 8],
+            base_fee_per_gas: U256::zero(),
+        }
+    }
+
+    /// The Ethereum hash of the header; the keccak256 of its RLP encoding.
+    pub fn hash(&self) -> H256 {
+        aurora_engine_sdk::keccak(&rlp::encode(self))
+    }
+}
+
+impl Encodable for BlockHeader {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(HEADER_FIELDS);
+        s.append(&self.parent_hash);
+        s.append(&self.ommers_hash);
+        s.append(&self.beneficiary);
+        s.append(&self.state_root);
+        s.append(&self.transactions_root);
+        s.append(&self.receipts_root);
+        s.append(&self.logs_bloom.as_bytes());
+        s.append(&self.difficulty);
+        s.append(&self.number);
+        s.append(&self.gas_limit);
+        s.append(&self.gas_used);
+        s.append(&self.timestamp);
+        s.append(&self.extra_data);
+        s.append(&self.mix_hash);
+        s.append(&self.nonce.as_slice());
+        s.append(&self.base_fee_per_gas);
+    }
+}
+
+impl Decodable for BlockHeader {
+    fn decode(rlp: &Rlp<'_>) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != HEADER_FIELDS {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        let logs_bloom = {
+            let data = rlp.at(6)?.data()?;
+            if data.len() != BLOOM_SIZE {
+                return Err(DecoderError::RlpInvalidLength);
+            }
+            let mut buf = [0u8; BLOOM_SIZE];
+            buf.copy_from_slice(data);
+            Bloom(buf)
+        };
+        let nonce = {
+            let data = rlp.at(14)?.data()?;
+            if data.len() != 8 {
+                return Err(DecoderError::RlpInvalidLength);
+            }
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(data);
+            buf
+        };
+        Ok(Self {
+            parent_hash: rlp.val_at(0)?,
+            ommers_hash: rlp.val_at(1)?,
+            beneficiary: rlp.val_at(2)?,
+            state_root: rlp.val_at(3)?,
+            transactions_root: rlp.val_at(4)?,
+            receipts_root: rlp.val_at(5)?,
+            logs_bloom,
+            difficulty: rlp.val_at(7)?,
+            number: rlp.val_at(8)?,
+            gas_limit: rlp.val_at(9)?,
+            gas_used: rlp.val_at(10)?,
+            timestamp: rlp.val_at(11)?,
+            extra_data: rlp.val_at(12)?,
+            mix_hash: rlp.val_at(13)?,
+            nonce,
+            base_fee_per_gas: rlp.val_at(15)?,
+        })
+    }
+}
+
+/// Root of the Merkle-Patricia trie mapping the RLP encoded index of each item to the item itself.
+pub fn ordered_trie_root<T: AsRef<[u8]>>(items: &[T]) -> H256 {
+    triehash::ordered_trie_root::<KeccakHasher, _>(items)
+}
+
+struct KeccakHasher;
+
+impl hash_db::Hasher for KeccakHasher {
+    type Out = H256;
+    type StdHasher = hash256_std_hasher::Hash256StdHasher;
+    const LENGTH: usize = 32;
+
+    fn hash(x: &[u8]) -> Self::Out {
+        aurora_engine_sdk::keccak(x)
+    }
+}
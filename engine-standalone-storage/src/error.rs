@@ -12,6 +12,7 @@ pub enum Error {
     Rocksdb(rocksdb::Error),
     EngineAccountIdNotSet,
     EngineAccountIdCorrupted,
+    Rlp(rlp::DecoderError),
 }
 
 impl From<rocksdb::Error> for Error {
@@ -25,3 +26,9 @@ impl From<std::io::Error> for Error {
         Self::Borsh(e.to_string())
     }
 }
+
+impl From<rlp::DecoderError> for Error {
+    fn from(e: rlp::DecoderError) -> Self {
+        Self::Rlp(e)
+    }
+}
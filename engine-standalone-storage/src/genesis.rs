@@ -0,0 +1,176 @@
+//! Initialisation of a fresh DB from a genesis specification of predeployed accounts.
+//!
+//! The JSON format follows the `alloc` section of a geth genesis file, so existing
+//! specifications (e.g. for CREATE2 deployers, multicall or WETH) can be reused as-is.
+
+use aurora_engine::parameters::{Genesis, GenesisAccount, NewCallArgs, NewWithGenesisCallArgs};
+use aurora_engine_sdk::env::Timestamp;
+use aurora_engine_types::types::{u256_to_arr, Address};
+use aurora_engine_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::sync::types::{BlockMessage, Message, TransactionKind, TransactionMessage};
+use crate::{sync, BlockMetadata, Storage};
+
+/// Initialise the Engine and install the genesis accounts in an empty DB.
+/// This is the standalone counterpart of calling `new` with a genesis specification,
+/// and is recorded as the first transaction of a block at height 0.
+/// The Engine account id must have been set on `storage` beforehand.
+pub fn initialize_genesis(
+    storage: &mut Storage,
+    new_args: NewCallArgs,
+    genesis: Genesis,
+) -> Result<(), crate::Error> {
+    let block_hash = H256::zero();
+    let block_message = BlockMessage {
+        height: 0,
+        hash: block_hash,
+        metadata: BlockMetadata {
+            timestamp: Timestamp::new(0),
+            random_seed: H256::zero(),
+            epoch_height: 0,
+        },
+    };
+    sync::consume_message(storage, Message::Block(block_message))?;
+
+    let engine_account_id = storage.get_engine_account_id()?;
+    let transaction_message = TransactionMessage {
+        block_hash,
+        near_receipt_id: H256::zero(),
+        position: 0,
+        succeeded: true,
+        signer: engine_account_id.clone(),
+        caller: engine_account_id,
+        attached_near: 0,
+        transaction: TransactionKind::NewEngineWithGenesis(NewWithGenesisCallArgs {
+            new_args,
+            genesis,
+        }),
+        promise_data: Vec::new(),
+    };
+    sync::consume_message(storage, Message::Transaction(Box::new(transaction_message)))?;
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct JsonGenesis {
+    /// Accounts keyed by their hex encoded address.
+    pub alloc: BTreeMap<String, JsonGenesisAccount>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct JsonGenesisAccount {
+    /// Hex (`0x` prefixed) or decimal quantity; zero if omitted.
+    #[serde(default)]
+    pub balance: Option<String>,
+    /// Hex (`0x` prefixed) or decimal quantity; zero if omitted.
+    #[serde(default)]
+    pub nonce: Option<String>,
+    /// Hex encoded bytecode.
+    #[serde(default)]
+    pub code: Option<String>,
+    /// Hex encoded words, each at most 32 bytes.
+    #[serde(default)]
+    pub storage: BTreeMap<String, String>,
+}
+
+impl JsonGenesis {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = std::fs::File::open(path)?;
+        let reader = std::io::BufReader::new(file);
+        let genesis = serde_json::from_reader(reader)?;
+        Ok(genesis)
+    }
+}
+
+impl TryFrom<JsonGenesis> for Genesis {
+    type Error = error::Error;
+
+    fn try_from(value: JsonGenesis) -> Result<Self, Self::Error> {
+        let accounts = value
+            .alloc
+            .into_iter()
+            .map(|(address, account)| parse_account(&address, account))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { accounts })
+    }
+}
+
+fn parse_account(
+    address: &str,
+    account: JsonGenesisAccount,
+) -> Result<GenesisAccount, error::Error> {
+    let address = Address::decode(strip_hex_prefix(address))
+        .map_err(|_| error::Error::InvalidAddress(address.to_string()))?;
+    let balance = parse_quantity(account.balance.as_deref())?;
+    let nonce = parse_quantity(account.nonce.as_deref())?;
+    let code = match account.code {
+        Some(code) => hex::decode(strip_hex_prefix(&code))?,
+        None => Vec::new(),
+    };
+    let storage = account
+        .storage
+        .iter()
+        .map(|(key, value)| Ok((parse_word(key)?, parse_word(value)?)))
+        .collect::<Result<Vec<_>, error::Error>>()?;
+
+    Ok(GenesisAccount {
+        address,
+        balance: u256_to_arr(&balance),
+        nonce: u256_to_arr(&nonce),
+        code,
+        storage,
+    })
+}
+
+fn parse_quantity(value: Option<&str>) -> Result<U256, error::Error> {
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(U256::zero()),
+    };
+    let result = match value.strip_prefix("0x") {
+        Some("") => Some(U256::zero()),
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(value).ok(),
+    };
+    result.ok_or_else(|| error::Error::InvalidQuantity(value.to_string()))
+}
+
+/// Parses a storage key or value; shorter words (e.g. `0x1`) are left-padded with zeros.
+fn parse_word(value: &str) -> Result<[u8; 32], error::Error> {
+    let hex = strip_hex_prefix(value);
+    let bytes = if hex.len() % 2 == 0 {
+        hex::decode(hex)?
+    } else {
+        hex::decode(format!("0{}", hex))?
+    };
+    if bytes.len() > 32 {
+        return Err(error::Error::InvalidWord(value.to_string()));
+    }
+    let mut word = [0u8; 32];
+    word[(32 - bytes.len())..].copy_from_slice(&bytes);
+    Ok(word)
+}
+
+fn strip_hex_prefix(value: &str) -> &str {
+    value.strip_prefix("0x").unwrap_or(value)
+}
+
+pub mod error {
+    #[derive(Debug)]
+    pub enum Error {
+        Hex(hex::FromHexError),
+        InvalidAddress(String),
+        InvalidQuantity(String),
+        InvalidWord(String),
+    }
+
+    impl From<hex::FromHexError> for Error {
+        fn from(e: hex::FromHexError) -> Self {
+            Self::Hex(e)
+        }
+    }
+}
@@ -1,5 +1,8 @@
+use aurora_engine_precompiles::extensions::PrecompileExtensions;
 use aurora_engine_sdk::env::Timestamp;
-use aurora_engine_types::{account_id::AccountId, H256};
+use aurora_engine_types::{account_id::AccountId, H256, U256};
+use block_header::{BlockHeader, TransactionReceipt};
+use borsh::{BorshDeserialize, BorshSerialize};
 use rocksdb::DB;
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
@@ -8,9 +11,11 @@ use sync::types::TransactionMessage;
 
 const VERSION: u8 = 0;
 
+pub mod block_header;
 pub mod diff;
 pub mod engine_state;
 pub mod error;
+pub mod genesis;
 pub mod json_snapshot;
 pub mod promise;
 pub mod relayer_db;
@@ -35,18 +40,34 @@ pub enum StoragePrefix {
     Engine = 0x05,
     BlockMetadata = 0x06,
     EngineAccountId = 0x07,
+    TransactionReceipt = 0x08,
+    BlockHeader = 0x09,
 }
 
 const ACCOUNT_ID_KEY: &[u8] = b"engine_account_id";
 
 pub struct Storage {
     db: DB,
+    /// Precompiles installed on top of the ones of the engine when executing transactions.
+    /// These are not persisted, so they must be set every time the storage is opened.
+    precompile_extensions: PrecompileExtensions,
 }
 
 impl Storage {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, rocksdb::Error> {
         let db = DB::open_default(path)?;
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            precompile_extensions: PrecompileExtensions::default(),
+        })
+    }
+
+    pub fn set_precompile_extensions(&mut self, extensions: PrecompileExtensions) {
+        self.precompile_extensions = extensions;
+    }
+
+    pub fn precompile_extensions(&self) -> &PrecompileExtensions {
+        &self.precompile_extensions
     }
 
     pub fn set_engine_account_id(&mut self, id: &AccountId) -> Result<(), rocksdb::Error> {
@@ -121,8 +142,10 @@ impl Storage {
         self.db
             .get_pinned(storage_key)?
             .map(|slice| {
-                let mut buf = [0u8; 40];
-                buf.copy_from_slice(slice.as_ref());
+                // Metadata stored before the epoch height was added is 40 bytes long,
+                // so its epoch height reads as zero.
+                let mut buf = [0u8; 48];
+                buf[0..slice.len()].copy_from_slice(slice.as_ref());
                 BlockMetadata::from_bytes(buf)
             })
             .ok_or(error::Error::BlockNotFound(block_hash))
@@ -150,6 +173,216 @@ impl Storage {
         self.db.write(batch)
     }
 
+    /// Returns the Ethereum header of the given block. Headers are persisted once the next block
+    /// is added; the header of the latest block is assembled from its receipts on every call.
+    pub fn get_block_header(&self, block_hash: H256) -> Result<BlockHeader, error::Error> {
+        match self.get_stored_block_header(block_hash)? {
+            Some(header) => Ok(header),
+            None => self.assemble_block_header(block_hash),
+        }
+    }
+
+    pub fn get_block_header_by_height(
+        &self,
+        block_height: u64,
+    ) -> Result<BlockHeader, error::Error> {
+        let block_hash = self.get_block_hash_by_height(block_height)?;
+        self.get_block_header(block_hash)
+    }
+
+    /// Assembles the Ethereum header of the given block from the receipts stored for it and
+    /// persists it, replacing any header previously stored for that block. The stored headers of
+    /// the blocks above it are rebuilt as well if their parent hash changes as a result.
+    pub fn update_block_header(&mut self, block_hash: H256) -> Result<BlockHeader, error::Error> {
+        let header = self.assemble_block_header(block_hash)?;
+        self.put_block_header(block_hash, &header)?;
+
+        let mut parent_hash = header.hash();
+        let mut block_height = header.number;
+        while let Some(child_hash) = self.get_next_block_hash(block_height)? {
+            let is_stale = self
+                .get_stored_block_header(child_hash)?
+                .map_or(false, |child_header| {
+                    child_header.parent_hash != parent_hash
+                });
+            if !is_stale {
+                break;
+            }
+            let child_header = self.assemble_block_header(child_hash)?;
+            self.put_block_header(child_hash, &child_header)?;
+            parent_hash = child_header.hash();
+            block_height = child_header.number;
+        }
+
+        Ok(header)
+    }
+
+    /// Rebuilds the header of the given block if it was already persisted, i.e. when one of its
+    /// transactions is included or reverted after the next block was added.
+    fn refresh_block_header(&mut self, block_hash: H256) -> Result<(), error::Error> {
+        if self.get_stored_block_header(block_hash)?.is_some() {
+            self.update_block_header(block_hash)?;
+        }
+        Ok(())
+    }
+
+    fn get_stored_block_header(
+        &self,
+        block_hash: H256,
+    ) -> Result<Option<BlockHeader>, error::Error> {
+        let storage_key = construct_storage_key(StoragePrefix::BlockHeader, block_hash.as_ref());
+        match self.db.get_pinned(storage_key)? {
+            Some(bytes) => Ok(Some(rlp::decode(bytes.as_ref())?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put_block_header(
+        &mut self,
+        block_hash: H256,
+        header: &BlockHeader,
+    ) -> Result<(), error::Error> {
+        let storage_key = construct_storage_key(StoragePrefix::BlockHeader, block_hash.as_ref());
+        self.db
+            .put(storage_key, rlp::encode(header))
+            .map_err(Into::into)
+    }
+
+    fn assemble_block_header(&self, block_hash: H256) -> Result<BlockHeader, error::Error> {
+        let block_height = self.get_block_height_by_hash(block_hash)?;
+        let block_metadata = self.get_block_metadata(block_hash)?;
+        let parent_hash = self.get_parent_header_hash(block_height)?;
+
+        let mut transactions: Vec<Vec<u8>> = Vec::new();
+        let mut receipts = Vec::new();
+        let mut relayer = None;
+        let receipts_prefix =
+            construct_storage_key(StoragePrefix::TransactionReceipt, block_hash.as_ref());
+        let n = receipts_prefix.len();
+        for (k, v) in self.db.prefix_iterator(&receipts_prefix) {
+            if k.len() < n || k[0..n] != receipts_prefix {
+                break;
+            }
+            let position = {
+                let mut buf = [0u8; 2];
+                buf.copy_from_slice(&k[n..(n + 2)]);
+                u16::from_be_bytes(buf)
+            };
+            let tx_hash = self.get_transaction_by_position(TransactionIncluded {
+                block_hash,
+                position,
+            })?;
+            let tx_msg = self.get_transaction_data(tx_hash)?;
+            match tx_msg.transaction {
+                sync::types::TransactionKind::Submit(tx) => transactions.push((&tx).into()),
+                // Receipts are only stored for Ethereum transactions
+                _ => continue,
+            }
+            relayer.get_or_insert(tx_msg.caller);
+            receipts.push(TransactionReceipt::try_from_slice(v.as_ref())?);
+        }
+
+        // The configuration in effect at the end of the block. If the coinbase is the relayer,
+        // the one of the first transaction of the block is used.
+        let (beneficiary, gas_limit) = self
+            .with_engine_access(block_height + 1, 0, &[], |io| {
+                let gas_limit = aurora_engine::engine::get_state(&io)
+                    .map_or_else(|_| U256::max_value(), |state| state.gas_limit());
+                let beneficiary = match relayer.as_ref() {
+                    Some(relayer) => aurora_engine::engine::get_coinbase_address(
+                        &io,
+                        aurora_engine::engine::get_coinbase(&io, block_height),
+                        relayer,
+                    ),
+                    None => aurora_engine::engine::DEFAULT_COINBASE,
+                };
+                (beneficiary, gas_limit)
+            })
+            .result;
+
+        Ok(BlockHeader::new(
+            parent_hash,
+            block_height,
+            &block_metadata,
+            beneficiary,
+            gas_limit,
+            &transactions,
+            &receipts,
+        ))
+    }
+
+    /// The parent of a block is the closest block stored below it (NEAR block heights are not
+    /// contiguous). The hash is zero if there is no such block or its header is not persisted.
+    fn get_parent_header_hash(&self, block_height: u64) -> Result<H256, error::Error> {
+        let parent_hash = match self.get_previous_block_hash(block_height)? {
+            Some(parent_hash) => parent_hash,
+            None => return Ok(H256::zero()),
+        };
+        Ok(self
+            .get_stored_block_header(parent_hash)?
+            .map_or_else(H256::zero, |header| header.hash()))
+    }
+
+    /// Hash of the closest block stored below the given height.
+    pub fn get_previous_block_hash(&self, block_height: u64) -> Result<Option<H256>, error::Error> {
+        if block_height == 0 {
+            return Ok(None);
+        }
+        let upper_bound =
+            construct_storage_key(StoragePrefix::BlockHash, &block_height.to_be_bytes());
+        let lower_bound = construct_storage_key(StoragePrefix::BlockHash, &[]);
+        self.block_hash_between(lower_bound, upper_bound, rocksdb::IteratorMode::End)
+    }
+
+    /// Hash of the closest block stored above the given height.
+    fn get_next_block_hash(&self, block_height: u64) -> Result<Option<H256>, error::Error> {
+        let lower_bound = match block_height.checked_add(1) {
+            Some(height) => construct_storage_key(StoragePrefix::BlockHash, &height.to_be_bytes()),
+            None => return Ok(None),
+        };
+        let upper_bound = construct_storage_key(StoragePrefix::BlockHash, &[0xff; 9]);
+        self.block_hash_between(lower_bound, upper_bound, rocksdb::IteratorMode::Start)
+    }
+
+    fn block_hash_between(
+        &self,
+        lower_bound: Vec<u8>,
+        upper_bound: Vec<u8>,
+        mode: rocksdb::IteratorMode,
+    ) -> Result<Option<H256>, error::Error> {
+        let mut opt = rocksdb::ReadOptions::default();
+        opt.set_iterate_upper_bound(upper_bound);
+        opt.set_iterate_lower_bound(lower_bound);
+        let mut iter = self.db.iterator_opt(mode, opt);
+        Ok(iter.next().map(|(_, value)| H256::from_slice(&value)))
+    }
+
+    pub fn get_transaction_receipt(
+        &self,
+        tx_included: TransactionIncluded,
+    ) -> Result<TransactionReceipt, error::Error> {
+        let storage_key =
+            construct_storage_key(StoragePrefix::TransactionReceipt, &tx_included.to_bytes());
+        let bytes = self
+            .db
+            .get_pinned(storage_key)?
+            .ok_or(error::Error::TransactionNotFound(tx_included))?;
+        let receipt = TransactionReceipt::try_from_slice(bytes.as_ref())?;
+        Ok(receipt)
+    }
+
+    pub fn set_transaction_receipt(
+        &mut self,
+        tx_included: TransactionIncluded,
+        receipt: &TransactionReceipt,
+    ) -> Result<(), error::Error> {
+        let storage_key =
+            construct_storage_key(StoragePrefix::TransactionReceipt, &tx_included.to_bytes());
+        let receipt_bytes = receipt.try_to_vec()?;
+        self.db.put(storage_key, receipt_bytes)?;
+        self.refresh_block_header(tx_included.block_hash)
+    }
+
     pub fn get_transaction_data(
         &self,
         tx_hash: H256,
@@ -204,10 +437,19 @@ impl Storage {
         tx_included: &TransactionMessage,
         diff: &Diff,
     ) -> Result<(), error::Error> {
-        let batch = rocksdb::WriteBatch::default();
+        let mut batch = rocksdb::WriteBatch::default();
+        let receipt_key = TransactionIncluded {
+            block_hash: tx_included.block_hash,
+            position: tx_included.position,
+        };
+        batch.delete(construct_storage_key(
+            StoragePrefix::TransactionReceipt,
+            &receipt_key.to_bytes(),
+        ));
         self.process_transaction(tx_hash, tx_included, diff, batch, |batch, key, _value| {
             batch.delete(key)
-        })
+        })?;
+        self.refresh_block_header(tx_included.block_hash)
     }
 
     fn process_transaction<F: Fn(&mut rocksdb::WriteBatch, &[u8], &[u8])>(
@@ -435,17 +677,20 @@ pub struct BlockMetadata {
     /// to execute the Aurora randomness precompile correctly because it uses this NEAR
     /// entropy source.
     pub random_seed: H256,
+    /// Needed to execute the Aurora block context precompile correctly.
+    pub epoch_height: u64,
 }
 
 impl BlockMetadata {
-    pub fn to_bytes(&self) -> [u8; 40] {
-        let mut buf = [0u8; 40];
+    pub fn to_bytes(&self) -> [u8; 48] {
+        let mut buf = [0u8; 48];
         buf[0..8].copy_from_slice(&self.timestamp.nanos().to_be_bytes());
         buf[8..40].copy_from_slice(self.random_seed.as_ref());
+        buf[40..48].copy_from_slice(&self.epoch_height.to_be_bytes());
         buf
     }
 
-    pub fn from_bytes(bytes: [u8; 40]) -> Self {
+    pub fn from_bytes(bytes: [u8; 48]) -> Self {
         let nanos = {
             let mut buf = [0u8; 8];
             buf.copy_from_slice(&bytes[0..8]);
@@ -456,10 +701,16 @@ impl BlockMetadata {
             buf.copy_from_slice(&bytes[8..40]);
             H256(buf)
         };
+        let epoch_height = {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&bytes[40..48]);
+            u64::from_be_bytes(buf)
+        };
 
         Self {
             timestamp: Timestamp::new(nanos),
             random_seed,
+            epoch_height,
         }
     }
 }
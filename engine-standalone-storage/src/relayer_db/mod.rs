@@ -51,6 +51,8 @@ where
             timestamp: env::Timestamp::new(row.timestamp.unwrap_or(0)),
             // TODO: need relayer to index this, tracking issue: https://github.com/aurora-is-near/aurora-relayer/issues/135
             random_seed: H256([0; 32]),
+            // TODO: need relayer to index this as well
+            epoch_height: 0,
         };
 
         storage
@@ -79,6 +81,7 @@ where
         predecessor_account_id,
         block_height: 0,
         block_timestamp: env::Timestamp::new(0),
+        epoch_height: 0,
         attached_deposit: 0,
         random_seed: H256::zero(),
         prepaid_gas: DEFAULT_PREPAID_GAS,
@@ -100,6 +103,7 @@ where
         env.block_height = block_height;
         env.block_timestamp = block_metadata.timestamp;
         env.random_seed = block_metadata.random_seed;
+        env.epoch_height = block_metadata.epoch_height;
 
         let result = storage.with_engine_access(block_height, transaction_position, &[], |io| {
             engine::submit(
@@ -215,6 +219,7 @@ mod test {
             owner_id: "aurora".parse().unwrap(),
             bridge_prover_id: "prover.bridge.near".parse().unwrap(),
             upgrade_delay_blocks: 0,
+            block_gas_limit: None,
         };
 
         // Initialize engine and connector states in storage.
@@ -225,6 +230,7 @@ mod test {
             let block_metadata = crate::BlockMetadata {
                 timestamp: aurora_engine_sdk::env::Timestamp::new(0),
                 random_seed: H256::zero(),
+                epoch_height: 0,
             };
             storage
                 .set_block_data(block_hash, block_height, block_metadata)
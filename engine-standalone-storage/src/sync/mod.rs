@@ -1,5 +1,8 @@
 use aurora_engine::{connector, engine, parameters::SubmitResult, xcc};
+use aurora_engine_precompiles::extensions::PrecompileExtensions;
 use aurora_engine_sdk::env::{self, Env, DEFAULT_PREPAID_GAS};
+use aurora_engine_sdk::promise::PromiseHandler;
+use aurora_engine_transactions::{eip_1559, eip_2930, EthTransactionKind};
 use aurora_engine_types::{
     account_id::AccountId,
     parameters::PromiseWithCallbackArgs,
@@ -9,8 +12,9 @@ use aurora_engine_types::{
 
 pub mod types;
 
+use crate::block_header::TransactionReceipt;
 use crate::engine_state::EngineStateAccess;
-use crate::{BlockMetadata, Diff, Storage};
+use crate::{BlockMetadata, Diff, Storage, TransactionIncluded};
 use types::{Message, TransactionKind, TransactionMessage};
 
 pub fn consume_message(
@@ -25,6 +29,10 @@ pub fn consume_message(
             storage
                 .set_block_data(block_hash, block_height, block_metadata)
                 .map_err(crate::Error::Rocksdb)?;
+            // The previous block is complete once its successor arrives, so its header is final.
+            if let Some(parent_hash) = storage.get_previous_block_hash(block_height)? {
+                storage.update_block_header(parent_hash)?;
+            }
             Ok(ConsumeMessageOutcome::BlockAdded)
         }
 
@@ -39,6 +47,7 @@ pub fn consume_message(
             let block_height = storage.get_block_height_by_hash(block_hash)?;
             let block_metadata = storage.get_block_metadata(block_hash)?;
             let engine_account_id = storage.get_engine_account_id()?;
+            let precompile_extensions = storage.precompile_extensions();
 
             let (tx_hash, diff, result) = storage
                 .with_engine_access(block_height, transaction_position, &[], |io| {
@@ -48,6 +57,7 @@ pub fn consume_message(
                         &block_metadata,
                         engine_account_id,
                         io,
+                        precompile_extensions,
                     )
                 })
                 .result;
@@ -55,6 +65,23 @@ pub fn consume_message(
                 Err(_) | Ok(Some(TransactionExecutionResult::Submit(Err(_)))) => (), // do not persist if Engine encounters an error
                 _ => storage.set_transaction_included(tx_hash, &transaction_message, &diff)?,
             }
+            if let (
+                TransactionKind::Submit(tx),
+                Ok(Some(TransactionExecutionResult::Submit(Ok(submit_result)))),
+            ) = (&transaction_message.transaction, result.as_ref())
+            {
+                let receipt = TransactionReceipt {
+                    transaction_type: transaction_type(tx),
+                    status: submit_result.status.is_ok(),
+                    gas_used: submit_result.gas_used,
+                    logs: submit_result.logs.clone(),
+                };
+                let tx_included = TransactionIncluded {
+                    block_hash,
+                    position: transaction_position,
+                };
+                storage.set_transaction_receipt(tx_included, &receipt)?;
+            }
             let outcome = TransactionIncludedOutcome {
                 hash: tx_hash,
                 info: *transaction_message,
@@ -77,6 +104,7 @@ pub fn execute_transaction_message(
     let block_height = storage.get_block_height_by_hash(block_hash)?;
     let block_metadata = storage.get_block_metadata(block_hash)?;
     let engine_account_id = storage.get_engine_account_id()?;
+    let precompile_extensions = storage.precompile_extensions();
     let result = storage.with_engine_access(block_height, transaction_position, &[], |io| {
         execute_transaction(
             &transaction_message,
@@ -84,6 +112,7 @@ pub fn execute_transaction_message(
             &block_metadata,
             engine_account_id,
             io,
+            precompile_extensions,
         )
     });
     let (tx_hash, diff, maybe_result) = result.result;
@@ -96,12 +125,22 @@ pub fn execute_transaction_message(
     Ok(outcome)
 }
 
+/// The EIP-2718 type of the transaction, or `None` for legacy transactions.
+fn transaction_type(tx: &EthTransactionKind) -> Option<u8> {
+    match tx {
+        EthTransactionKind::Legacy(_) => None,
+        EthTransactionKind::Eip2930(_) => Some(eip_2930::TYPE_BYTE),
+        EthTransactionKind::Eip1559(_) => Some(eip_1559::TYPE_BYTE),
+    }
+}
+
 fn execute_transaction<'db>(
     transaction_message: &TransactionMessage,
     block_height: u64,
     block_metadata: &BlockMetadata,
     engine_account_id: AccountId,
     io: EngineStateAccess<'db, 'db, 'db>,
+    precompile_extensions: &PrecompileExtensions,
 ) -> (
     H256,
     Diff,
@@ -119,6 +158,7 @@ fn execute_transaction<'db>(
         predecessor_account_id,
         block_height,
         block_timestamp: block_metadata.timestamp,
+        epoch_height: block_metadata.epoch_height,
         attached_deposit: transaction_message.attached_near,
         random_seed: block_metadata.random_seed,
         prepaid_gas: DEFAULT_PREPAID_GAS,
@@ -136,7 +176,7 @@ fn execute_transaction<'db>(
 
             let result = engine::get_state(&io)
                 .map(|engine_state| {
-                    let submit_result = engine::submit(
+                    let submit_result = engine::submit_with_precompile_extensions(
                         io,
                         &env,
                         &transaction_bytes,
@@ -144,6 +184,7 @@ fn execute_transaction<'db>(
                         env.current_account_id(),
                         relayer_address,
                         &mut handler,
+                        Some(precompile_extensions),
                     );
                     Some(TransactionExecutionResult::Submit(submit_result))
                 })
@@ -159,6 +200,7 @@ fn execute_transaction<'db>(
                 env,
                 relayer_address,
                 &transaction_message.promise_data,
+                precompile_extensions,
             );
             (near_receipt_id, result)
         }
@@ -178,13 +220,15 @@ fn non_submit_execute<'db>(
     env: env::Fixed,
     relayer_address: Address,
     promise_data: &[Option<Vec<u8>>],
+    precompile_extensions: &PrecompileExtensions,
 ) -> Result<Option<TransactionExecutionResult>, error::Error> {
     let result = match transaction {
         TransactionKind::Call(args) => {
             // We can ignore promises in the standalone engine (see above)
             let mut handler = crate::promise::NoScheduler { promise_data };
             let mut engine =
-                engine::Engine::new(relayer_address, env.current_account_id(), io, &env)?;
+                engine::Engine::new(relayer_address, env.current_account_id(), io, &env)?
+                    .with_precompile_extensions(precompile_extensions);
 
             let result = engine.call_with_args(args.clone(), &mut handler);
 
@@ -195,7 +239,8 @@ fn non_submit_execute<'db>(
             // We can ignore promises in the standalone engine (see above)
             let mut handler = crate::promise::NoScheduler { promise_data };
             let mut engine =
-                engine::Engine::new(relayer_address, env.current_account_id(), io, &env)?;
+                engine::Engine::new(relayer_address, env.current_account_id(), io, &env)?
+                    .with_precompile_extensions(precompile_extensions);
 
             let result = engine.deploy_code_with_input(input.clone(), &mut handler);
 
@@ -203,7 +248,7 @@ fn non_submit_execute<'db>(
         }
 
         TransactionKind::DeployErc20(args) => {
-            // No promises can be created by `deploy_erc20_token`
+            // The promise fetching the NEP-141 metadata can be ignored (see above)
             let mut handler = crate::promise::NoScheduler { promise_data };
             let result = engine::deploy_erc20_token(args.clone(), io, &env, &mut handler)?;
 
@@ -214,7 +259,8 @@ fn non_submit_execute<'db>(
             // No promises can be created by `ft_on_transfer`
             let mut handler = crate::promise::NoScheduler { promise_data };
             let mut engine =
-                engine::Engine::new(relayer_address, env.current_account_id(), io, &env)?;
+                engine::Engine::new(relayer_address, env.current_account_id(), io, &env)?
+                    .with_precompile_extensions(precompile_extensions);
 
             if env.predecessor_account_id == env.current_account_id {
                 connector::EthConnectorContract::init_instance(io)?
@@ -369,6 +415,33 @@ fn non_submit_execute<'db>(
 
             None
         }
+        TransactionKind::SetBlockGasLimit(args) => {
+            let mut state = engine::get_state(&io)?;
+            state.block_gas_limit = args.block_gas_limit;
+            engine::set_state(&mut io, state);
+
+            None
+        }
+        TransactionKind::SetCoinbase(args) => {
+            engine::set_coinbase(&mut io, env.block_height, args);
+
+            None
+        }
+        TransactionKind::NewEngineWithGenesis(args) => {
+            // The genesis accounts can only be installed on a new state.
+            if engine::get_state(&io).is_ok() {
+                return Err(error::Error::ContractInitialized);
+            }
+            engine::set_state(&mut io, args.new_args.clone().into());
+            engine::install_genesis(&mut io, &env.current_account_id, &args.genesis)?;
+
+            None
+        }
+        TransactionKind::SetPrecompileStatus(args) => {
+            engine::set_precompile_status(&mut io, args)?;
+
+            None
+        }
         TransactionKind::FactoryUpdate(bytecode) => {
             let router_bytecode = xcc::RouterCode::borrowed(bytecode);
             xcc::update_router_code(&mut io, &router_bytecode);
@@ -376,7 +449,8 @@ fn non_submit_execute<'db>(
             None
         }
         TransactionKind::FactoryUpdateAddressVersion(args) => {
-            xcc::set_code_version_of_address(&mut io, &args.address, args.version);
+            let handler = crate::promise::NoScheduler { promise_data };
+            xcc::handle_address_version_update(&mut io, &handler, args);
 
             None
         }
@@ -385,6 +459,40 @@ fn non_submit_execute<'db>(
 
             None
         }
+        TransactionKind::XccCallback(args) => {
+            let mut handler = crate::promise::NoScheduler { promise_data };
+            let address =
+                xcc::router_owner_address(&env.predecessor_account_id, &env.current_account_id);
+            // On NEAR the callback fails unless it comes from a router with a promise result,
+            // so otherwise there is nothing to execute.
+            match (address, handler.promise_result(0)) {
+                (Some(address), Some(promise_result)) => {
+                    let engine_state = engine::get_state(&io)?;
+                    let result = xcc::handle_evm_callback(
+                        io,
+                        &env,
+                        engine_state,
+                        address,
+                        args,
+                        &promise_result,
+                        &mut handler,
+                    );
+                    Some(TransactionExecutionResult::Submit(result))
+                }
+                _ => None,
+            }
+        }
+        TransactionKind::SetErc20Metadata(erc20_token) => {
+            let mut handler = crate::promise::NoScheduler { promise_data };
+            let maybe_metadata = handler
+                .promise_result(0)
+                .and_then(|result| engine::parse_nep141_metadata(&result));
+            if let Some(metadata) = maybe_metadata {
+                engine::set_erc20_metadata(*erc20_token, &metadata, io, &env, &mut handler)?;
+            }
+
+            None
+        }
         TransactionKind::Unknown => None,
         // Not handled in this function; is handled by the general `execute_transaction` function
         TransactionKind::Submit(_) => unreachable!(),
@@ -432,6 +540,9 @@ pub mod error {
         InvalidAddress(aurora_engine_types::types::address::error::AddressError),
         ConnectorInit(connector::error::InitContractError),
         ConnectorStorage(connector::error::StorageReadError),
+        PrecompileRegistry(aurora_engine_precompiles::registry::RegistryError),
+        Genesis(fungible_token::error::DepositError),
+        ContractInitialized,
     }
 
     impl From<engine::EngineStateError> for Error {
@@ -505,4 +616,16 @@ pub mod error {
             Self::ConnectorStorage(e)
         }
     }
+
+    impl From<aurora_engine_precompiles::registry::RegistryError> for Error {
+        fn from(e: aurora_engine_precompiles::registry::RegistryError) -> Self {
+            Self::PrecompileRegistry(e)
+        }
+    }
+
+    impl From<fungible_token::error::DepositError> for Error {
+        fn from(e: fungible_token::error::DepositError) -> Self {
+            Self::Genesis(e)
+        }
+    }
 }
@@ -109,11 +109,23 @@ pub enum TransactionKind {
     NewConnector(parameters::InitCallArgs),
     /// Initialize Engine
     NewEngine(parameters::NewCallArgs),
+    /// Update the block gas limit configuration
+    SetBlockGasLimit(parameters::SetBlockGasLimitArgs),
+    /// Update the coinbase address
+    SetCoinbase(parameters::SetCoinbaseArgs),
+    /// Initialize Engine and install the genesis accounts
+    NewEngineWithGenesis(parameters::NewWithGenesisCallArgs),
+    /// Update the precompile registry
+    SetPrecompileStatus(parameters::SetPrecompileStatusArgs),
     /// Update xcc-router bytecode
     FactoryUpdate(Vec<u8>),
     /// Update the version of a deployed xcc-router contract
     FactoryUpdateAddressVersion(AddressVersionUpdateArgs),
     FactorySetWNearAddress(types::Address),
+    /// Result of a cross contract call delivered back into the EVM by a router contract
+    XccCallback(aurora_engine_types::parameters::EvmCallbackArgs),
+    /// Callback with the metadata of the NEP-141 token of a new bridged token
+    SetErc20Metadata(types::Address),
     /// Sentinel kind for cases where a NEAR receipt caused a
     /// change in Aurora state, but we failed to parse the Action.
     Unknown,
@@ -244,6 +256,13 @@ enum BorshableTransactionKind<'a> {
     FactoryUpdateAddressVersion(Cow<'a, AddressVersionUpdateArgs>),
     FactorySetWNearAddress(types::Address),
     Unknown,
+    // New variants must be added at the end, since this type is stored in the DB.
+    SetBlockGasLimit(Cow<'a, parameters::SetBlockGasLimitArgs>),
+    SetCoinbase(Cow<'a, parameters::SetCoinbaseArgs>),
+    NewEngineWithGenesis(Cow<'a, parameters::NewWithGenesisCallArgs>),
+    SetPrecompileStatus(Cow<'a, parameters::SetPrecompileStatusArgs>),
+    XccCallback(Cow<'a, aurora_engine_types::parameters::EvmCallbackArgs>),
+    SetErc20Metadata(types::Address),
 }
 
 impl<'a> From<&'a TransactionKind> for BorshableTransactionKind<'a> {
@@ -274,6 +293,12 @@ impl<'a> From<&'a TransactionKind> for BorshableTransactionKind<'a> {
             TransactionKind::SetConnectorData(x) => Self::SetConnectorData(Cow::Borrowed(x)),
             TransactionKind::NewConnector(x) => Self::NewConnector(Cow::Borrowed(x)),
             TransactionKind::NewEngine(x) => Self::NewEngine(Cow::Borrowed(x)),
+            TransactionKind::SetBlockGasLimit(x) => Self::SetBlockGasLimit(Cow::Borrowed(x)),
+            TransactionKind::SetCoinbase(x) => Self::SetCoinbase(Cow::Borrowed(x)),
+            TransactionKind::NewEngineWithGenesis(x) => {
+                Self::NewEngineWithGenesis(Cow::Borrowed(x))
+            }
+            TransactionKind::SetPrecompileStatus(x) => Self::SetPrecompileStatus(Cow::Borrowed(x)),
             TransactionKind::FactoryUpdate(x) => Self::FactoryUpdate(Cow::Borrowed(x)),
             TransactionKind::FactoryUpdateAddressVersion(x) => {
                 Self::FactoryUpdateAddressVersion(Cow::Borrowed(x))
@@ -281,6 +306,8 @@ impl<'a> From<&'a TransactionKind> for BorshableTransactionKind<'a> {
             TransactionKind::FactorySetWNearAddress(address) => {
                 Self::FactorySetWNearAddress(*address)
             }
+            TransactionKind::XccCallback(x) => Self::XccCallback(Cow::Borrowed(x)),
+            TransactionKind::SetErc20Metadata(address) => Self::SetErc20Metadata(*address),
             TransactionKind::Unknown => Self::Unknown,
         }
     }
@@ -325,6 +352,16 @@ impl<'a> TryFrom<BorshableTransactionKind<'a>> for TransactionKind {
             }
             BorshableTransactionKind::NewConnector(x) => Ok(Self::NewConnector(x.into_owned())),
             BorshableTransactionKind::NewEngine(x) => Ok(Self::NewEngine(x.into_owned())),
+            BorshableTransactionKind::SetBlockGasLimit(x) => {
+                Ok(Self::SetBlockGasLimit(x.into_owned()))
+            }
+            BorshableTransactionKind::SetCoinbase(x) => Ok(Self::SetCoinbase(x.into_owned())),
+            BorshableTransactionKind::NewEngineWithGenesis(x) => {
+                Ok(Self::NewEngineWithGenesis(x.into_owned()))
+            }
+            BorshableTransactionKind::SetPrecompileStatus(x) => {
+                Ok(Self::SetPrecompileStatus(x.into_owned()))
+            }
             BorshableTransactionKind::FactoryUpdate(x) => Ok(Self::FactoryUpdate(x.into_owned())),
             BorshableTransactionKind::FactoryUpdateAddressVersion(x) => {
                 Ok(Self::FactoryUpdateAddressVersion(x.into_owned()))
@@ -333,6 +370,55 @@ impl<'a> TryFrom<BorshableTransactionKind<'a>> for TransactionKind {
                 Ok(Self::FactorySetWNearAddress(address))
             }
             BorshableTransactionKind::Unknown => Ok(Self::Unknown),
+            BorshableTransactionKind::XccCallback(x) => Ok(Self::XccCallback(x.into_owned())),
+            BorshableTransactionKind::SetErc20Metadata(address) => {
+                Ok(Self::SetErc20Metadata(address))
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{TransactionKind, TransactionMessage};
+    use aurora_engine_types::account_id::AccountId;
+    use aurora_engine_types::H256;
+    use borsh::BorshSerialize;
+
+    /// Bytes of a `TransactionMessage` with the given kind, written the way the messages stored
+    /// before any `TransactionKind` was added are.
+    fn stored_message(kind_index: u8, kind_data: &[u8]) -> Vec<u8> {
+        let account_id: AccountId = "aurora".parse().unwrap();
+        let account_id = account_id.try_to_vec().unwrap();
+        [
+            &[1][..], // V2
+            &[1; 32], // block_hash
+            &[2; 32], // near_receipt_id
+            &3u16.to_le_bytes(),
+            &[1], // succeeded
+            &account_id,
+            &account_id,
+            &0u128.to_le_bytes(),
+            &[kind_index],
+            kind_data,
+            &0u32.to_le_bytes(), // promise_data
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_decode_stored_transaction_kinds() {
+        let code = vec![0xaa, 0xbb];
+        let bytes = stored_message(20, &code.try_to_vec().unwrap());
+        let message = TransactionMessage::try_from_slice(&bytes).unwrap();
+        assert_eq!(message.block_hash, H256([1; 32]));
+        assert_eq!(message.position, 3);
+        assert_eq!(message.transaction, TransactionKind::FactoryUpdate(code));
+        // Stored messages still round trip
+        assert_eq!(message.to_bytes(), bytes);
+
+        let bytes = stored_message(23, &[]);
+        let message = TransactionMessage::try_from_slice(&bytes).unwrap();
+        assert_eq!(message.transaction, TransactionKind::Unknown);
+    }
+}
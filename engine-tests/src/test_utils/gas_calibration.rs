@@ -0,0 +1,184 @@
+//! Calibration of the EVM gas charged by precompiles against the NEAR gas they burn.
+//!
+//! Precompiles which run inside the engine's wasm are priced by their NEAR gas cost: the
+//! precompile is called with inputs of different sizes, the NEAR gas used on top of a baseline
+//! transaction is measured, and a line `NEAR_gas = base + size * per_unit` is fitted by least
+//! squares. The line is then converted to EVM gas with `NEAR_GAS_PER_EVM_GAS`.
+
+use crate::prelude::{transactions::legacy::TransactionLegacy, Address, Wei, U256};
+use crate::test_utils::{self, AuroraRunner, Signer};
+use aurora_engine::parameters::SubmitResult;
+use aurora_engine_types::types::EthGas;
+
+/// The same ratio as the one used to charge for the NEAR gas attached to cross-contract calls.
+pub(crate) const NEAR_GAS_PER_EVM_GAS: u64 =
+    aurora_engine_precompiles::xcc::costs::CROSS_CONTRACT_CALL_NEAR_GAS;
+
+/// NEAR gas to subtract from the measured transactions, to isolate the precompile itself.
+pub(crate) enum Baseline {
+    /// NEAR gas of the same input sent to the given account, which must have no code.
+    /// It is measured again for every sample.
+    EmptyAccount(Address),
+    /// NEAR gas measured once, e.g. of a transaction making the same EVM subcalls as the
+    /// precompile (which are charged separately).
+    Fixed(u64),
+}
+
+struct Sample {
+    size: u64,
+    /// NEAR gas used by the precompile, without the baseline.
+    near_gas: u64,
+    /// NEAR gas used by the whole transaction.
+    total_near_gas: u64,
+    evm_gas: u64,
+}
+
+/// Line fitted through the samples, in NEAR gas.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LinearFit {
+    pub base: f64,
+    pub per_unit: f64,
+}
+
+pub(crate) struct GasCalibration {
+    address: Address,
+    baseline: Baseline,
+    samples: Vec<Sample>,
+}
+
+impl GasCalibration {
+    pub fn new(address: Address, baseline: Baseline) -> Self {
+        Self {
+            address,
+            baseline,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Calls the precompile with the given input, which must succeed, and records the NEAR gas
+    /// used for an input of the given size (in whatever unit the cost is charged for).
+    pub fn sample(
+        &mut self,
+        runner: &mut AuroraRunner,
+        signer: &mut Signer,
+        size: u64,
+        input: Vec<u8>,
+    ) -> SubmitResult {
+        let baseline = match self.baseline {
+            Baseline::EmptyAccount(address) => {
+                let (_, profile) = runner
+                    .submit_with_signer_profiled(signer, call_transaction(address, input.clone()))
+                    .unwrap();
+                profile.all_gas()
+            }
+            Baseline::Fixed(near_gas) => near_gas,
+        };
+        let (result, profile) = runner
+            .submit_with_signer_profiled(signer, call_transaction(self.address, input))
+            .unwrap();
+        assert!(
+            result.status.is_ok(),
+            "Unexpected status: {:?}",
+            result.status
+        );
+        self.samples.push(Sample {
+            size,
+            near_gas: profile.all_gas() - baseline,
+            total_near_gas: profile.all_gas(),
+            evm_gas: result.gas_used,
+        });
+        result
+    }
+
+    /// Least squares line through the samples, which must have at least two different sizes.
+    pub fn fit(&self) -> LinearFit {
+        let n = self.samples.len() as f64;
+        let mean_size = self.samples.iter().map(|s| s.size as f64).sum::<f64>() / n;
+        let mean_gas = self.samples.iter().map(|s| s.near_gas as f64).sum::<f64>() / n;
+        let (covariance, variance) =
+            self.samples
+                .iter()
+                .fold((0.0, 0.0), |(covariance, variance), sample| {
+                    let dx = sample.size as f64 - mean_size;
+                    let dy = sample.near_gas as f64 - mean_gas;
+                    (covariance + dx * dy, variance + dx * dx)
+                });
+        assert!(variance > 0.0, "Samples must have different sizes");
+        let per_unit = covariance / variance;
+        LinearFit {
+            base: mean_gas - per_unit * mean_size,
+            per_unit,
+        }
+    }
+
+    /// Panics if the given costs drift from the fitted ones by more than `tolerance_percent`.
+    /// Since the base cost is a whole amount of EVM gas, it may also be off by one unit.
+    pub fn assert_cost(
+        &self,
+        name: &str,
+        tolerance_percent: u64,
+        base: EthGas,
+        per_unit: Option<EthGas>,
+    ) {
+        let fit = self.fit();
+        assert_close(
+            &format!("{} base", name),
+            tolerance_percent,
+            1.0,
+            fit.base,
+            base,
+        );
+        if let Some(per_unit) = per_unit {
+            assert_close(
+                &format!("{} per unit", name),
+                tolerance_percent,
+                0.0,
+                fit.per_unit,
+                per_unit,
+            );
+        }
+    }
+
+    /// Panics if the EVM gas charged for a sample is not within `tolerance_percent` of the NEAR
+    /// gas used by its transaction, converted to EVM gas.
+    pub fn assert_evm_gas(&self, tolerance_percent: u64) {
+        for sample in &self.samples {
+            let expected = sample.total_near_gas / NEAR_GAS_PER_EVM_GAS;
+            assert!(
+                test_utils::within_x_percent(tolerance_percent, sample.evm_gas, expected),
+                "Incorrect EVM gas used. Expected: {} Actual: {}",
+                expected,
+                sample.evm_gas
+            );
+        }
+    }
+}
+
+/// `slack` is an absolute amount of EVM gas allowed on top of the relative tolerance.
+fn assert_close(name: &str, tolerance_percent: u64, slack: f64, near_gas: f64, cost: EthGas) {
+    let measured = near_gas / NEAR_GAS_PER_EVM_GAS as f64;
+    let actual = cost.as_u64() as f64;
+    let tolerance = measured.abs().max(actual) * tolerance_percent as f64 / 100.0 + slack;
+    assert!(
+        (measured - actual).abs() <= tolerance,
+        "Incorrect {} cost. Expected: {} Actual: {}",
+        name,
+        measured,
+        cost
+    );
+}
+
+/// Transaction calling `to` with the given data, without any gas limit.
+pub(crate) fn call_transaction(
+    to: Address,
+    data: Vec<u8>,
+) -> impl FnOnce(U256) -> TransactionLegacy {
+    move |nonce| TransactionLegacy {
+        nonce,
+        gas_price: U256::zero(),
+        gas_limit: u64::MAX.into(),
+        to: Some(to),
+        value: Wei::zero(),
+        data,
+    }
+}
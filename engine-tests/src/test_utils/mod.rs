@@ -33,9 +33,17 @@ pub fn origin() -> String {
 pub(crate) const SUBMIT: &str = "submit";
 pub(crate) const CALL: &str = "call";
 pub(crate) const DEPLOY_ERC20: &str = "deploy_erc20_token";
+pub(crate) const XCC_CALLBACK: &str = "xcc_callback";
+pub(crate) const SET_ERC20_METADATA: &str = "set_erc20_metadata";
+pub(crate) const SET_BLOCK_GAS_LIMIT: &str = "set_block_gas_limit";
+pub(crate) const SET_COINBASE: &str = "set_coinbase";
+pub(crate) const SET_PRECOMPILE_STATUS: &str = "set_precompile_status";
+pub(crate) const FACTORY_UPDATE: &str = "factory_update";
+pub(crate) const FACTORY_SET_WNEAR_ADDRESS: &str = "factory_set_wnear_address";
 
 pub(crate) mod erc20;
 pub(crate) mod exit_precompile;
+pub(crate) mod gas_calibration;
 pub(crate) mod mocked_external;
 pub(crate) mod one_inch;
 pub(crate) mod random;
@@ -223,7 +231,16 @@ impl AuroraRunner {
 
         if let Some(standalone_runner) = &mut self.standalone_runner {
             if maybe_error.is_none()
-                && (method_name == SUBMIT || method_name == CALL || method_name == DEPLOY_ERC20)
+                && (method_name == SUBMIT
+                    || method_name == CALL
+                    || method_name == DEPLOY_ERC20
+                    || method_name == XCC_CALLBACK
+                    || method_name == SET_ERC20_METADATA
+                    || method_name == SET_BLOCK_GAS_LIMIT
+                    || method_name == SET_COINBASE
+                    || method_name == SET_PRECOMPILE_STATUS
+                    || method_name == FACTORY_UPDATE
+                    || method_name == FACTORY_SET_WNEAR_ADDRESS)
             {
                 standalone_runner
                     .submit_raw(method_name, &self.context, &self.promise_results)
@@ -28,6 +28,7 @@ pub fn insert_block(storage: &mut Storage, block_height: u64) {
     let block_metadata = BlockMetadata {
         timestamp: aurora_engine_sdk::env::Timestamp::new(0),
         random_seed: H256::zero(),
+        epoch_height: 0,
     };
     storage
         .set_block_data(block_hash, block_height, block_metadata)
@@ -45,6 +46,7 @@ pub fn default_env(block_height: u64) -> aurora_engine_sdk::env::Fixed {
         predecessor_account_id: aurora_id,
         block_height,
         block_timestamp: aurora_engine_sdk::env::Timestamp::new(0),
+        epoch_height: 0,
         attached_deposit: 0,
         random_seed: H256::zero(),
         prepaid_gas: DEFAULT_PREPAID_GAS,
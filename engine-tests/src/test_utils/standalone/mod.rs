@@ -1,7 +1,11 @@
 use aurora_engine::engine;
-use aurora_engine::parameters::{CallArgs, DeployErc20TokenArgs, SubmitResult, TransactionStatus};
+use aurora_engine::parameters::{
+    CallArgs, DeployErc20TokenArgs, SetBlockGasLimitArgs, SetCoinbaseArgs, SetPrecompileStatusArgs,
+    SubmitResult, TransactionStatus,
+};
 use aurora_engine_sdk::env::{self, Env};
 use aurora_engine_transactions::legacy::{LegacyEthSignedTransaction, TransactionLegacy};
+use aurora_engine_types::parameters::EvmCallbackArgs;
 use aurora_engine_types::types::{Address, NearGas, PromiseResult, Wei};
 use aurora_engine_types::{H256, U256};
 use borsh::BorshDeserialize;
@@ -27,6 +31,9 @@ pub struct StandaloneRunner {
     pub chain_id: u64,
     // Cumulative diff from all transactions (ie full state representation)
     pub cumulative_diff: Diff,
+    // Block height and position of the last transaction given to `submit_raw`, so that
+    // transactions of the same block are executed one after the other.
+    last_transaction_position: Option<(u64, u16)>,
 }
 
 impl StandaloneRunner {
@@ -190,17 +197,23 @@ impl StandaloneRunner {
         env.block_height = ctx.block_index;
         env.attached_deposit = ctx.attached_deposit;
         env.block_timestamp = aurora_engine_sdk::env::Timestamp::new(ctx.block_timestamp);
+        env.epoch_height = ctx.epoch_height;
         env.predecessor_account_id = ctx.predecessor_account_id.as_ref().parse().unwrap();
         env.current_account_id = ctx.current_account_id.as_ref().parse().unwrap();
         env.signer_account_id = ctx.signer_account_id.as_ref().parse().unwrap();
         env.prepaid_gas = NearGas::new(ctx.prepaid_gas);
+        let transaction_position = match self.last_transaction_position {
+            Some((block_height, position)) if block_height == env.block_height => position + 1,
+            _ => 0,
+        };
+        self.last_transaction_position = Some((env.block_height, transaction_position));
 
         let storage = &mut self.storage;
         if method_name == test_utils::SUBMIT {
             let transaction_bytes = &ctx.input;
             Self::internal_submit_transaction(
                 transaction_bytes,
-                0,
+                transaction_position,
                 storage,
                 &mut env,
                 &mut self.cumulative_diff,
@@ -209,8 +222,13 @@ impl StandaloneRunner {
         } else if method_name == test_utils::CALL {
             let call_args = CallArgs::try_from_slice(&ctx.input).unwrap();
             let transaction_hash = aurora_engine_sdk::keccak(&ctx.input);
-            let mut tx_msg =
-                Self::template_tx_msg(storage, &env, 0, transaction_hash, promise_results);
+            let mut tx_msg = Self::template_tx_msg(
+                storage,
+                &env,
+                transaction_position,
+                transaction_hash,
+                promise_results,
+            );
             tx_msg.transaction = TransactionKind::Call(call_args);
 
             let outcome = sync::execute_transaction_message(storage, tx_msg).unwrap();
@@ -221,8 +239,13 @@ impl StandaloneRunner {
         } else if method_name == test_utils::DEPLOY_ERC20 {
             let deploy_args = DeployErc20TokenArgs::try_from_slice(&ctx.input).unwrap();
             let transaction_hash = aurora_engine_sdk::keccak(&ctx.input);
-            let mut tx_msg =
-                Self::template_tx_msg(storage, &env, 0, transaction_hash, promise_results);
+            let mut tx_msg = Self::template_tx_msg(
+                storage,
+                &env,
+                transaction_position,
+                transaction_hash,
+                promise_results,
+            );
             tx_msg.transaction = TransactionKind::DeployErc20(deploy_args);
 
             let outcome = sync::execute_transaction_message(storage, tx_msg).unwrap();
@@ -238,6 +261,154 @@ impl StandaloneRunner {
                 0,
                 Vec::new(),
             ))
+        } else if method_name == test_utils::XCC_CALLBACK {
+            let callback_args = EvmCallbackArgs::try_from_slice(&ctx.input).unwrap();
+            let transaction_hash = aurora_engine_sdk::keccak(&ctx.input);
+            let mut tx_msg = Self::template_tx_msg(
+                storage,
+                &env,
+                transaction_position,
+                transaction_hash,
+                promise_results,
+            );
+            tx_msg.transaction = TransactionKind::XccCallback(callback_args);
+
+            let outcome = sync::execute_transaction_message(storage, tx_msg).unwrap();
+            self.cumulative_diff.append(outcome.diff.clone());
+            test_utils::standalone::storage::commit(storage, &outcome);
+
+            unwrap_result(outcome)
+        } else if method_name == test_utils::SET_ERC20_METADATA {
+            let erc20_token = Address::try_from_slice(&ctx.input).unwrap();
+            let transaction_hash = aurora_engine_sdk::keccak(&ctx.input);
+            let mut tx_msg = Self::template_tx_msg(
+                storage,
+                &env,
+                transaction_position,
+                transaction_hash,
+                promise_results,
+            );
+            tx_msg.transaction = TransactionKind::SetErc20Metadata(erc20_token);
+
+            let outcome = sync::execute_transaction_message(storage, tx_msg).unwrap();
+            assert!(outcome.maybe_result.is_ok());
+            self.cumulative_diff.append(outcome.diff.clone());
+            test_utils::standalone::storage::commit(storage, &outcome);
+
+            Ok(SubmitResult::new(
+                TransactionStatus::Succeed(Vec::new()),
+                0,
+                Vec::new(),
+            ))
+        } else if method_name == test_utils::SET_BLOCK_GAS_LIMIT {
+            let args = SetBlockGasLimitArgs::try_from_slice(&ctx.input).unwrap();
+            let transaction_hash = aurora_engine_sdk::keccak(&ctx.input);
+            let mut tx_msg = Self::template_tx_msg(
+                storage,
+                &env,
+                transaction_position,
+                transaction_hash,
+                promise_results,
+            );
+            tx_msg.transaction = TransactionKind::SetBlockGasLimit(args);
+
+            let outcome = sync::execute_transaction_message(storage, tx_msg).unwrap();
+            assert!(outcome.maybe_result.is_ok());
+            self.cumulative_diff.append(outcome.diff.clone());
+            test_utils::standalone::storage::commit(storage, &outcome);
+
+            Ok(SubmitResult::new(
+                TransactionStatus::Succeed(Vec::new()),
+                0,
+                Vec::new(),
+            ))
+        } else if method_name == test_utils::SET_COINBASE {
+            let args = SetCoinbaseArgs::try_from_slice(&ctx.input).unwrap();
+            let transaction_hash = aurora_engine_sdk::keccak(&ctx.input);
+            let mut tx_msg = Self::template_tx_msg(
+                storage,
+                &env,
+                transaction_position,
+                transaction_hash,
+                promise_results,
+            );
+            tx_msg.transaction = TransactionKind::SetCoinbase(args);
+
+            let outcome = sync::execute_transaction_message(storage, tx_msg).unwrap();
+            assert!(outcome.maybe_result.is_ok());
+            self.cumulative_diff.append(outcome.diff.clone());
+            test_utils::standalone::storage::commit(storage, &outcome);
+
+            Ok(SubmitResult::new(
+                TransactionStatus::Succeed(Vec::new()),
+                0,
+                Vec::new(),
+            ))
+        } else if method_name == test_utils::SET_PRECOMPILE_STATUS {
+            let args = SetPrecompileStatusArgs::try_from_slice(&ctx.input).unwrap();
+            let transaction_hash = aurora_engine_sdk::keccak(&ctx.input);
+            let mut tx_msg = Self::template_tx_msg(
+                storage,
+                &env,
+                transaction_position,
+                transaction_hash,
+                promise_results,
+            );
+            tx_msg.transaction = TransactionKind::SetPrecompileStatus(args);
+
+            let outcome = sync::execute_transaction_message(storage, tx_msg).unwrap();
+            assert!(outcome.maybe_result.is_ok());
+            self.cumulative_diff.append(outcome.diff.clone());
+            test_utils::standalone::storage::commit(storage, &outcome);
+
+            Ok(SubmitResult::new(
+                TransactionStatus::Succeed(Vec::new()),
+                0,
+                Vec::new(),
+            ))
+        } else if method_name == test_utils::FACTORY_UPDATE {
+            let transaction_hash = aurora_engine_sdk::keccak(&ctx.input);
+            let mut tx_msg = Self::template_tx_msg(
+                storage,
+                &env,
+                transaction_position,
+                transaction_hash,
+                promise_results,
+            );
+            tx_msg.transaction = TransactionKind::FactoryUpdate(ctx.input.clone());
+
+            let outcome = sync::execute_transaction_message(storage, tx_msg).unwrap();
+            assert!(outcome.maybe_result.is_ok());
+            self.cumulative_diff.append(outcome.diff.clone());
+            test_utils::standalone::storage::commit(storage, &outcome);
+
+            Ok(SubmitResult::new(
+                TransactionStatus::Succeed(Vec::new()),
+                0,
+                Vec::new(),
+            ))
+        } else if method_name == test_utils::FACTORY_SET_WNEAR_ADDRESS {
+            let address = Address::try_from_slice(&ctx.input).unwrap();
+            let transaction_hash = aurora_engine_sdk::keccak(&ctx.input);
+            let mut tx_msg = Self::template_tx_msg(
+                storage,
+                &env,
+                transaction_position,
+                transaction_hash,
+                promise_results,
+            );
+            tx_msg.transaction = TransactionKind::FactorySetWNearAddress(address);
+
+            let outcome = sync::execute_transaction_message(storage, tx_msg).unwrap();
+            assert!(outcome.maybe_result.is_ok());
+            self.cumulative_diff.append(outcome.diff.clone());
+            test_utils::standalone::storage::commit(storage, &outcome);
+
+            Ok(SubmitResult::new(
+                TransactionStatus::Succeed(Vec::new()),
+                0,
+                Vec::new(),
+            ))
         } else {
             panic!("Unsupported standalone method {}", method_name);
         }
@@ -287,6 +458,7 @@ impl StandaloneRunner {
         let block_metadata = BlockMetadata {
             timestamp: env.block_timestamp,
             random_seed: env.random_seed,
+            epoch_height: env.epoch_height,
         };
         storage
             .set_block_data(block_hash, env.block_height, block_metadata)
@@ -358,6 +530,7 @@ impl Default for StandaloneRunner {
             env,
             chain_id,
             cumulative_diff: Diff::default(),
+            last_transaction_position: None,
         }
     }
 }
@@ -0,0 +1,342 @@
+use crate::test_utils::{self, standalone, AuroraRunner, Signer};
+use aurora_engine::parameters::{DeployErc20TokenArgs, RefreshErc20MetadataArgs};
+use aurora_engine_precompiles::address_resolver::{self, selectors};
+use aurora_engine_transactions::legacy::TransactionLegacy;
+use aurora_engine_types::types::{Address, PromiseResult, Wei};
+use aurora_engine_types::U256;
+use borsh::{BorshDeserialize, BorshSerialize};
+use ethabi::{ParamType, Token};
+use near_primitives::transaction::Action;
+use near_vm_logic::VMOutcome;
+
+fn deploy_erc20(runner: &mut AuroraRunner, nep141: &str) -> (Address, VMOutcome) {
+    let engine_account = runner.aurora_account_id.clone();
+    let args = DeployErc20TokenArgs {
+        nep141: nep141.parse().unwrap(),
+    };
+    let (maybe_output, maybe_error) = runner.call(
+        test_utils::DEPLOY_ERC20,
+        &engine_account,
+        args.try_to_vec().unwrap(),
+    );
+    assert!(maybe_error.is_none());
+    let output = maybe_output.unwrap();
+    let bytes: Vec<u8> =
+        BorshDeserialize::try_from_slice(&output.return_data.as_value().unwrap()).unwrap();
+    (Address::try_from_slice(&bytes).unwrap(), output)
+}
+
+fn call_contract(
+    runner: &mut AuroraRunner,
+    signer: &mut Signer,
+    to: Address,
+    data: Vec<u8>,
+    outputs: &[ParamType],
+) -> Vec<Token> {
+    let transaction = TransactionLegacy {
+        nonce: signer.use_nonce().into(),
+        gas_price: U256::zero(),
+        gas_limit: u64::MAX.into(),
+        to: Some(to),
+        value: Wei::zero(),
+        data,
+    };
+    let result = runner
+        .submit_transaction(&signer.secret_key, transaction)
+        .unwrap();
+    ethabi::decode(outputs, test_utils::unwrap_success_slice(&result)).unwrap()
+}
+
+/// Function calls scheduled by the outcome, as (receiver, method, args).
+fn function_calls(outcome: &VMOutcome) -> Vec<(&str, &str, &[u8])> {
+    outcome
+        .action_receipts
+        .iter()
+        .map(|(receiver_id, receipt)| match &receipt.actions[0] {
+            Action::FunctionCall(function_call) => (
+                receiver_id.as_str(),
+                function_call.method_name.as_str(),
+                function_call.args.as_slice(),
+            ),
+            other => panic!("Unexpected action {:?}", other),
+        })
+        .collect()
+}
+
+/// Runs the `set_erc20_metadata` callback with the given `ft_metadata` result.
+fn set_erc20_metadata(runner: &mut AuroraRunner, erc20_address: Address, ft_metadata: &str) {
+    let engine_account = runner.aurora_account_id.clone();
+    runner.promise_results = vec![PromiseResult::Successful(ft_metadata.as_bytes().to_vec())];
+    let (_, maybe_error) = runner.call(
+        test_utils::SET_ERC20_METADATA,
+        &engine_account,
+        erc20_address.try_to_vec().unwrap(),
+    );
+    assert!(maybe_error.is_none());
+    runner.promise_results.clear();
+}
+
+/// Name, symbol and decimals of the ERC-20 token.
+fn erc20_metadata(
+    runner: &mut AuroraRunner,
+    signer: &mut Signer,
+    erc20_address: Address,
+) -> Vec<Token> {
+    [
+        ("name()", ParamType::String),
+        ("symbol()", ParamType::String),
+        ("decimals()", ParamType::Uint(8)),
+    ]
+    .into_iter()
+    .map(|(signature, output)| {
+        let data = aurora_engine_sdk::keccak(signature.as_bytes())[..4].to_vec();
+        call_contract(runner, signer, erc20_address, data, &[output])
+            .pop()
+            .unwrap()
+    })
+    .collect()
+}
+
+#[test]
+fn test_address_resolver_precompile() {
+    let mut signer = test_utils::Signer::random();
+    let mut runner = test_utils::deploy_evm();
+    runner.context.block_index = aurora_engine::engine::EXTENDED_PRECOMPILES_HEIGHT;
+    let mut standalone = standalone::StandaloneRunner::default();
+
+    standalone.init_evm();
+    runner.standalone_runner = Some(standalone);
+
+    let (erc20_address, _) = deploy_erc20(&mut runner, "token.near");
+
+    let mut call = |runner: &mut AuroraRunner, selector: [u8; 4], arg: Token, output: ParamType| {
+        let data = [selector.to_vec(), ethabi::encode(&[arg])].concat();
+        call_contract(
+            runner,
+            &mut signer,
+            address_resolver::ADDRESS,
+            data,
+            &[output],
+        )
+        .pop()
+        .unwrap()
+    };
+
+    assert_eq!(
+        call(
+            &mut runner,
+            selectors::NEP141_TO_ERC20,
+            Token::String("token.near".into()),
+            ParamType::Address,
+        ),
+        Token::Address(erc20_address.raw())
+    );
+    assert_eq!(
+        call(
+            &mut runner,
+            selectors::ERC20_TO_NEP141,
+            Token::Address(erc20_address.raw()),
+            ParamType::String,
+        ),
+        Token::String("token.near".into())
+    );
+
+    // Tokens which are not bridged resolve to empty values
+    assert_eq!(
+        call(
+            &mut runner,
+            selectors::NEP141_TO_ERC20,
+            Token::String("other.near".into()),
+            ParamType::Address,
+        ),
+        Token::Address(Address::zero().raw())
+    );
+    assert_eq!(
+        call(
+            &mut runner,
+            selectors::ERC20_TO_NEP141,
+            Token::Address(Address::from_array([1; 20]).raw()),
+            ParamType::String,
+        ),
+        Token::String(String::new())
+    );
+
+    assert_eq!(
+        call(
+            &mut runner,
+            selectors::NEAR_ACCOUNT_TO_EVM_ADDRESS,
+            Token::String("alice.near".into()),
+            ParamType::Address,
+        ),
+        Token::Address(aurora_engine_sdk::types::near_account_to_evm_address(b"alice.near").raw())
+    );
+}
+
+#[test]
+fn test_erc20_metadata() {
+    let mut signer = test_utils::Signer::random();
+    let mut runner = test_utils::deploy_evm();
+    runner.context.block_index = aurora_engine::engine::EXTENDED_PRECOMPILES_HEIGHT;
+    let mut standalone = standalone::StandaloneRunner::default();
+
+    standalone.init_evm();
+    runner.standalone_runner = Some(standalone);
+
+    let engine_account = runner.aurora_account_id.clone();
+    let (erc20_address, outcome) = deploy_erc20(&mut runner, "token.near");
+
+    // Deploying the token fetches the metadata of the NEP-141 token
+    let erc20_address_args = erc20_address.try_to_vec().unwrap();
+    assert_eq!(
+        function_calls(&outcome),
+        vec![
+            ("token.near", "ft_metadata", &[][..]),
+            (
+                engine_account.as_str(),
+                "set_erc20_metadata",
+                erc20_address_args.as_slice()
+            )
+        ]
+    );
+
+    let metadata = |runner: &mut AuroraRunner, signer: &mut Signer| {
+        let data = [
+            selectors::ERC20_METADATA.to_vec(),
+            ethabi::encode(&[Token::Address(erc20_address.raw())]),
+        ]
+        .concat();
+        let outputs = [
+            ParamType::String,
+            ParamType::String,
+            ParamType::Uint(8),
+            ParamType::String,
+        ];
+        call_contract(runner, signer, address_resolver::ADDRESS, data, &outputs)
+    };
+    // The metadata is empty until the callback runs
+    assert_eq!(
+        metadata(&mut runner, &mut signer),
+        vec![
+            Token::String(String::new()),
+            Token::String(String::new()),
+            Token::Uint(0.into()),
+            Token::String(String::new()),
+        ]
+    );
+
+    // Invalid metadata is ignored
+    set_erc20_metadata(&mut runner, erc20_address, r#"{"name": "Token"}"#);
+    assert_eq!(
+        erc20_metadata(&mut runner, &mut signer, erc20_address),
+        vec![
+            Token::String("Empty".into()),
+            Token::String("EMPTY".into()),
+            Token::Uint(0.into()),
+        ]
+    );
+
+    let ft_metadata = r#"{
+        "spec": "ft-1.0.0",
+        "name": "Wrapped NEAR fungible token",
+        "symbol": "wNEAR",
+        "icon": "data:image/svg+xml,token",
+        "reference": null,
+        "reference_hash": null,
+        "decimals": 24
+    }"#;
+    set_erc20_metadata(&mut runner, erc20_address, ft_metadata);
+
+    assert_eq!(
+        metadata(&mut runner, &mut signer),
+        vec![
+            Token::String("Wrapped NEAR fungible token".into()),
+            Token::String("wNEAR".into()),
+            Token::Uint(24.into()),
+            Token::String("data:image/svg+xml,token".into()),
+        ]
+    );
+    assert_eq!(
+        erc20_metadata(&mut runner, &mut signer, erc20_address),
+        vec![
+            Token::String("Wrapped NEAR fungible token".into()),
+            Token::String("wNEAR".into()),
+            Token::Uint(24.into()),
+        ]
+    );
+
+    // Only the engine can set the metadata
+    let (_, maybe_error) = runner.call(
+        test_utils::SET_ERC20_METADATA,
+        "alice.near",
+        erc20_address.try_to_vec().unwrap(),
+    );
+    assert!(maybe_error.is_some());
+}
+
+#[test]
+fn test_refresh_erc20_metadata() {
+    let mut signer = test_utils::Signer::random();
+    let mut runner = test_utils::deploy_evm();
+    let mut standalone = standalone::StandaloneRunner::default();
+
+    standalone.init_evm();
+    runner.standalone_runner = Some(standalone);
+
+    let engine_account = runner.aurora_account_id.clone();
+    let (erc20_address, _) = deploy_erc20(&mut runner, "token.near");
+    set_erc20_metadata(
+        &mut runner,
+        erc20_address,
+        r#"{"name": "Token", "symbol": "TKN", "decimals": 6}"#,
+    );
+
+    let refresh = |runner: &mut AuroraRunner, nep141: &str| {
+        let args = RefreshErc20MetadataArgs {
+            nep141: nep141.parse().unwrap(),
+        };
+        runner.call(
+            "refresh_erc20_metadata",
+            "alice.near",
+            args.try_to_vec().unwrap(),
+        )
+    };
+
+    // Anyone can fetch the metadata of a bridged token again
+    let (maybe_output, maybe_error) = refresh(&mut runner, "token.near");
+    assert!(maybe_error.is_none());
+    let outcome = maybe_output.unwrap();
+    let bytes: Vec<u8> =
+        BorshDeserialize::try_from_slice(&outcome.return_data.as_value().unwrap()).unwrap();
+    assert_eq!(Address::try_from_slice(&bytes).unwrap(), erc20_address);
+    let erc20_address_args = erc20_address.try_to_vec().unwrap();
+    assert_eq!(
+        function_calls(&outcome),
+        vec![
+            ("token.near", "ft_metadata", &[][..]),
+            (
+                engine_account.as_str(),
+                "set_erc20_metadata",
+                erc20_address_args.as_slice()
+            )
+        ]
+    );
+
+    // The ERC-20 token gets the new metadata from the callback
+    set_erc20_metadata(
+        &mut runner,
+        erc20_address,
+        r#"{"name": "Renamed Token", "symbol": "RTKN", "decimals": 6}"#,
+    );
+    assert_eq!(
+        erc20_metadata(&mut runner, &mut signer, erc20_address),
+        vec![
+            Token::String("Renamed Token".into()),
+            Token::String("RTKN".into()),
+            Token::Uint(6.into()),
+        ]
+    );
+
+    // Tokens which are not bridged can't be refreshed
+    let (_, maybe_error) = refresh(&mut runner, "other.near");
+    assert!(maybe_error.is_some());
+}
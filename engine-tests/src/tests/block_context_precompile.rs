@@ -0,0 +1,44 @@
+use crate::test_utils::{self, standalone};
+use aurora_engine_precompiles::block_context::{self, selectors};
+use aurora_engine_transactions::legacy::TransactionLegacy;
+use aurora_engine_types::{types::Wei, U256};
+
+#[test]
+fn test_block_context_precompile() {
+    let mut signer = test_utils::Signer::random();
+    let mut runner = test_utils::deploy_evm();
+    let mut standalone = standalone::StandaloneRunner::default();
+
+    standalone.init_evm();
+    runner.standalone_runner = Some(standalone);
+
+    const EPOCH_HEIGHT: u64 = 1_723;
+    runner.context.epoch_height = EPOCH_HEIGHT;
+
+    let mut call = |runner: &mut test_utils::AuroraRunner, selector: [u8; 4]| {
+        let transaction = TransactionLegacy {
+            nonce: signer.use_nonce().into(),
+            gas_price: U256::zero(),
+            gas_limit: u64::MAX.into(),
+            to: Some(block_context::ADDRESS),
+            value: Wei::zero(),
+            data: selector.to_vec(),
+        };
+        let result = runner
+            .submit_transaction(&signer.secret_key, transaction)
+            .unwrap();
+        test_utils::unwrap_success(result)
+    };
+
+    // The precompile is only installed from its activation height
+    assert!(call(&mut runner, selectors::EPOCH_HEIGHT).is_empty());
+
+    runner.context.block_index = aurora_engine::engine::EXTENDED_PRECOMPILES_HEIGHT;
+    assert_eq!(
+        U256::from_big_endian(&call(&mut runner, selectors::EPOCH_HEIGHT)),
+        U256::from(EPOCH_HEIGHT)
+    );
+    // The runner advances the block timestamp before executing the transaction.
+    let block_timestamp = U256::from_big_endian(&call(&mut runner, selectors::BLOCK_TIMESTAMP));
+    assert_eq!(block_timestamp, U256::from(runner.context.block_timestamp));
+}
@@ -3,6 +3,9 @@ use crate::test_utils::{origin, AuroraRunner, Signer};
 
 use crate::test_utils;
 use crate::test_utils::exit_precompile::{Tester, TesterConstructor, DEST_ACCOUNT, DEST_ADDRESS};
+use crate::test_utils::gas_calibration::{Baseline, GasCalibration};
+use aurora_engine_precompiles::native::exit_to_near;
+use aurora_engine_types::types::EthGas;
 
 fn setup_test() -> (AuroraRunner, Signer, Address, Tester) {
     let mut runner = AuroraRunner::new();
@@ -229,6 +232,37 @@ fn withdraw_eth() {
     assert_eq!(&expected_event, &exit_events[0].params);
 }
 
+#[test]
+fn test_exit_to_near_eth_gas_cost() {
+    // ExitToNear is not charged any EVM gas yet (see TODO(#483)). This is the cost matching the
+    // NEAR gas it uses, which the precompile is to be priced at.
+    const EXIT_TO_NEAR_GAS: EthGas = EthGas::new(32_000);
+
+    let mut runner = test_utils::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    #[cfg(feature = "error_refund")]
+    let refund_address = test_utils::address_from_secret_key(&signer.secret_key);
+    // Measured against an account without code receiving the same input, so only the
+    // precompile and the transfer promise it creates are accounted for.
+    let mut calibration = GasCalibration::new(
+        exit_to_near::ADDRESS,
+        Baseline::EmptyAccount(Address::from_array([1u8; 20])),
+    );
+
+    let long_receiver = format!("{}.near", "a".repeat(59));
+    for receiver in ["a.near", long_receiver.as_str()] {
+        // Flag 0x0: ETH transfer to the given NEAR account
+        #[cfg(feature = "error_refund")]
+        let input = [&[0x0][..], refund_address.as_bytes(), receiver.as_bytes()].concat();
+        #[cfg(not(feature = "error_refund"))]
+        let input = [&[0x0][..], receiver.as_bytes()].concat();
+        let input_length = u64::try_from(input.len()).unwrap();
+        calibration.sample(&mut runner, &mut signer, input_length, input);
+    }
+
+    calibration.assert_cost("exit_to_near", 5, EXIT_TO_NEAR_GAS, None);
+}
+
 fn parse_exit_events(result: SubmitResult, schema: &ethabi::Event) -> Vec<ethabi::Log> {
     let signature = schema.signature();
     result
@@ -0,0 +1,93 @@
+use crate::test_utils;
+use crate::test_utils::gas_calibration::{call_transaction, Baseline, GasCalibration};
+use aurora_engine_precompiles::ed25519::{costs, Ed25519Verify};
+use aurora_engine_types::types::Address;
+use aurora_engine_types::U256;
+use near_crypto::{InMemorySigner, KeyType, Signature, Signer};
+
+fn verify_input(signer: &InMemorySigner, message: &[u8]) -> Vec<u8> {
+    let signature = match signer.sign(message) {
+        Signature::ED25519(signature) => signature.to_bytes(),
+        Signature::SECP256K1(_) => panic!("Wrong Signer"),
+    };
+    [
+        signer.public_key().key_data(),
+        signature.as_slice(),
+        message,
+    ]
+    .concat()
+}
+
+#[test]
+fn test_ed25519_verify_precompile() {
+    let mut runner = test_utils::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let near_signer =
+        InMemorySigner::from_seed("alice.near".parse().unwrap(), KeyType::ED25519, "a");
+
+    let mut input = verify_input(&near_signer, b"hello_world");
+
+    // The precompile is only installed from the Prague precompiles height
+    let result = runner
+        .submit_with_signer(
+            &mut signer,
+            call_transaction(Ed25519Verify::ADDRESS, input.clone()),
+        )
+        .unwrap();
+    assert!(test_utils::unwrap_success_slice(&result).is_empty());
+
+    runner.context.block_index = aurora_engine::engine::PRAGUE_PRECOMPILES_HEIGHT;
+    let result = runner
+        .submit_with_signer(
+            &mut signer,
+            call_transaction(Ed25519Verify::ADDRESS, input.clone()),
+        )
+        .unwrap();
+    assert_eq!(
+        U256::from_big_endian(test_utils::unwrap_success_slice(&result)),
+        U256::one()
+    );
+
+    // Tampering with the message invalidates the signature
+    *input.last_mut().unwrap() ^= 1;
+    let result = runner
+        .submit_with_signer(&mut signer, call_transaction(Ed25519Verify::ADDRESS, input))
+        .unwrap();
+    assert_eq!(
+        U256::from_big_endian(test_utils::unwrap_success_slice(&result)),
+        U256::zero()
+    );
+}
+
+#[test]
+fn test_ed25519_verify_eth_gas_cost() {
+    let mut runner = test_utils::deploy_evm();
+    runner.standalone_runner = None;
+    runner.context.block_index = aurora_engine::engine::PRAGUE_PRECOMPILES_HEIGHT;
+    let mut signer = test_utils::Signer::random();
+    let near_signer =
+        InMemorySigner::from_seed("alice.near".parse().unwrap(), KeyType::ED25519, "a");
+    // The baseline sends the same data to an account without code, to isolate just the
+    // precompile logic from the cost of processing the transaction input.
+    let mut calibration = GasCalibration::new(
+        Ed25519Verify::ADDRESS,
+        Baseline::EmptyAccount(Address::from_array([1u8; 20])),
+    );
+
+    for message in [&[7u8; 32][..], &[7u8; 32 * 1024][..]] {
+        let message_words = u64::try_from((message.len() + 31) / 32).unwrap();
+        let input = verify_input(&near_signer, message);
+        let result = calibration.sample(&mut runner, &mut signer, message_words, input);
+        assert_eq!(
+            U256::from_big_endian(test_utils::unwrap_success_slice(&result)),
+            U256::one()
+        );
+    }
+
+    calibration.assert_cost(
+        "ed25519 verify",
+        5,
+        costs::ED25519_VERIFY_BASE,
+        Some(costs::ED25519_VERIFY_WORD),
+    );
+}
@@ -392,7 +392,7 @@ fn test_transfer_erc20_token() {
 // Note: `AuroraRunner` is not suitable for these tests because
 // it does not execute promises; but `near-sdk-sim` does.
 pub mod sim_tests {
-    use crate::prelude::{Wei, WeiU256, U256};
+    use crate::prelude::{Wei, WeiU256, H256, U256};
     use crate::test_utils;
     use crate::test_utils::erc20::{ERC20Constructor, ERC20};
     use crate::test_utils::exit_precompile::TesterConstructor;
@@ -400,6 +400,7 @@ pub mod sim_tests {
     use aurora_engine::parameters::{
         CallArgs, DeployErc20TokenArgs, FunctionCallArgsV2, SubmitResult,
     };
+    use aurora_engine_precompiles::native::{events, exit_to_ethereum, exit_to_near};
     use aurora_engine_types::types::Address;
     use borsh::{BorshDeserialize, BorshSerialize};
     use near_sdk_sim::UserAccount;
@@ -661,6 +662,139 @@ pub mod sim_tests {
         );
     }
 
+    #[test]
+    fn test_exit_to_near_eth_transfer_call_refund() {
+        // Test the case where the receiver of an `ft_transfer_call` made by the exit call
+        // does not use the tokens; ensure ETH is refunded.
+
+        let TestExitToNearEthContext {
+            signer,
+            signer_address,
+            chain_id,
+            tester_address: _,
+            aurora,
+        } = test_exit_to_near_eth_common();
+        // There is no contract at this account, so `ft_on_transfer` fails and all the
+        // tokens are returned to Aurora.
+        let exit_account_id = "any.near".to_owned();
+
+        // call exit to near
+        let input = [
+            &[0x2][..],
+            signer_address.as_bytes(),
+            ethabi::encode(&[
+                ethabi::Token::String(exit_account_id.clone()),
+                ethabi::Token::String("deposit".to_string()),
+                ethabi::Token::String(String::new()),
+            ])
+            .as_slice(),
+        ]
+        .concat();
+        let tx = test_utils::create_eth_transaction(
+            Some(exit_to_near::ADDRESS),
+            Wei::new_u64(ETH_EXIT_AMOUNT),
+            input,
+            Some(chain_id),
+            &signer.secret_key,
+        );
+        aurora.call("submit", &rlp::encode(&tx)).assert_success();
+
+        // check balances
+        assert_eq!(
+            nep_141_balance_of(exit_account_id.as_str(), &aurora.contract, &aurora),
+            0
+        );
+        assert_eq!(
+            nep_141_balance_of(
+                aurora.contract.account_id.as_str(),
+                &aurora.contract,
+                &aurora,
+            ),
+            INITIAL_ETH_BALANCE as u128
+        );
+        assert_eq!(
+            eth_balance_of(signer_address, &aurora),
+            Wei::new_u64(INITIAL_ETH_BALANCE)
+        );
+    }
+
+    #[test]
+    fn test_exit_to_ethereum_eth_with_fee() {
+        // The relayer fee of an exit to Ethereum is only advertised in the exit event;
+        // ensure the whole amount is still withdrawn to the recipient.
+
+        let TestExitToNearEthContext {
+            signer,
+            signer_address,
+            chain_id,
+            tester_address: _,
+            aurora,
+        } = test_exit_to_near_eth_common();
+        let recipient = Address::from_array([0x11; 20]);
+        let fee = 1_000_000_u64;
+
+        // call exit to ethereum
+        let mut fee_bytes = [0u8; 32];
+        U256::from(fee).to_big_endian(&mut fee_bytes);
+        let input = [&[0x2][..], recipient.as_bytes(), &fee_bytes].concat();
+        let tx = test_utils::create_eth_transaction(
+            Some(exit_to_ethereum::ADDRESS),
+            Wei::new_u64(ETH_EXIT_AMOUNT),
+            input,
+            Some(chain_id),
+            &signer.secret_key,
+        );
+        let result = aurora.call("submit", &rlp::encode(&tx));
+        result.assert_success();
+        let submit_result: SubmitResult = result.unwrap_borsh();
+
+        // check the exit event
+        let schema = events::exit_to_eth_with_fee_schema();
+        let exit_events: Vec<ethabi::Log> = submit_result
+            .logs
+            .into_iter()
+            .filter(|log| log.topics.first() == Some(&schema.signature().0))
+            .map(|log| {
+                schema
+                    .parse_log(ethabi::RawLog {
+                        topics: log.topics.into_iter().map(H256).collect(),
+                        data: log.data,
+                    })
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(exit_events.len(), 1);
+        let params: Vec<ethabi::Token> = exit_events[0]
+            .params
+            .iter()
+            .map(|param| param.value.clone())
+            .collect();
+        assert_eq!(
+            params,
+            vec![
+                ethabi::Token::Address(signer_address.raw()),
+                ethabi::Token::Address(events::ETH_ADDRESS.raw()),
+                ethabi::Token::Address(recipient.raw()),
+                ethabi::Token::Uint(U256::from(ETH_EXIT_AMOUNT)),
+                ethabi::Token::Uint(U256::from(fee)),
+            ]
+        );
+
+        // check balances: the fee is not held back from the withdrawal
+        assert_eq!(
+            nep_141_balance_of(
+                aurora.contract.account_id.as_str(),
+                &aurora.contract,
+                &aurora,
+            ),
+            (INITIAL_ETH_BALANCE - ETH_EXIT_AMOUNT) as u128
+        );
+        assert_eq!(
+            eth_balance_of(signer_address, &aurora),
+            Wei::new_u64(INITIAL_ETH_BALANCE - ETH_EXIT_AMOUNT)
+        );
+    }
+
     fn test_exit_to_near_eth_common() -> TestExitToNearEthContext {
         let aurora = deploy_evm();
         let chain_id = test_utils::AuroraRunner::default().chain_id;
@@ -853,7 +987,7 @@ pub mod sim_tests {
         }
     }
 
-    fn erc20_balance(erc20: &ERC20, address: Address, aurora: &AuroraAccount) -> U256 {
+    pub(crate) fn erc20_balance(erc20: &ERC20, address: Address, aurora: &AuroraAccount) -> U256 {
         let balance_tx = erc20.balance_of(address, 0.into());
         let call_args = CallArgs::V2(FunctionCallArgsV2 {
             contract: erc20.0.address,
@@ -0,0 +1,54 @@
+use crate::test_utils;
+use aurora_engine_precompiles::kzg::KzgPointEvaluation;
+use aurora_engine_transactions::legacy::TransactionLegacy;
+use aurora_engine_types::types::{u256_to_arr, Wei};
+use aurora_engine_types::U256;
+
+/// Input proving that the zero polynomial, committed to by the point at infinity,
+/// evaluates to zero at `z = 1`.
+fn zero_polynomial_input() -> Vec<u8> {
+    let mut point_at_infinity = [0u8; 48];
+    point_at_infinity[0] = 0xc0;
+    let mut versioned_hash = aurora_engine_sdk::sha256(&point_at_infinity).0;
+    versioned_hash[0] = 0x01;
+    [
+        &versioned_hash[..],
+        &u256_to_arr(&U256::one()),
+        &[0u8; 32],
+        &point_at_infinity,
+        &point_at_infinity,
+    ]
+    .concat()
+}
+
+#[test]
+fn test_kzg_point_evaluation_precompile() {
+    let mut runner = test_utils::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let mut call_precompile = |runner: &mut test_utils::AuroraRunner| {
+        let result = runner
+            .submit_with_signer(&mut signer, |nonce| TransactionLegacy {
+                nonce,
+                gas_price: U256::zero(),
+                gas_limit: u64::MAX.into(),
+                to: Some(KzgPointEvaluation::ADDRESS),
+                value: Wei::zero(),
+                data: zero_polynomial_input(),
+            })
+            .unwrap();
+        test_utils::unwrap_success(result)
+    };
+
+    // The precompile is only installed from the Cancun precompiles height
+    assert!(call_precompile(&mut runner).is_empty());
+
+    runner.context.block_index = aurora_engine::engine::CANCUN_PRECOMPILES_HEIGHT;
+    let output = call_precompile(&mut runner);
+    assert_eq!(output.len(), 64);
+    // The number of field elements per blob, followed by the modulus of the field
+    assert_eq!(U256::from_big_endian(&output[0..32]), U256::from(4096));
+    assert_eq!(
+        hex::encode(&output[32..64]),
+        "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001"
+    );
+}
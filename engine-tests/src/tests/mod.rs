@@ -1,16 +1,21 @@
 mod access_lists;
 mod account_id_precompiles;
+mod address_resolver_precompile;
+mod block_context_precompile;
 mod contract_call;
 mod ecrecover;
+mod ed25519_verify;
 mod eip1559;
 mod erc20;
 mod erc20_connector;
 pub mod eth_connector;
 mod ghsa_3p69_m8gg_fwmf;
+mod kzg_point_evaluation;
 #[cfg(feature = "meta-call")]
 mod meta_parsing;
 mod multisender;
 mod one_inch;
+mod precompile_registry;
 mod prepaid_gas_precompile;
 mod promise_results_precompile;
 mod random;
@@ -21,4 +26,5 @@ mod standalone;
 mod standard_precompiles;
 mod state_migration;
 pub(crate) mod uniswap;
+mod wrapped_eth_precompile;
 mod xcc;
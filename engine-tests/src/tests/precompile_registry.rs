@@ -0,0 +1,124 @@
+use crate::test_utils;
+use aurora_engine::parameters::SetPrecompileStatusArgs;
+use aurora_engine_precompiles::native::exit_to_near;
+use aurora_engine_precompiles::prepaid_gas;
+use aurora_engine_precompiles::registry::{PrecompileRegistry, PrecompileStatus};
+use aurora_engine_transactions::legacy::TransactionLegacy;
+use aurora_engine_types::types::{Address, Wei};
+use aurora_engine_types::U256;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[test]
+fn test_precompile_registry() {
+    let mut signer = test_utils::Signer::random();
+    let mut runner = test_utils::deploy_evm();
+    runner.context.prepaid_gas = 157_277_246_352_223;
+    let remapped_address = Address::from_array([0x55; 20]);
+
+    let mut call_address = |runner: &mut test_utils::AuroraRunner, address: Address| {
+        let transaction = TransactionLegacy {
+            nonce: signer.use_nonce().into(),
+            gas_price: U256::zero(),
+            gas_limit: u64::MAX.into(),
+            to: Some(address),
+            value: Wei::zero(),
+            data: Vec::new(),
+        };
+        let result = runner
+            .submit_transaction(&signer.secret_key, transaction)
+            .unwrap();
+        test_utils::unwrap_success(result)
+    };
+    let set_status =
+        |runner: &mut test_utils::AuroraRunner, address: Address, status: PrecompileStatus| {
+            let args = SetPrecompileStatusArgs { address, status };
+            let owner = runner.aurora_account_id.clone();
+            runner.call("set_precompile_status", &owner, args.try_to_vec().unwrap())
+        };
+
+    let expected_prepaid_gas = {
+        let mut buf = vec![0u8; 32];
+        U256::from(runner.context.prepaid_gas).to_big_endian(&mut buf);
+        buf
+    };
+    assert_eq!(
+        call_address(&mut runner, prepaid_gas::ADDRESS),
+        expected_prepaid_gas
+    );
+    assert!(call_address(&mut runner, remapped_address).is_empty());
+
+    // Remapped precompiles are only reachable at their new address
+    let (_, maybe_error) = set_status(
+        &mut runner,
+        prepaid_gas::ADDRESS,
+        PrecompileStatus::Remapped(remapped_address),
+    );
+    assert!(maybe_error.is_none());
+    assert!(call_address(&mut runner, prepaid_gas::ADDRESS).is_empty());
+    assert_eq!(
+        call_address(&mut runner, remapped_address),
+        expected_prepaid_gas
+    );
+
+    // The exit precompiles can be disabled, but not remapped
+    let (_, maybe_error) = set_status(
+        &mut runner,
+        exit_to_near::ADDRESS,
+        PrecompileStatus::Remapped(remapped_address),
+    );
+    let error_message = format!("{:?}", maybe_error);
+    assert!(error_message.contains("ERR_PRECOMPILE_NOT_REMAPPABLE"));
+    let (_, maybe_error) = set_status(
+        &mut runner,
+        exit_to_near::ADDRESS,
+        PrecompileStatus::Disabled,
+    );
+    assert!(maybe_error.is_none());
+    // Calling a disabled precompile is the same as calling an empty account.
+    assert!(call_address(&mut runner, exit_to_near::ADDRESS).is_empty());
+
+    let (outcome, maybe_error) =
+        runner
+            .one_shot()
+            .call("get_precompile_registry", "viewer", Vec::new());
+    assert!(maybe_error.is_none());
+    let registry =
+        PrecompileRegistry::try_from_slice(&outcome.unwrap().return_data.as_value().unwrap())
+            .unwrap();
+    assert_eq!(
+        registry.status(&prepaid_gas::ADDRESS),
+        PrecompileStatus::Remapped(remapped_address)
+    );
+    assert_eq!(
+        registry.status(&exit_to_near::ADDRESS),
+        PrecompileStatus::Disabled
+    );
+
+    // Re-enabling restores the default address
+    let (_, maybe_error) = set_status(&mut runner, prepaid_gas::ADDRESS, PrecompileStatus::Enabled);
+    assert!(maybe_error.is_none());
+    assert_eq!(
+        call_address(&mut runner, prepaid_gas::ADDRESS),
+        expected_prepaid_gas
+    );
+}
+
+#[test]
+fn test_corrupted_precompile_registry() {
+    let mut runner = test_utils::deploy_evm();
+    let registry_key = aurora_engine_types::storage::bytes_to_key(
+        aurora_engine_types::storage::KeyPrefix::Config,
+        b"PRECOMPILE_REGISTRY",
+    );
+    runner
+        .ext
+        .underlying
+        .fake_trie
+        .insert(registry_key, vec![0xff; 3]);
+
+    // A registry that cannot be read does not silently fall back to the default precompiles
+    let (_, maybe_error) = runner
+        .one_shot()
+        .call("get_precompile_registry", "viewer", Vec::new());
+    assert!(format!("{:?}", maybe_error.unwrap()).contains("ERR_CORRUPTED_REGISTRY_STORAGE"));
+}
@@ -1,14 +1,13 @@
+use crate::test_utils::gas_calibration::{Baseline, GasCalibration};
 use crate::test_utils::{self, standalone};
 use aurora_engine_precompiles::promise_result::{self, costs};
 use aurora_engine_transactions::legacy::TransactionLegacy;
 use aurora_engine_types::{
-    types::{Address, EthGas, NearGas, PromiseResult, Wei},
+    types::{Address, PromiseResult, Wei},
     U256,
 };
 use borsh::BorshSerialize;
 
-const NEAR_GAS_PER_EVM: u64 = 175_000_000;
-
 #[test]
 fn test_promise_results_precompile() {
     let mut signer = test_utils::Signer::random();
@@ -69,26 +68,17 @@ fn test_promise_result_gas_cost() {
         })
         .unwrap();
 
-    let mut profile_for_promises = |promise_data: Vec<PromiseResult>| -> (u64, u64, u64) {
+    let mut calibration =
+        GasCalibration::new(promise_result::ADDRESS, Baseline::Fixed(baseline.all_gas()));
+    let mut sample = |promise_data: Vec<PromiseResult>| {
         let input_length: usize = promise_data.iter().map(|p| p.size()).sum();
         runner.promise_results = promise_data;
-        let (submit_result, profile) = runner
-            .submit_with_signer_profiled(&mut signer, |nonce| TransactionLegacy {
-                nonce,
-                gas_price: U256::zero(),
-                gas_limit: u64::MAX.into(),
-                to: Some(promise_result::ADDRESS),
-                value: Wei::zero(),
-                data: Vec::new(),
-            })
-            .unwrap();
-        assert!(submit_result.status.is_ok());
-        // Subtract off baseline transaction to isolate just precompile things
-        (
+        calibration.sample(
+            &mut runner,
+            &mut signer,
             u64::try_from(input_length).unwrap(),
-            profile.all_gas() - baseline.all_gas(),
-            submit_result.gas_used,
-        )
+            Vec::new(),
+        );
     };
 
     let promise_results = vec![
@@ -97,45 +87,14 @@ fn test_promise_result_gas_cost() {
         PromiseResult::Successful(vec![1u8; 100]),
     ];
 
-    let (x1, y1, evm1) = profile_for_promises(Vec::new());
-    let (x2, y2, evm2) = profile_for_promises(promise_results);
-
-    let cost_per_byte = (y2 - y1) / (x2 - x1);
-    let base_cost = NearGas::new(y1 - cost_per_byte * x1);
-
-    let base_cost = EthGas::new(base_cost.as_u64() / NEAR_GAS_PER_EVM);
-    let cost_per_byte = cost_per_byte / NEAR_GAS_PER_EVM;
-
-    assert!(
-        test_utils::within_x_percent(
-            5,
-            base_cost.as_u64(),
-            costs::PROMISE_RESULT_BASE_COST.as_u64()
-        ),
-        "Incorrect promise_result base cost. Expected: {} Actual: {}",
-        base_cost,
-        costs::PROMISE_RESULT_BASE_COST
-    );
-
-    assert!(
-        test_utils::within_x_percent(5, cost_per_byte, costs::PROMISE_RESULT_BYTE_COST.as_u64()),
-        "Incorrect promise_result per byte cost. Expected: {} Actual: {}",
-        cost_per_byte,
-        costs::PROMISE_RESULT_BYTE_COST
-    );
+    sample(Vec::new());
+    sample(promise_results);
 
-    let total_gas1 = y1 + baseline.all_gas();
-    let total_gas2 = y2 + baseline.all_gas();
-    assert!(
-        test_utils::within_x_percent(6, evm1, total_gas1 / NEAR_GAS_PER_EVM),
-        "Incorrect EVM gas used. Expected: {} Actual: {}",
-        evm1,
-        total_gas1 / NEAR_GAS_PER_EVM
-    );
-    assert!(
-        test_utils::within_x_percent(6, evm2, total_gas2 / NEAR_GAS_PER_EVM),
-        "Incorrect EVM gas used. Expected: {} Actual: {}",
-        evm2,
-        total_gas2 / NEAR_GAS_PER_EVM
+    calibration.assert_cost(
+        "promise_result",
+        5,
+        costs::PROMISE_RESULT_BASE_COST,
+        Some(costs::PROMISE_RESULT_BYTE_COST),
     );
+    calibration.assert_evm_gas(6);
 }
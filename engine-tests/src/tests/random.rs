@@ -1,6 +1,11 @@
-use crate::test_utils;
 use crate::test_utils::random::{Random, RandomConstructor};
-use aurora_engine_types::H256;
+use crate::test_utils::{self, standalone};
+use aurora_engine::parameters::{CallArgs, FunctionCallArgsV2, SubmitResult};
+use aurora_engine_precompiles::random::TransactionRandomSeed;
+use aurora_engine_transactions::legacy::TransactionLegacy;
+use aurora_engine_types::types::Wei;
+use aurora_engine_types::{H256, U256};
+use borsh::{BorshDeserialize, BorshSerialize};
 
 #[test]
 fn test_random_number_precompile() {
@@ -17,3 +22,74 @@ fn test_random_number_precompile() {
     let counter_value = random.random_seed(&mut runner, &mut signer);
     assert_eq!(counter_value, random_seed);
 }
+
+#[test]
+fn test_transaction_random_seed_precompile() {
+    let random_seed = H256::from_slice(vec![7; 32].as_slice());
+    let mut signer = test_utils::Signer::random();
+    let mut runner = test_utils::deploy_evm().with_random_seed(random_seed);
+    runner.context.block_index = aurora_engine::engine::EXTENDED_PRECOMPILES_HEIGHT;
+    let mut standalone = standalone::StandaloneRunner::default();
+    standalone.init_evm();
+    runner.standalone_runner = Some(standalone);
+
+    let mut random_bytes = || {
+        let transaction = TransactionLegacy {
+            nonce: signer.use_nonce().into(),
+            gas_price: U256::zero(),
+            gas_limit: u64::MAX.into(),
+            to: Some(TransactionRandomSeed::ADDRESS),
+            value: Wei::zero(),
+            data: Vec::new(),
+        };
+        let result = runner
+            .submit_transaction(&signer.secret_key, transaction)
+            .unwrap();
+        H256::from_slice(test_utils::unwrap_success_slice(&result))
+    };
+
+    // Transactions with the same block seed see different values
+    let first = random_bytes();
+    let second = random_bytes();
+    assert_ne!(first, random_seed);
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_transaction_random_seed_precompile_outside_submit() {
+    let random_seed = H256::from_slice(vec![7; 32].as_slice());
+    let mut runner = test_utils::deploy_evm().with_random_seed(random_seed);
+    runner.context.block_index = aurora_engine::engine::EXTENDED_PRECOMPILES_HEIGHT;
+    let mut standalone = standalone::StandaloneRunner::default();
+    standalone.init_evm();
+    runner.standalone_runner = Some(standalone);
+
+    let random_bytes = |runner: &mut test_utils::AuroraRunner, caller: &str, input: Vec<u8>| {
+        let args = CallArgs::V2(FunctionCallArgsV2 {
+            contract: TransactionRandomSeed::ADDRESS,
+            value: Default::default(),
+            input,
+        });
+        let (outcome, error) = runner.call("call", caller, args.try_to_vec().unwrap());
+        assert!(error.is_none(), "{:?}", error);
+        let result =
+            SubmitResult::try_from_slice(&outcome.unwrap().return_data.as_value().unwrap())
+                .unwrap();
+        H256::from_slice(test_utils::unwrap_success_slice(&result))
+    };
+
+    // Receipts without an Ethereum transaction are told apart by their input and predecessor
+    let first = random_bytes(&mut runner, "alice.near", vec![1]);
+    let second = random_bytes(&mut runner, "alice.near", vec![2]);
+    let third = random_bytes(&mut runner, "bob.near", vec![2]);
+    assert_ne!(first, random_seed);
+    assert_ne!(first, second);
+    assert_ne!(second, third);
+
+    // Identical receipts of the same block are told apart as well
+    let fourth = random_bytes(&mut runner, "bob.near", vec![2]);
+    runner.context.block_index -= 1;
+    let fifth = random_bytes(&mut runner, "bob.near", vec![2]);
+    assert_ne!(third, fourth);
+    assert_ne!(fourth, fifth);
+}
@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+interface IERC20 {
+    function approve(address spender, uint256 amount) external returns (bool);
+}
+
+// Makes cross contract calls through its own router, and records the results
+// delivered back by the engine.
+contract XccCaller {
+    address public xcc;
+    bytes32 public lastResultHash;
+    uint256 public callbackCount;
+
+    constructor(address _xcc) {
+        xcc = _xcc;
+    }
+
+    function approveWNear(IERC20 wnear, uint256 amount) public {
+        wnear.approve(xcc, amount);
+    }
+
+    function crossContractCall(bytes memory args) public {
+        (bool success, ) = xcc.call(args);
+        require(success, "ERR_XCC_CALL");
+    }
+
+    function onResult(bytes memory result) public {
+        require(msg.sender == xcc, "ERR_NOT_XCC");
+        lastResultHash = keccak256(result);
+        callbackCount += 1;
+    }
+}
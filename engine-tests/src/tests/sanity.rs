@@ -1,11 +1,11 @@
-use crate::prelude::{Address, U256};
+use crate::prelude::{Address, H256, U256};
 use crate::prelude::{Wei, ERC20_MINT_SELECTOR};
 use crate::test_utils;
 use crate::tests::state_migration;
 use aurora_engine::fungible_token::FungibleTokenMetadata;
 use aurora_engine::parameters::{SubmitResult, TransactionStatus};
 use aurora_engine_sdk as sdk;
-use borsh::BorshSerialize;
+use borsh::{BorshDeserialize, BorshSerialize};
 use libsecp256k1::SecretKey;
 use rand::RngCore;
 use std::path::{Path, PathBuf};
@@ -141,15 +141,401 @@ fn test_state_format() {
     };
     let state: aurora_engine::engine::EngineState = args.into();
     let expected_hex: String = [
+        "00",
         "000000000000000000000000000000000000000000000000000000000000029a",
         "04000000626f7373",
         "1300000070726f7665725f6d6370726f76795f66616365",
         "0300000000000000",
+        "00",
     ]
     .concat();
     assert_eq!(hex::encode(state.try_to_vec().unwrap()), expected_hex);
 }
 
+#[test]
+fn test_state_format_with_block_gas_limit() {
+    let args = aurora_engine::parameters::NewCallArgs {
+        chain_id: aurora_engine_types::types::u256_to_arr(&666.into()),
+        owner_id: "boss".parse().unwrap(),
+        bridge_prover_id: "prover_mcprovy_face".parse().unwrap(),
+        upgrade_delay_blocks: 3,
+    };
+    let mut state: aurora_engine::engine::EngineState = args.into();
+    let block_gas_limit = aurora_engine::parameters::BlockGasLimit {
+        limit: 15_000_000,
+        enforce: true,
+    };
+    state.block_gas_limit = Some(block_gas_limit);
+    let bytes = state.try_to_vec().unwrap();
+    assert_eq!(
+        hex::encode(&bytes[(bytes.len() - 10)..]),
+        "01c0e1e4000000000001"
+    );
+    let state = aurora_engine::engine::EngineState::try_from_slice(&bytes).unwrap();
+    assert_eq!(state.block_gas_limit, Some(block_gas_limit));
+}
+
+#[test]
+fn test_legacy_state_migration() {
+    use sdk::io::{StorageIntermediate, IO};
+
+    let mut runner = test_utils::standalone::StandaloneRunner::default();
+    runner.init_evm();
+
+    // The state as it was written before its format was versioned.
+    let legacy_key = aurora_engine_types::storage::bytes_to_key(
+        aurora_engine_types::storage::KeyPrefix::Config,
+        b"STATE",
+    );
+    let state_key = aurora_engine_types::storage::bytes_to_key(
+        aurora_engine_types::storage::KeyPrefix::Config,
+        b"ENGINE_STATE",
+    );
+    let legacy_bytes = hex::decode(
+        [
+            "000000000000000000000000000000000000000000000000000000000000029a",
+            "04000000626f7373",
+            "1300000070726f7665725f6d6370726f76795f66616365",
+            "0300000000000000",
+        ]
+        .concat(),
+    )
+    .unwrap();
+
+    runner
+        .storage
+        .with_engine_access(runner.env.block_height + 1, 0, &[], |mut io| {
+            io.remove_storage(&state_key);
+            io.write_storage(&legacy_key, &legacy_bytes);
+
+            // The legacy state is readable before the migration runs
+            let state = aurora_engine::engine::get_state(&io).unwrap();
+            assert_eq!(state.owner_id, "boss".parse().unwrap());
+            assert_eq!(state.upgrade_delay_blocks, 3);
+            assert_eq!(state.block_gas_limit, None);
+
+            aurora_engine::engine::migrate_legacy_state(&mut io).unwrap();
+            assert!(io.read_storage(&legacy_key).is_none());
+            let state = aurora_engine::engine::get_state(&io).unwrap();
+            assert_eq!(
+                state.bridge_prover_id,
+                "prover_mcprovy_face".parse().unwrap()
+            );
+            assert_eq!(state.upgrade_delay_blocks, 3);
+
+            // Running the migration again leaves the state untouched
+            let bytes = io.read_storage(&state_key).unwrap().to_vec();
+            aurora_engine::engine::migrate_legacy_state(&mut io).unwrap();
+            assert_eq!(io.read_storage(&state_key).unwrap().to_vec(), bytes);
+
+            // A stale legacy state never overwrites the versioned one
+            io.write_storage(&legacy_key, &legacy_bytes);
+            let mut state = aurora_engine::engine::get_state(&io).unwrap();
+            state.upgrade_delay_blocks = 5;
+            io.write_storage(&state_key, &state.try_to_vec().unwrap());
+            aurora_engine::engine::migrate_legacy_state(&mut io).unwrap();
+            assert!(io.read_storage(&legacy_key).is_none());
+            let state = aurora_engine::engine::get_state(&io).unwrap();
+            assert_eq!(state.upgrade_delay_blocks, 5);
+
+            // Writing the state drops the legacy one
+            io.write_storage(&legacy_key, &legacy_bytes);
+            aurora_engine::engine::set_state(&mut io, state);
+            assert!(io.read_storage(&legacy_key).is_none());
+        })
+        .result;
+
+    runner.close();
+}
+
+#[test]
+fn test_new_with_genesis() {
+    let mut runner = test_utils::AuroraRunner::default();
+    let predeploy = aurora_engine::parameters::GenesisAccount {
+        address: Address::from_array([0x4e; 20]),
+        balance: aurora_engine_types::types::u256_to_arr(&INITIAL_BALANCE.raw()),
+        nonce: aurora_engine_types::types::u256_to_arr(&U256::one()),
+        code: hex::decode("600160005260206000f3").unwrap(),
+        storage: vec![([0u8; 32], [0x11; 32]), ([0x22; 32], [0x33; 32])],
+    };
+    let args = aurora_engine::parameters::NewWithGenesisCallArgs {
+        new_args: aurora_engine::parameters::NewCallArgs {
+            chain_id: aurora_engine_types::types::u256_to_arr(&U256::from(runner.chain_id)),
+            owner_id: runner.aurora_account_id.parse().unwrap(),
+            bridge_prover_id: "bridge_prover.near".parse().unwrap(),
+            upgrade_delay_blocks: 1,
+        },
+        genesis: aurora_engine::parameters::Genesis {
+            accounts: vec![predeploy.clone()],
+        },
+    };
+    let owner = runner.aurora_account_id.clone();
+    let (_, maybe_error) = runner.call("new", &owner, args.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+
+    let address = predeploy.address;
+    assert_eq!(runner.get_balance(address), INITIAL_BALANCE);
+    assert_eq!(runner.get_nonce(address), U256::one());
+    assert_eq!(runner.get_code(address), predeploy.code.clone());
+    for (key, value) in predeploy.storage.iter() {
+        assert_eq!(runner.get_storage(address, H256(*key)), H256(*value));
+    }
+
+    // The genesis balances are part of the ETH supply on Aurora
+    let init_args = aurora_engine::parameters::InitCallArgs {
+        prover_account: "prover.near".parse().unwrap(),
+        eth_custodian_address: "d045f7e19B2488924B97F9c145b5E51D0D895A65".to_string(),
+        metadata: FungibleTokenMetadata::default(),
+    };
+    let (_, maybe_error) =
+        runner.call("new_eth_connector", &owner, init_args.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+    let (outcome, _) = runner.call("ft_total_eth_supply_on_aurora", &owner, Vec::new());
+    let total_supply: u128 = String::from_utf8(outcome.unwrap().return_data.as_value().unwrap())
+        .unwrap()
+        .replace('"', "")
+        .parse()
+        .unwrap();
+    assert_eq!(Wei::new(U256::from(total_supply)), INITIAL_BALANCE);
+
+    // Genesis accounts cannot be installed on a live engine
+    let mut args = args;
+    args.genesis.accounts[0].balance =
+        aurora_engine_types::types::u256_to_arr(&U256::from(1_000_000));
+    let (_, maybe_error) = runner.call("new", &owner, args.try_to_vec().unwrap());
+    assert!(format!("{:?}", maybe_error.unwrap()).contains("ERR_CONTRACT_INITIALIZED"));
+    assert_eq!(runner.get_balance(address), INITIAL_BALANCE);
+}
+
+#[test]
+fn test_exit_after_genesis() {
+    let mut runner = test_utils::AuroraRunner::default();
+    let mut signer = test_utils::Signer::random();
+    let owner = runner.aurora_account_id.clone();
+    let args = aurora_engine::parameters::NewWithGenesisCallArgs {
+        new_args: aurora_engine::parameters::NewCallArgs {
+            chain_id: aurora_engine_types::types::u256_to_arr(&U256::from(runner.chain_id)),
+            owner_id: owner.parse().unwrap(),
+            bridge_prover_id: "bridge_prover.near".parse().unwrap(),
+            upgrade_delay_blocks: 1,
+        },
+        genesis: aurora_engine::parameters::Genesis {
+            accounts: vec![aurora_engine::parameters::GenesisAccount {
+                address: test_utils::address_from_secret_key(&signer.secret_key),
+                balance: aurora_engine_types::types::u256_to_arr(&INITIAL_BALANCE.raw()),
+                nonce: [0u8; 32],
+                code: Vec::new(),
+                storage: Vec::new(),
+            }],
+        },
+    };
+    let (_, maybe_error) = runner.call("new", &owner, args.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+    let init_args = aurora_engine::parameters::InitCallArgs {
+        prover_account: "prover.near".parse().unwrap(),
+        eth_custodian_address: "d045f7e19B2488924B97F9c145b5E51D0D895A65".to_string(),
+        metadata: FungibleTokenMetadata::default(),
+    };
+    let (_, maybe_error) =
+        runner.call("new_eth_connector", &owner, init_args.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+
+    let ft_balance_of = |runner: &test_utils::AuroraRunner, account_id: &str| -> u128 {
+        let input = serde_json::json!({ "account_id": account_id }).to_string();
+        let (outcome, maybe_error) =
+            runner
+                .one_shot()
+                .call("ft_balance_of", "viewer", input.into_bytes());
+        assert!(maybe_error.is_none());
+        String::from_utf8(outcome.unwrap().return_data.as_value().unwrap())
+            .unwrap()
+            .replace('"', "")
+            .parse()
+            .unwrap()
+    };
+    // The genesis balances are backed by the ETH the engine holds on NEAR
+    assert_eq!(
+        ft_balance_of(&runner, &owner),
+        INITIAL_BALANCE.raw().as_u128()
+    );
+
+    // Exit part of the genesis balance to a NEAR account
+    let recipient = "recipient.near";
+    let exit_amount = Wei::new_u64(400_000);
+    let sender = test_utils::address_from_secret_key(&signer.secret_key);
+    #[cfg(feature = "error_refund")]
+    let input = [&[0x0][..], sender.as_bytes(), recipient.as_bytes()].concat();
+    #[cfg(not(feature = "error_refund"))]
+    let input = [&[0x0][..], recipient.as_bytes()].concat();
+    let result = runner
+        .submit_with_signer(&mut signer, |nonce| {
+            aurora_engine_transactions::legacy::TransactionLegacy {
+                nonce,
+                gas_price: U256::zero(),
+                gas_limit: u64::MAX.into(),
+                to: Some(aurora_engine_precompiles::native::exit_to_near::ADDRESS),
+                value: exit_amount,
+                data: input,
+            }
+        })
+        .unwrap();
+    test_utils::unwrap_success(result);
+    assert_eq!(runner.get_balance(sender), INITIAL_BALANCE - exit_amount);
+
+    // The exit promise transfers the tokens from the engine account
+    let transfer_args = format!(
+        r#"{{"receiver_id": "{}", "amount": "{}", "memo": null}}"#,
+        recipient,
+        exit_amount.raw()
+    );
+    runner.context.attached_deposit = 1;
+    let (_, maybe_error) = runner.call("ft_transfer", &owner, transfer_args.into_bytes());
+    runner.context.attached_deposit = 0;
+    assert!(maybe_error.is_none());
+    assert_eq!(
+        ft_balance_of(&runner, recipient),
+        exit_amount.raw().as_u128()
+    );
+    assert_eq!(
+        ft_balance_of(&runner, &owner),
+        (INITIAL_BALANCE - exit_amount).raw().as_u128()
+    );
+}
+
+#[test]
+fn test_block_gas_limit() {
+    let (mut runner, mut signer, dest_address) = initialize_transfer();
+
+    // GASLIMIT
+    let contract_address = deploy_opcode_reader(&mut runner, &mut signer, 0x45);
+    let mut get_gas_limit = |runner: &mut test_utils::AuroraRunner| {
+        let result = runner
+            .submit_with_signer(&mut signer, |nonce| {
+                test_utils::transfer(contract_address, Wei::zero(), nonce)
+            })
+            .unwrap();
+        U256::from_big_endian(&test_utils::unwrap_success(result))
+    };
+    let set_block_gas_limit = |runner: &mut test_utils::AuroraRunner, limit: u64, enforce: bool| {
+        let args = aurora_engine::parameters::SetBlockGasLimitArgs {
+            block_gas_limit: Some(aurora_engine::parameters::BlockGasLimit { limit, enforce }),
+        };
+        let owner = runner.aurora_account_id.clone();
+        let (_, maybe_error) =
+            runner.call("set_block_gas_limit", &owner, args.try_to_vec().unwrap());
+        assert!(maybe_error.is_none());
+    };
+
+    assert_eq!(get_gas_limit(&mut runner), U256::max_value());
+    set_block_gas_limit(&mut runner, 30_000_000, false);
+    assert_eq!(get_gas_limit(&mut runner), U256::from(30_000_000));
+
+    // Only the owner can change the block gas limit
+    let args = aurora_engine::parameters::SetBlockGasLimitArgs {
+        block_gas_limit: None,
+    };
+    let (_, maybe_error) = runner.call(
+        "set_block_gas_limit",
+        "mallory.near",
+        args.try_to_vec().unwrap(),
+    );
+    assert!(maybe_error.is_some());
+
+    set_block_gas_limit(&mut runner, 50_000, true);
+    let transfer = |nonce, gas_limit: u64| {
+        let mut tx = test_utils::transfer(dest_address, TRANSFER_AMOUNT, nonce);
+        tx.gas_limit = gas_limit.into();
+        tx
+    };
+
+    // A transaction cannot ask for more gas than the block has
+    let err = runner
+        .submit_transaction(&signer.secret_key, transfer(signer.nonce.into(), 50_001))
+        .unwrap_err();
+    assert!(format!("{:?}", err).contains("ERR_BLOCK_GAS_LIMIT_EXCEEDED"));
+
+    // Gas used in the same NEAR block is accumulated
+    runner
+        .submit_with_signer(&mut signer, |nonce| transfer(nonce, 30_000))
+        .unwrap();
+    runner.context.block_index -= 1;
+    let err = runner
+        .submit_transaction(&signer.secret_key, transfer(signer.nonce.into(), 30_000))
+        .unwrap_err();
+    assert!(format!("{:?}", err).contains("ERR_BLOCK_GAS_LIMIT_EXCEEDED"));
+
+    // The budget is reset in the next block
+    runner
+        .submit_with_signer(&mut signer, |nonce| transfer(nonce, 30_000))
+        .unwrap();
+}
+
+#[test]
+fn test_coinbase() {
+    let (mut runner, mut signer, _) = initialize_transfer();
+
+    // COINBASE
+    let contract_address = deploy_opcode_reader(&mut runner, &mut signer, 0x41);
+    let mut get_coinbase = |runner: &mut test_utils::AuroraRunner| {
+        let result = runner
+            .submit_with_signer(&mut signer, |nonce| {
+                test_utils::transfer(contract_address, Wei::zero(), nonce)
+            })
+            .unwrap();
+        Address::try_from_slice(&test_utils::unwrap_success(result)[12..32]).unwrap()
+    };
+    let set_coinbase = |runner: &mut test_utils::AuroraRunner,
+                        coinbase: aurora_engine::parameters::Coinbase,
+                        activation_height: u64| {
+        let args = aurora_engine::parameters::SetCoinbaseArgs {
+            coinbase,
+            activation_height,
+        };
+        let owner = runner.aurora_account_id.clone();
+        let (_, maybe_error) = runner.call("set_coinbase", &owner, args.try_to_vec().unwrap());
+        assert!(maybe_error.is_none());
+    };
+
+    assert_eq!(
+        get_coinbase(&mut runner).raw(),
+        aurora_engine::engine::DEFAULT_COINBASE
+    );
+
+    // The relayer coinbase only takes effect at the activation height
+    let activation_height = runner.context.block_index + 3;
+    set_coinbase(
+        &mut runner,
+        aurora_engine::parameters::Coinbase::Relayer,
+        activation_height,
+    );
+    assert_eq!(
+        get_coinbase(&mut runner).raw(),
+        aurora_engine::engine::DEFAULT_COINBASE
+    );
+    runner.context.block_index = activation_height;
+    // Transactions are submitted by `some-account.near`, which did not register a relayer address
+    assert_eq!(
+        get_coinbase(&mut runner),
+        aurora_engine_sdk::types::near_account_to_evm_address(b"some-account.near")
+    );
+
+    let fixed_address = Address::from_array([0x55; 20]);
+    set_coinbase(
+        &mut runner,
+        aurora_engine::parameters::Coinbase::Fixed(fixed_address),
+        0,
+    );
+    assert_eq!(get_coinbase(&mut runner), fixed_address);
+
+    // Only the owner can change the coinbase
+    let args = aurora_engine::parameters::SetCoinbaseArgs {
+        coinbase: aurora_engine::parameters::Coinbase::Default,
+        activation_height: 0,
+    };
+    let (_, maybe_error) = runner.call("set_coinbase", "mallory.near", args.try_to_vec().unwrap());
+    assert!(maybe_error.is_some());
+}
+
 #[test]
 fn test_deploy_contract() {
     let (mut runner, mut signer, _) = initialize_transfer();
@@ -792,6 +1178,34 @@ fn test_eth_transfer_charging_gas_not_enough_balance() {
     test_utils::validate_address_balance_and_nonce(&runner, relayer, Wei::zero(), 0.into());
 }
 
+/// Deploys a contract which returns the 32-byte word pushed on the stack by `opcode`
+/// (which must take no arguments).
+fn deploy_opcode_reader(
+    runner: &mut test_utils::AuroraRunner,
+    signer: &mut test_utils::Signer,
+    opcode: u8,
+) -> Address {
+    // OPCODE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let runtime_code = [
+        &[opcode],
+        hex::decode("60005260206000f3").unwrap().as_slice(),
+    ]
+    .concat();
+    // PUSH9 runtime_code PUSH1 0 MSTORE PUSH1 9 PUSH1 23 RETURN
+    let init_code = [
+        vec![0x68],
+        runtime_code,
+        hex::decode("6000526009601760f3").unwrap(),
+    ]
+    .concat();
+    let result = runner
+        .submit_with_signer(signer, |nonce| {
+            test_utils::create_deploy_transaction(init_code, nonce)
+        })
+        .unwrap();
+    Address::try_from_slice(test_utils::unwrap_success_slice(&result)).unwrap()
+}
+
 pub(crate) fn initialize_transfer() -> (test_utils::AuroraRunner, test_utils::Signer, Address) {
     // set up Aurora runner and accounts
     let mut runner = test_utils::deploy_evm();
@@ -0,0 +1,117 @@
+use crate::test_utils::standalone;
+use aurora_engine::{engine, parameters};
+use aurora_engine_types::types::{Address, Wei};
+use aurora_engine_types::{H256, U256};
+use engine_standalone_storage::{genesis, sync};
+
+const GENESIS_JSON: &str = r#"{
+    "config": { "chainId": 1313161556 },
+    "alloc": {
+        "0x4e59b44847b379578588920ca78fbf26c0b4956c": {
+            "code": "0x600160005260206000f3",
+            "nonce": "0x1"
+        },
+        "1111111111111111111111111111111111111111": {
+            "balance": "1000000000000000000",
+            "storage": {
+                "0x0": "0x2a",
+                "0x0000000000000000000000000000000000000000000000000000000000000001": "0xff"
+            }
+        }
+    }
+}"#;
+
+#[test]
+fn test_initialize_genesis() {
+    let json: genesis::JsonGenesis = serde_json::from_str(GENESIS_JSON).unwrap();
+    let genesis = parameters::Genesis::try_from(json).unwrap();
+
+    let mut runner = standalone::StandaloneRunner::default();
+    runner
+        .storage
+        .set_engine_account_id(&"aurora".parse().unwrap())
+        .unwrap();
+    let new_args = parameters::NewCallArgs {
+        chain_id: aurora_engine_types::types::u256_to_arr(&U256::from(runner.chain_id)),
+        owner_id: "aurora".parse().unwrap(),
+        bridge_prover_id: "bridge_prover.near".parse().unwrap(),
+        upgrade_delay_blocks: 1,
+    };
+    genesis::initialize_genesis(&mut runner.storage, new_args.clone(), genesis.clone()).unwrap();
+
+    let deployer = Address::decode("4e59b44847b379578588920ca78fbf26c0b4956c").unwrap();
+    assert_eq!(runner.get_nonce(&deployer), U256::one());
+    assert_eq!(
+        runner.get_code(&deployer),
+        hex::decode("600160005260206000f3").unwrap()
+    );
+    assert_eq!(runner.get_balance(&deployer), Wei::zero());
+
+    let funded = Address::from_array([0x11; 20]);
+    assert_eq!(
+        runner.get_balance(&funded),
+        Wei::new_u64(1_000_000_000_000_000_000)
+    );
+    let get_storage = |runner: &standalone::StandaloneRunner, key: u8| {
+        runner
+            .storage
+            .with_engine_access(runner.env.block_height + 1, 0, &[], |io| {
+                engine::get_storage(&io, &funded, &H256::from_low_u64_be(key.into()), 0)
+            })
+            .result
+    };
+    assert_eq!(get_storage(&runner, 0), H256::from_low_u64_be(0x2a));
+    assert_eq!(get_storage(&runner, 1), H256::from_low_u64_be(0xff));
+
+    // Genesis accounts cannot be installed on an existing state
+    let transaction_message = sync::types::TransactionMessage {
+        block_hash: H256::zero(),
+        near_receipt_id: H256([0x11; 32]),
+        position: 1,
+        succeeded: true,
+        signer: "aurora".parse().unwrap(),
+        caller: "aurora".parse().unwrap(),
+        attached_near: 0,
+        transaction: sync::types::TransactionKind::NewEngineWithGenesis(
+            parameters::NewWithGenesisCallArgs { new_args, genesis },
+        ),
+        promise_data: Vec::new(),
+    };
+    let outcome = sync::consume_message(
+        &mut runner.storage,
+        sync::types::Message::Transaction(Box::new(transaction_message)),
+    )
+    .unwrap();
+    match outcome {
+        sync::ConsumeMessageOutcome::TransactionIncluded(outcome) => assert!(matches!(
+            outcome.maybe_result,
+            Err(sync::error::Error::ContractInitialized)
+        )),
+        other => panic!("Unexpected outcome {:?}", other),
+    }
+    assert_eq!(
+        runner.get_balance(&funded),
+        Wei::new_u64(1_000_000_000_000_000_000)
+    );
+
+    runner.close();
+}
+
+#[test]
+fn test_invalid_genesis_json() {
+    let parse = |json: &str| {
+        let json: genesis::JsonGenesis = serde_json::from_str(json).unwrap();
+        parameters::Genesis::try_from(json)
+    };
+
+    assert!(matches!(
+        parse(r#"{ "alloc": { "0x1234": {} } }"#),
+        Err(genesis::error::Error::InvalidAddress(_))
+    ));
+    assert!(matches!(
+        parse(
+            r#"{ "alloc": { "0x1111111111111111111111111111111111111111": { "balance": "0xzz" } } }"#
+        ),
+        Err(genesis::error::Error::InvalidQuantity(_))
+    ));
+}
@@ -1,4 +1,6 @@
+mod genesis;
 mod json_snapshot;
+mod precompile_extensions;
 mod sanity;
 mod storage;
 mod sync;
@@ -0,0 +1,94 @@
+use aurora_engine_precompiles::extensions::PrecompileExtensions;
+use aurora_engine_precompiles::{HandleBasedPrecompile, Precompile, PrecompileOutput};
+use aurora_engine_transactions::legacy::TransactionLegacy;
+use aurora_engine_types::types::{Address, EthGas, Wei};
+use aurora_engine_types::U256;
+use evm::executor::stack::{PrecompileFailure, PrecompileHandle};
+use evm::{Context, ExitError};
+
+use crate::test_utils::{self, standalone::StandaloneRunner};
+
+const REVERSE_ADDRESS: Address = aurora_engine_precompiles::make_address(0xffff, 1);
+const CALLER_ADDRESS: Address = aurora_engine_precompiles::make_address(0xffff, 2);
+
+/// Returns its input in reverse order.
+struct Reverse;
+
+impl Precompile for Reverse {
+    fn required_gas(_input: &[u8]) -> Result<EthGas, ExitError> {
+        Ok(EthGas::new(15))
+    }
+
+    fn run(
+        &self,
+        input: &[u8],
+        _target_gas: Option<EthGas>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> Result<PrecompileOutput, ExitError> {
+        let output = input.iter().rev().copied().collect();
+        Ok(PrecompileOutput::without_logs(
+            Self::required_gas(input)?,
+            output,
+        ))
+    }
+}
+
+/// Returns the address of its caller, read from the precompile handle.
+struct Caller;
+
+impl HandleBasedPrecompile for Caller {
+    fn run_with_handle(
+        &self,
+        handle: &mut dyn PrecompileHandle,
+    ) -> Result<PrecompileOutput, PrecompileFailure> {
+        let caller = handle.context().caller;
+        Ok(PrecompileOutput::without_logs(
+            EthGas::new(15),
+            caller.as_bytes().to_vec(),
+        ))
+    }
+}
+
+#[test]
+fn test_precompile_extensions() {
+    let mut runner = StandaloneRunner::default();
+    runner.storage.set_precompile_extensions(
+        PrecompileExtensions::new()
+            .with_precompile(REVERSE_ADDRESS, Box::new(Reverse))
+            .with_handle_based_precompile(CALLER_ADDRESS, Box::new(Caller)),
+    );
+    runner.init_evm();
+
+    let mut signer = test_utils::Signer::random();
+    let signer_address = test_utils::address_from_secret_key(&signer.secret_key);
+    let mut call = |runner: &mut StandaloneRunner, to: Address, data: Vec<u8>| {
+        let tx = TransactionLegacy {
+            nonce: signer.use_nonce().into(),
+            gas_price: U256::zero(),
+            gas_limit: u64::MAX.into(),
+            to: Some(to),
+            value: Wei::zero(),
+            data,
+        };
+        runner.submit_transaction(&signer.secret_key, tx).unwrap()
+    };
+
+    let result = call(&mut runner, REVERSE_ADDRESS, vec![1, 2, 3]);
+    assert_eq!(test_utils::unwrap_success_slice(&result), &[3, 2, 1]);
+
+    let result = call(&mut runner, CALLER_ADDRESS, Vec::new());
+    assert_eq!(
+        test_utils::unwrap_success_slice(&result),
+        signer_address.as_bytes()
+    );
+
+    // Extensions are not persisted, so a runner without them sees plain accounts.
+    runner
+        .storage
+        .set_precompile_extensions(PrecompileExtensions::default());
+    let result = call(&mut runner, REVERSE_ADDRESS, vec![1, 2, 3]);
+    assert!(test_utils::unwrap_success_slice(&result).is_empty());
+
+    runner.close();
+}
@@ -19,6 +19,7 @@ fn test_deploy_code() {
         owner_id: owner_id.clone(),
         bridge_prover_id: "mr_the_prover".parse().unwrap(),
         upgrade_delay_blocks: 0,
+        block_gas_limit: None,
     };
     let origin = Address::new(H160([0u8; 20]));
     let storage = RwLock::new(storage::Storage::default());
@@ -29,6 +30,7 @@ fn test_deploy_code() {
         predecessor_account_id: owner_id.clone(),
         block_height: 0,
         block_timestamp: aurora_engine_sdk::env::Timestamp::new(0),
+        epoch_height: 0,
         attached_deposit: 0,
         random_seed: H256::zero(),
         prepaid_gas: DEFAULT_PREPAID_GAS,
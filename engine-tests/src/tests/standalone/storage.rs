@@ -166,6 +166,7 @@ fn test_block_index() {
     let block_metadata = BlockMetadata {
         timestamp: Timestamp::new(23_000),
         random_seed: H256([91u8; 32]),
+        epoch_height: 17,
     };
 
     // write block hash / height association
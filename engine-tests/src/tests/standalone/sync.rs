@@ -3,7 +3,7 @@ use aurora_engine_sdk::env::{Env, Timestamp};
 use aurora_engine_types::types::{Address, Balance, Fee, NEP141Wei, Wei};
 use aurora_engine_types::{account_id::AccountId, H160, H256, U256};
 use borsh::{BorshDeserialize, BorshSerialize};
-use engine_standalone_storage::sync;
+use engine_standalone_storage::{block_header, sync};
 
 use crate::test_utils::{self, standalone::StandaloneRunner};
 
@@ -406,6 +406,151 @@ fn test_consume_submit_message() {
     assert_eq!(runner.get_nonce(&signer_address), U256::one());
 }
 
+#[test]
+fn test_block_header_construction() {
+    let (mut runner, block_message) = initialize();
+
+    let header = runner.storage.get_block_header(block_message.hash).unwrap();
+    assert_eq!(header.number, block_message.height);
+    assert_eq!(header.timestamp, block_message.metadata.timestamp.secs());
+    assert_eq!(header.mix_hash, block_message.metadata.random_seed);
+    assert_eq!(header.gas_used, 0);
+    assert_eq!(
+        header.transactions_root,
+        block_header::ordered_trie_root::<Vec<u8>>(&[])
+    );
+
+    let mut signer = test_utils::Signer::random();
+    let signer_address = test_utils::address_from_secret_key(&signer.secret_key);
+    runner.mint_account(
+        signer_address,
+        Wei::new_u64(800_000),
+        signer.nonce.into(),
+        None,
+    );
+
+    runner.env.block_height += 1;
+    let next_block = sync::types::BlockMessage {
+        height: runner.env.block_height,
+        hash: test_utils::standalone::mocks::compute_block_hash(runner.env.block_height),
+        metadata: engine_standalone_storage::BlockMetadata {
+            timestamp: Timestamp::new(3_000_000_000),
+            random_seed: H256([3u8; 32]),
+            epoch_height: 3,
+        },
+    };
+    sync::consume_message(
+        &mut runner.storage,
+        sync::types::Message::Block(next_block.clone()),
+    )
+    .unwrap();
+    // The header of the block inserted by `mint_account` is persisted once its successor arrives
+    let parent_hash = runner
+        .storage
+        .get_block_hash_by_height(next_block.height - 1)
+        .unwrap();
+    assert_eq!(
+        runner
+            .storage
+            .get_block_header(next_block.hash)
+            .unwrap()
+            .parent_hash,
+        runner.storage.get_block_header(parent_hash).unwrap().hash()
+    );
+
+    let transaction = test_utils::transfer(
+        Address::new(H160([1u8; 20])),
+        Wei::new_u64(115_321),
+        signer.use_nonce().into(),
+    );
+    let signed_transaction =
+        test_utils::sign_transaction(transaction, Some(runner.chain_id), &signer.secret_key);
+    let transaction_bytes = rlp::encode(&signed_transaction).to_vec();
+    let eth_transaction =
+        crate::prelude::transactions::EthTransactionKind::Legacy(signed_transaction);
+    let transaction_message = sync::types::TransactionMessage {
+        block_hash: next_block.hash,
+        near_receipt_id: H256([8u8; 32]),
+        position: 0,
+        succeeded: true,
+        signer: runner.env.signer_account_id(),
+        caller: runner.env.predecessor_account_id(),
+        attached_near: 0,
+        transaction: sync::types::TransactionKind::Submit(eth_transaction),
+        promise_data: Vec::new(),
+    };
+    let outcome = match sync::consume_message(
+        &mut runner.storage,
+        sync::types::Message::Transaction(Box::new(transaction_message)),
+    )
+    .unwrap()
+    {
+        sync::ConsumeMessageOutcome::TransactionIncluded(outcome) => outcome,
+        other => panic!("Unexpected outcome {:?}", other),
+    };
+
+    let receipt = runner
+        .storage
+        .get_transaction_receipt(engine_standalone_storage::TransactionIncluded {
+            block_hash: next_block.hash,
+            position: 0,
+        })
+        .unwrap();
+    assert!(receipt.status);
+    assert_eq!(receipt.transaction_type, None);
+
+    let header = runner.storage.get_block_header(next_block.hash).unwrap();
+    assert_eq!(header.number, next_block.height);
+    assert_eq!(header.timestamp, 3);
+    assert_eq!(header.mix_hash, next_block.metadata.random_seed);
+    assert_eq!(header.gas_used, receipt.gas_used);
+    assert_eq!(header.beneficiary, aurora_engine::engine::DEFAULT_COINBASE);
+    assert_eq!(header.gas_limit, U256::max_value());
+    assert_eq!(
+        header.transactions_root,
+        block_header::ordered_trie_root(&[transaction_bytes])
+    );
+    assert_eq!(
+        header.receipts_root,
+        block_header::ordered_trie_root(&[receipt.encode(receipt.gas_used)])
+    );
+    assert_eq!(
+        header,
+        rlp::decode::<block_header::BlockHeader>(&rlp::encode(&header)).unwrap()
+    );
+
+    // Headers are chained through the hash of the previous header.
+    runner.env.block_height += 1;
+    let child_block = sync::types::BlockMessage {
+        height: runner.env.block_height,
+        hash: test_utils::standalone::mocks::compute_block_hash(runner.env.block_height),
+        metadata: next_block.metadata,
+    };
+    sync::consume_message(
+        &mut runner.storage,
+        sync::types::Message::Block(child_block.clone()),
+    )
+    .unwrap();
+    let child_header = runner.storage.get_block_header(child_block.hash).unwrap();
+    assert_eq!(child_header.parent_hash, header.hash());
+
+    // Reverting the transaction rebuilds the header and the parent hash of its child.
+    runner
+        .storage
+        .revert_transaction_included(outcome.hash, &outcome.info, &outcome.diff)
+        .unwrap();
+    let header = runner.storage.get_block_header(next_block.hash).unwrap();
+    assert_eq!(header.gas_used, 0);
+    assert_eq!(
+        header.transactions_root,
+        block_header::ordered_trie_root::<Vec<u8>>(&[])
+    );
+    let child_header = runner.storage.get_block_header(child_block.hash).unwrap();
+    assert_eq!(child_header.parent_hash, header.hash());
+
+    runner.close()
+}
+
 fn mock_proof(recipient_address: Address, deposit_amount: Wei) -> aurora_engine::proof::Proof {
     let eth_custodian_address = test_utils::standalone::mocks::ETH_CUSTODIAN_ADDRESS;
 
@@ -473,6 +618,7 @@ fn sample_block() -> sync::types::BlockMessage {
         metadata: engine_standalone_storage::BlockMetadata {
             timestamp: Timestamp::new(1_000_001),
             random_seed: H256([2u8; 32]),
+            epoch_height: 2,
         },
     }
 }
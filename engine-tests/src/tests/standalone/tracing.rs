@@ -58,6 +58,7 @@ fn test_evm_tracing_with_storage() {
     let block_metadata = engine_standalone_storage::BlockMetadata {
         timestamp: runner.env.block_timestamp(),
         random_seed: runner.env.random_seed(),
+        epoch_height: runner.env.epoch_height(),
     };
     runner
         .storage
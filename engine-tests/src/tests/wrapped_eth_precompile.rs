@@ -0,0 +1,234 @@
+use crate::test_utils::{self, AuroraRunner, Signer};
+use aurora_engine::parameters::SubmitResult;
+use aurora_engine_precompiles::wrapped_eth::{self, events, selectors};
+use aurora_engine_transactions::legacy::TransactionLegacy;
+use aurora_engine_types::types::{Address, Wei};
+use aurora_engine_types::{H256, U256};
+use ethabi::Token;
+
+const INITIAL_BALANCE: Wei = Wei::new_u64(1_000_000);
+
+fn call(
+    runner: &mut AuroraRunner,
+    signer: &mut Signer,
+    selector: [u8; 4],
+    args: &[Token],
+) -> SubmitResult {
+    let transaction = TransactionLegacy {
+        nonce: signer.use_nonce().into(),
+        gas_price: U256::zero(),
+        gas_limit: u64::MAX.into(),
+        to: Some(wrapped_eth::ADDRESS),
+        value: Wei::zero(),
+        data: [selector.to_vec(), ethabi::encode(args)].concat(),
+    };
+    runner
+        .submit_transaction(&signer.secret_key, transaction)
+        .unwrap()
+}
+
+fn view_uint(
+    runner: &mut AuroraRunner,
+    signer: &mut Signer,
+    selector: [u8; 4],
+    args: &[Token],
+) -> U256 {
+    let result = call(runner, signer, selector, args);
+    U256::from_big_endian(test_utils::unwrap_success_slice(&result))
+}
+
+fn assert_transfer_event(result: &SubmitResult, from: Address, to: Address, amount: u64) {
+    assert_eq!(result.logs.len(), 1);
+    let log = &result.logs[0];
+    assert_eq!(log.address, wrapped_eth::ADDRESS);
+    assert_eq!(
+        log.topics,
+        vec![
+            events::TRANSFER.0,
+            H256::from(from.raw()).0,
+            H256::from(to.raw()).0
+        ]
+    );
+    assert_eq!(log.data, ethabi::encode(&[Token::Uint(amount.into())]));
+}
+
+#[test]
+fn test_wrapped_eth_precompile() {
+    let mut runner = test_utils::deploy_evm();
+    runner.context.block_index = aurora_engine::engine::EXTENDED_PRECOMPILES_HEIGHT;
+    let mut alice = Signer::random();
+    let mut bob = Signer::random();
+    let mut spender = Signer::random();
+    let alice_address = test_utils::address_from_secret_key(&alice.secret_key);
+    let bob_address = test_utils::address_from_secret_key(&bob.secret_key);
+    let spender_address = test_utils::address_from_secret_key(&spender.secret_key);
+    runner.create_address(alice_address, INITIAL_BALANCE, U256::zero());
+
+    // transfer moves native ETH
+    let result = call(
+        &mut runner,
+        &mut alice,
+        selectors::TRANSFER,
+        &[Token::Address(bob_address.raw()), Token::Uint(100.into())],
+    );
+    assert_eq!(
+        test_utils::unwrap_success_slice(&result),
+        ethabi::encode(&[Token::Bool(true)])
+    );
+    assert_transfer_event(&result, alice_address, bob_address, 100);
+    assert_eq!(runner.get_balance(bob_address), Wei::new_u64(100));
+    assert_eq!(
+        view_uint(
+            &mut runner,
+            &mut bob,
+            selectors::BALANCE_OF,
+            &[Token::Address(bob_address.raw())]
+        ),
+        U256::from(100)
+    );
+
+    // transferFrom spends the allowance
+    let result = call(
+        &mut runner,
+        &mut alice,
+        selectors::APPROVE,
+        &[
+            Token::Address(spender_address.raw()),
+            Token::Uint(50.into()),
+        ],
+    );
+    assert_eq!(result.logs[0].topics[0], events::APPROVAL.0);
+    let allowance = |runner: &mut AuroraRunner, spender: &mut Signer| {
+        view_uint(
+            runner,
+            spender,
+            selectors::ALLOWANCE,
+            &[
+                Token::Address(alice_address.raw()),
+                Token::Address(spender_address.raw()),
+            ],
+        )
+    };
+    assert_eq!(allowance(&mut runner, &mut spender), U256::from(50));
+
+    let transfer_from_args = |amount: u64| {
+        [
+            Token::Address(alice_address.raw()),
+            Token::Address(bob_address.raw()),
+            Token::Uint(amount.into()),
+        ]
+    };
+    let result = call(
+        &mut runner,
+        &mut spender,
+        selectors::TRANSFER_FROM,
+        &transfer_from_args(60),
+    );
+    assert_eq!(
+        test_utils::unwrap_revert(result),
+        b"ERR_WRAPPED_ETH_INSUFFICIENT_ALLOWANCE"
+    );
+    let result = call(
+        &mut runner,
+        &mut spender,
+        selectors::TRANSFER_FROM,
+        &transfer_from_args(50),
+    );
+    assert_transfer_event(&result, alice_address, bob_address, 50);
+    assert_eq!(allowance(&mut runner, &mut spender), U256::zero());
+    assert_eq!(runner.get_balance(bob_address), Wei::new_u64(150));
+    assert_eq!(
+        runner.get_balance(alice_address),
+        Wei::new_u64(1_000_000 - 150)
+    );
+
+    // Transfers are limited by the native balance
+    let result = call(
+        &mut runner,
+        &mut bob,
+        selectors::TRANSFER,
+        &[Token::Address(alice_address.raw()), Token::Uint(151.into())],
+    );
+    assert_eq!(
+        test_utils::unwrap_revert(result),
+        b"ERR_WRAPPED_ETH_INSUFFICIENT_BALANCE"
+    );
+    assert_eq!(runner.get_balance(bob_address), Wei::new_u64(150));
+}
+
+#[test]
+fn test_wrapped_eth_approval_reverts_with_call() {
+    let mut runner = test_utils::deploy_evm();
+    runner.context.block_index = aurora_engine::engine::EXTENDED_PRECOMPILES_HEIGHT;
+    let mut signer = Signer::random();
+    let spender_address = Address::from_array([1; 20]);
+
+    // Contracts forwarding their input to the precompile, then stopping or reverting.
+    let deploy_forwarder = |runner: &mut AuroraRunner, signer: &mut Signer, end: &[u8]| {
+        let runtime_code = [
+            // CALLDATACOPY(0, 0, CALLDATASIZE)
+            &[0x36, 0x60, 0x00, 0x60, 0x00, 0x37][..],
+            // CALL(GAS, ADDRESS, 0, 0, CALLDATASIZE, 0, 0) POP
+            &[0x60, 0x00, 0x60, 0x00, 0x36, 0x60, 0x00, 0x60, 0x00, 0x73],
+            wrapped_eth::ADDRESS.as_bytes(),
+            &[0x5a, 0xf1, 0x50],
+            end,
+        ]
+        .concat();
+        // Returns the runtime code, which follows this 11 bytes prefix.
+        let init_code = [
+            &[0x60, runtime_code.len() as u8][..],
+            &[0x80, 0x60, 0x0b, 0x60, 0x00, 0x39, 0x60, 0x00, 0xf3],
+            &runtime_code,
+        ]
+        .concat();
+        let result = runner
+            .submit_with_signer(signer, |nonce| {
+                test_utils::create_deploy_transaction(init_code, nonce)
+            })
+            .unwrap();
+        Address::try_from_slice(test_utils::unwrap_success_slice(&result)).unwrap()
+    };
+    let stopping = deploy_forwarder(&mut runner, &mut signer, &[0x00]);
+    let reverting = deploy_forwarder(&mut runner, &mut signer, &[0x60, 0x00, 0x60, 0x00, 0xfd]);
+
+    let approve_data = [
+        selectors::APPROVE.to_vec(),
+        ethabi::encode(&[
+            Token::Address(spender_address.raw()),
+            Token::Uint(50.into()),
+        ]),
+    ]
+    .concat();
+    for contract in [stopping, reverting] {
+        let transaction = TransactionLegacy {
+            nonce: signer.use_nonce().into(),
+            gas_price: U256::zero(),
+            gas_limit: u64::MAX.into(),
+            to: Some(contract),
+            value: Wei::zero(),
+            data: approve_data.clone(),
+        };
+        runner
+            .submit_transaction(&signer.secret_key, transaction)
+            .unwrap();
+    }
+
+    let allowance = |runner: &mut AuroraRunner, signer: &mut Signer, owner: Address| {
+        view_uint(
+            runner,
+            signer,
+            selectors::ALLOWANCE,
+            &[
+                Token::Address(owner.raw()),
+                Token::Address(spender_address.raw()),
+            ],
+        )
+    };
+    assert_eq!(
+        allowance(&mut runner, &mut signer, stopping),
+        U256::from(50)
+    );
+    // The approval is reverted along with the call which made it.
+    assert_eq!(allowance(&mut runner, &mut signer, reverting), U256::zero());
+}
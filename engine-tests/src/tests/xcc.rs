@@ -1,14 +1,17 @@
 use crate::test_utils::erc20::{ERC20Constructor, ERC20};
+use crate::test_utils::gas_calibration::{Baseline, GasCalibration};
 use crate::test_utils::{self, AuroraRunner};
 use crate::tests::erc20_connector::sim_tests;
 use crate::tests::state_migration::{deploy_evm, AuroraAccount};
+use aurora_engine::parameters::{GetStorageAtArgs, SubmitResult, TransactionStatus, ViewCallArgs};
 use aurora_engine_precompiles::xcc::{self, costs, cross_contract_call};
 use aurora_engine_transactions::legacy::TransactionLegacy;
 use aurora_engine_types::parameters::{
-    CrossContractCallArgs, PromiseArgs, PromiseCreateArgs, PromiseWithCallbackArgs,
+    CrossContractCallArgs, EvmCallbackArgs, NearPromise, NearPublicKey, PromiseAction, PromiseArgs,
+    PromiseBatchAction, PromiseCreateArgs, PromiseWithCallbackArgs, SimpleNearPromise, XccPermit,
 };
-use aurora_engine_types::types::{Address, EthGas, NearGas, Wei, Yocto};
-use aurora_engine_types::U256;
+use aurora_engine_types::types::{Address, NearGas, PromiseResult, Wei, Yocto};
+use aurora_engine_types::{H256, U256};
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_primitives::transaction::Action;
 use near_primitives_core::contract::ContractCode;
@@ -65,26 +68,14 @@ fn test_xcc_eth_gas_cost() {
         panic!("Unexpected baseline status: {:?}", baseline_result);
     }
 
-    let mut profile_for_promise = |p: PromiseArgs| -> (u64, u64, u64) {
+    let mut calibration = GasCalibration::new(
+        cross_contract_call::ADDRESS,
+        Baseline::Fixed(baseline.all_gas()),
+    );
+    let mut sample = |p: PromiseArgs| {
         let data = CrossContractCallArgs::Eager(p).try_to_vec().unwrap();
-        let input_length = data.len();
-        let (submit_result, profile) = runner
-            .submit_with_signer_profiled(&mut signer, |nonce| TransactionLegacy {
-                nonce,
-                gas_price: U256::zero(),
-                gas_limit: u64::MAX.into(),
-                to: Some(cross_contract_call::ADDRESS),
-                value: Wei::zero(),
-                data,
-            })
-            .unwrap();
-        assert!(submit_result.status.is_ok());
-        // Subtract off baseline transaction to isolate just precompile things
-        (
-            u64::try_from(input_length).unwrap(),
-            profile.all_gas() - baseline.all_gas(),
-            submit_result.gas_used,
-        )
+        let input_length = u64::try_from(data.len()).unwrap();
+        calibration.sample(&mut runner, &mut signer, input_length, data);
     };
 
     let promise = PromiseCreateArgs {
@@ -95,58 +86,433 @@ fn test_xcc_eth_gas_cost() {
         attached_gas: NearGas::new(500),
     };
     // Shorter input
-    let (x1, y1, evm1) = profile_for_promise(PromiseArgs::Create(promise.clone()));
+    sample(PromiseArgs::Create(promise.clone()));
     // longer input
-    let (x2, y2, evm2) = profile_for_promise(PromiseArgs::Callback(PromiseWithCallbackArgs {
+    sample(PromiseArgs::Callback(PromiseWithCallbackArgs {
         base: promise.clone(),
         callback: promise,
     }));
 
-    // NEAR costs (inferred from a line through (x1, y1) and (x2, y2))
-    let xcc_cost_per_byte = (y2 - y1) / (x2 - x1);
-    let xcc_base_cost = NearGas::new(y1 - xcc_cost_per_byte * x1);
+    calibration.assert_cost(
+        "xcc",
+        5,
+        costs::CROSS_CONTRACT_CALL_BASE,
+        Some(costs::CROSS_CONTRACT_CALL_BYTE),
+    );
+    // As a sanity check, confirm that the total EVM gas spent aligns with expectations
+    calibration.assert_evm_gas(20);
+}
 
-    // Convert to EVM cost using conversion ratio
-    let xcc_base_cost = EthGas::new(xcc_base_cost.as_u64() / costs::CROSS_CONTRACT_CALL_NEAR_GAS);
-    let xcc_cost_per_byte = xcc_cost_per_byte / costs::CROSS_CONTRACT_CALL_NEAR_GAS;
+#[test]
+fn test_xcc_callback() {
+    let mut runner = test_utils::deploy_evm();
+    let mut signer = test_utils::Signer::random();
 
-    assert!(
-        test_utils::within_x_percent(
-            5,
-            xcc_base_cost.as_u64(),
-            costs::CROSS_CONTRACT_CALL_BASE.as_u64()
+    // Contract which returns its caller followed by its input.
+    let runtime_code = hex::decode("36600060203733600052366020016000f3").unwrap();
+    let init_code = [
+        hex::decode("6011600c60003960116000f3").unwrap(),
+        runtime_code,
+    ]
+    .concat();
+    let result = runner
+        .submit_with_signer(&mut signer, |nonce| TransactionLegacy {
+            nonce,
+            gas_price: U256::zero(),
+            gas_limit: u64::MAX.into(),
+            to: None,
+            value: Wei::zero(),
+            data: init_code,
+        })
+        .unwrap();
+    let contract = Address::try_from_slice(&test_utils::unwrap_success(result)).unwrap();
+
+    let args = EvmCallbackArgs {
+        selector: [0xaa, 0xbb, 0xcc, 0xdd],
+        gas: NearGas::new(100_000_000_000_000),
+    };
+    let router_account = format!("{}.{}", contract.encode(), runner.aurora_account_id);
+    let promise_result = PromiseResult::Successful(b"hello".to_vec());
+    runner.promise_results = vec![promise_result.clone()];
+    let (maybe_outcome, maybe_error) =
+        runner.call("xcc_callback", &router_account, args.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+    let result =
+        SubmitResult::try_from_slice(&maybe_outcome.unwrap().return_data.as_value().unwrap())
+            .unwrap();
+    let output = test_utils::unwrap_success(result);
+
+    // The callback is made by the xcc precompile, with the serialized promise result
+    assert_eq!(&output[12..32], cross_contract_call::ADDRESS.as_bytes());
+    assert_eq!(&output[32..36], &args.selector);
+    assert_eq!(
+        ethabi::decode(&[ethabi::ParamType::Bytes], &output[36..]).unwrap(),
+        vec![ethabi::Token::Bytes(promise_result.try_to_vec().unwrap())]
+    );
+
+    // Only router contracts can deliver results
+    let (_, maybe_error) = runner.call("xcc_callback", "alice.near", args.try_to_vec().unwrap());
+    assert!(maybe_error.is_some());
+    runner.promise_results.clear();
+}
+
+#[test]
+fn test_xcc_router_takeover() {
+    let mut runner = test_utils::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let sender = test_utils::address_from_secret_key(&signer.secret_key);
+    let router_account = format!("{}.{}", sender.encode(), runner.aurora_account_id);
+    let engine_account = runner.aurora_account_id.clone();
+
+    let mut call = |target_account: &str, action: PromiseAction| {
+        let promise = PromiseBatchAction {
+            target_account_id: target_account.parse().unwrap(),
+            actions: vec![action],
+        };
+        let data = CrossContractCallArgs::Delayed(PromiseArgs::Recursive(NearPromise::Simple(
+            SimpleNearPromise::Batch(promise),
+        )))
+        .try_to_vec()
+        .unwrap();
+        runner
+            .submit_with_signer(&mut signer, |nonce| TransactionLegacy {
+                nonce,
+                gas_price: U256::zero(),
+                gas_limit: u64::MAX.into(),
+                to: Some(cross_contract_call::ADDRESS),
+                value: Wei::zero(),
+                data,
+            })
+            .unwrap()
+            .status
+    };
+
+    // The router can be neither deleted, handed over with a full access key nor have its
+    // code replaced, and neither can the engine.
+    let expected = TransactionStatus::Revert(b"ERR_XCC_ROUTER_TAKEOVER".to_vec());
+    for target_account in [router_account.as_str(), engine_account.as_str()] {
+        assert_eq!(
+            call(
+                target_account,
+                PromiseAction::DeleteAccount {
+                    beneficiary_id: "alice.near".parse().unwrap(),
+                }
+            ),
+            expected
+        );
+        assert_eq!(
+            call(
+                target_account,
+                PromiseAction::AddFullAccessKey {
+                    public_key: NearPublicKey::Ed25519([7; 32]),
+                    nonce: 0,
+                }
+            ),
+            expected
+        );
+        assert_eq!(
+            call(
+                target_account,
+                PromiseAction::DeployConotract {
+                    code: b"some_code".to_vec(),
+                }
+            ),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_xcc_permit() {
+    let mut runner = test_utils::deploy_evm();
+    let _ = runner.call(test_utils::FACTORY_UPDATE, "aurora", contract_bytes());
+    let mut owner = test_utils::Signer::random();
+    let mut signer = test_utils::Signer::random();
+    let mut deployer = test_utils::Signer::random();
+    let owner_address = test_utils::address_from_secret_key(&owner.secret_key);
+    let signer_address = test_utils::address_from_secret_key(&signer.secret_key);
+    let chain_id = runner.chain_id;
+    // The owner holds the wNEAR, but never approves the precompile to spend it.
+    let wnear_erc20 = deploy_erc20(&mut runner, &mut owner);
+    let _ = runner.call(
+        test_utils::FACTORY_SET_WNEAR_ADDRESS,
+        "aurora",
+        wnear_erc20.0.address.as_bytes().to_vec(),
+    );
+
+    let attached_near = 1_000_000_000_000_000_000_000_000;
+    // The promises are not executed, so the router of the caller is never recorded as deployed
+    // and every call also pays for its storage.
+    let required_near = attached_near + xcc::state::STORAGE_AMOUNT.as_u128();
+    let call = || {
+        CrossContractCallArgs::Eager(PromiseArgs::Create(PromiseCreateArgs {
+            target_account_id: "some_account.near".parse().unwrap(),
+            method: "some_method".into(),
+            args: b"hello_world".to_vec(),
+            attached_balance: Yocto::new(attached_near),
+            attached_gas: NearGas::new(10_000_000_000_000),
+        }))
+    };
+    let call_hash = aurora_engine_sdk::keccak(&call().try_to_vec().unwrap());
+    let sign_permit = |caller: Address, nonce: u64, deadline: u64, call_hash: H256| {
+        let value = U256::from(WNEAR_AMOUNT);
+        let digest = xcc::permit::digest(
+            chain_id.into(),
+            owner_address,
+            caller,
+            value,
+            nonce.into(),
+            deadline,
+            call_hash,
+        );
+        let (signature, recovery_id) =
+            libsecp256k1::sign(&libsecp256k1::Message::parse(&digest.0), &owner.secret_key);
+        let signature = signature.serialize();
+        XccPermit {
+            owner: owner_address,
+            value: aurora_engine_types::types::u256_to_arr(&value),
+            deadline,
+            v: recovery_id.serialize() + 27,
+            r: signature[0..32].try_into().unwrap(),
+            s: signature[32..64].try_into().unwrap(),
+        }
+    };
+    let mut submit = |runner: &mut AuroraRunner, to: Address, data: Vec<u8>| {
+        runner
+            .submit_with_signer(&mut signer, |nonce| TransactionLegacy {
+                nonce,
+                gas_price: U256::zero(),
+                gas_limit: u64::MAX.into(),
+                to: Some(to),
+                value: Wei::zero(),
+                data,
+            })
+            .unwrap()
+            .status
+    };
+    let with_permit = |permit: XccPermit| {
+        CrossContractCallArgs::WithPermit(permit, Box::new(call()))
+            .try_to_vec()
+            .unwrap()
+    };
+    let deadline = 1_000_000;
+
+    // The permit only allows the caller it was signed for to make the call it was signed for,
+    // until its deadline.
+    let invalid_signature = TransactionStatus::Revert(b"ERR_XCC_PERMIT_SIGNATURE".to_vec());
+    let permit = sign_permit(Address::from_array([1; 20]), 0, deadline, call_hash);
+    assert_eq!(
+        submit(
+            &mut runner,
+            cross_contract_call::ADDRESS,
+            with_permit(permit)
         ),
-        "Incorrect xcc base cost. Expected: {} Actual: {}",
-        xcc_base_cost,
-        costs::CROSS_CONTRACT_CALL_BASE
+        invalid_signature
     );
+    let permit = sign_permit(signer_address, 0, deadline, H256([2; 32]));
+    assert_eq!(
+        submit(
+            &mut runner,
+            cross_contract_call::ADDRESS,
+            with_permit(permit)
+        ),
+        invalid_signature
+    );
+    let permit = sign_permit(signer_address, 0, 0, call_hash);
+    assert_eq!(
+        submit(
+            &mut runner,
+            cross_contract_call::ADDRESS,
+            with_permit(permit)
+        ),
+        TransactionStatus::Revert(b"ERR_XCC_PERMIT_EXPIRED".to_vec())
+    );
+    assert_eq!(get_permit_nonce(&runner, owner_address), 0);
 
-    assert!(
-        test_utils::within_x_percent(
-            5,
-            xcc_cost_per_byte,
-            costs::CROSS_CONTRACT_CALL_BYTE.as_u64()
+    // A valid permit pays for the call (and the router of the caller) with the wNEAR of the owner
+    let permit = sign_permit(signer_address, 0, deadline, call_hash);
+    let status = submit(
+        &mut runner,
+        cross_contract_call::ADDRESS,
+        with_permit(permit.clone()),
+    );
+    assert!(status.is_ok(), "Unexpected status {:?}", status);
+    let mut owner_balance = WNEAR_AMOUNT - required_near;
+    assert_eq!(
+        erc20_balance(&runner, &wnear_erc20, owner_address),
+        U256::from(owner_balance)
+    );
+    assert_eq!(get_permit_nonce(&runner, owner_address), 1);
+
+    // It cannot be used again
+    assert_eq!(
+        submit(
+            &mut runner,
+            cross_contract_call::ADDRESS,
+            with_permit(permit)
         ),
-        "Incorrect xcc per byte cost. Expected: {} Actual: {}",
-        xcc_cost_per_byte,
-        costs::CROSS_CONTRACT_CALL_BYTE
+        invalid_signature
     );
 
-    // As a sanity check, confirm that the total EVM gas spent aligns with expectations
-    let total_gas1 = y1 + baseline.all_gas();
-    let total_gas2 = y2 + baseline.all_gas();
-    assert!(
-        test_utils::within_x_percent(20, evm1, total_gas1 / costs::CROSS_CONTRACT_CALL_NEAR_GAS),
-        "Incorrect EVM gas used. Expected: {} Actual: {}",
-        evm1,
-        total_gas1 / costs::CROSS_CONTRACT_CALL_NEAR_GAS
+    // A permit used by a transaction which reverts is not consumed. This contract passes its
+    // input to the precompile, then reverts with the success flag of that call.
+    let reverter_code = [
+        "36600060003760006000366000600073",
+        &hex::encode(cross_contract_call::ADDRESS.as_bytes()),
+        "5af160005260206000fd",
+    ]
+    .concat();
+    let init_code = hex::decode(["602e600c600039602e6000f3", &reverter_code].concat()).unwrap();
+    let result = runner
+        .submit_with_signer(&mut deployer, |nonce| TransactionLegacy {
+            nonce,
+            gas_price: U256::zero(),
+            gas_limit: u64::MAX.into(),
+            to: None,
+            value: Wei::zero(),
+            data: init_code,
+        })
+        .unwrap();
+    let reverter = Address::try_from_slice(&test_utils::unwrap_success(result)).unwrap();
+    let permit = sign_permit(reverter, 1, deadline, call_hash);
+    let mut precompile_success = [0u8; 32];
+    precompile_success[31] = 1;
+    assert_eq!(
+        submit(&mut runner, reverter, with_permit(permit)),
+        TransactionStatus::Revert(precompile_success.to_vec())
     );
-    assert!(
-        test_utils::within_x_percent(20, evm2, total_gas2 / costs::CROSS_CONTRACT_CALL_NEAR_GAS),
-        "Incorrect EVM gas used. Expected: {} Actual: {}",
-        evm2,
-        total_gas2 / costs::CROSS_CONTRACT_CALL_NEAR_GAS
+    assert_eq!(
+        erc20_balance(&runner, &wnear_erc20, owner_address),
+        U256::from(owner_balance)
+    );
+    assert_eq!(get_permit_nonce(&runner, owner_address), 1);
+
+    // So the next nonce can still be used
+    let permit = sign_permit(signer_address, 1, deadline, call_hash);
+    let status = submit(
+        &mut runner,
+        cross_contract_call::ADDRESS,
+        with_permit(permit),
     );
+    assert!(status.is_ok(), "Unexpected status {:?}", status);
+    owner_balance -= required_near;
+    assert_eq!(
+        erc20_balance(&runner, &wnear_erc20, owner_address),
+        U256::from(owner_balance)
+    );
+    assert_eq!(get_permit_nonce(&runner, owner_address), 2);
+}
+
+fn get_permit_nonce(runner: &AuroraRunner, owner: Address) -> u64 {
+    let standalone_runner = runner.standalone_runner.as_ref().unwrap();
+    standalone_runner
+        .storage
+        .with_engine_access(runner.context.block_index + 1, 0, &[], |io| {
+            xcc::state::get_permit_nonce(&io, &owner)
+        })
+        .result
+}
+
+fn erc20_balance(runner: &AuroraRunner, token: &ERC20, address: Address) -> U256 {
+    let args = ViewCallArgs {
+        sender: address,
+        address: token.0.address,
+        amount: [0; 32],
+        input: token.balance_of(address, U256::zero()).data,
+    };
+    match runner.view_call(args).unwrap() {
+        TransactionStatus::Succeed(bytes) => U256::from_big_endian(&bytes),
+        other => panic!("Unexpected status {:?}", other),
+    }
+}
+
+#[test]
+fn test_xcc_upgrade_routers() {
+    use crate::test_utils::standalone::mocks::{promise, storage};
+    use aurora_engine::xcc::{CodeVersion, RouterCode};
+    use aurora_engine_sdk::io::IO;
+    use aurora_engine_types::account_id::AccountId;
+    use aurora_engine_types::parameters::PromiseAction;
+
+    let storage = std::sync::RwLock::new(storage::Storage::default());
+    let mut io = storage::StoragePointer(&storage);
+    let aurora: AccountId = "aurora".parse().unwrap();
+    let with_router = [Address::from_array([1; 20]), Address::from_array([2; 20])];
+    let without_router = Address::from_array([3; 20]);
+
+    aurora_engine::xcc::update_router_code(&mut io, &RouterCode::new(vec![1]));
+    for address in &with_router {
+        aurora_engine::xcc::set_code_version_of_address(&mut io, address, CodeVersion(1));
+    }
+    let new_code = vec![2];
+    aurora_engine::xcc::update_router_code(&mut io, &RouterCode::new(new_code.clone()));
+    assert_eq!(
+        aurora_engine::xcc::get_router_version_counts(&io),
+        vec![(CodeVersion(1), 2), (CodeVersion(2), 0)]
+    );
+    assert!(aurora_engine::xcc::router_version_counts_complete(&io));
+
+    // Only enough gas to upgrade one router: the address without a router is skipped, and the
+    // last address is left for another transaction.
+    let mut handler = promise::PromiseTracker::default();
+    let prepaid_gas =
+        aurora_engine::xcc::UPGRADE_ROUTERS_BASE_GAS + aurora_engine::xcc::UPGRADE_ROUTER_GAS;
+    let addresses = [without_router, with_router[0], with_router[1]];
+    let processed =
+        aurora_engine::xcc::upgrade_routers(&io, &mut handler, &addresses, prepaid_gas, &aurora);
+    assert_eq!(processed, 2);
+
+    let batches: Vec<_> = handler
+        .scheduled_promises
+        .values()
+        .filter_map(|promise| match promise {
+            promise::PromiseArgs::Batch(batch) => Some(batch),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(
+        batches[0].target_account_id.as_ref(),
+        format!("{}.aurora", with_router[0].encode())
+    );
+    assert_eq!(
+        batches[0].actions[0],
+        PromiseAction::DeployConotract { code: new_code }
+    );
+    assert!(matches!(
+        &batches[0].actions[1],
+        PromiseAction::FunctionCall { name, .. } if name == "migrate"
+    ));
+
+    // The version is recorded by the callback of the deployment, only if it succeeded
+    let args = aurora_engine::xcc::AddressVersionUpdateArgs {
+        address: with_router[0],
+        version: CodeVersion(2),
+    };
+    let mut handler = promise::PromiseTracker::default();
+    handler.promise_results = vec![PromiseResult::Failed];
+    aurora_engine::xcc::handle_address_version_update(&mut io, &handler, &args);
+    assert_eq!(
+        aurora_engine::xcc::get_code_version_of_address(&io, &with_router[0]),
+        Some(CodeVersion(1))
+    );
+    handler.promise_results = vec![PromiseResult::Successful(Vec::new())];
+    aurora_engine::xcc::handle_address_version_update(&mut io, &handler, &args);
+    assert_eq!(
+        aurora_engine::xcc::get_router_version_counts(&io),
+        vec![(CodeVersion(1), 1), (CodeVersion(2), 1)]
+    );
+
+    // Routers recorded before the first code was counted make the counts incomplete
+    let storage = std::sync::RwLock::new(storage::Storage::default());
+    let mut io = storage::StoragePointer(&storage);
+    let version_key = aurora_engine_types::storage::bytes_to_key(
+        aurora_engine_types::storage::KeyPrefix::CrossContractCall,
+        aurora_engine::xcc::VERSION_KEY,
+    );
+    io.write_storage(&version_key, &1u32.to_le_bytes());
+    aurora_engine::xcc::update_router_code(&mut io, &RouterCode::new(vec![2]));
+    assert!(!aurora_engine::xcc::router_version_counts_complete(&io));
 }
 
 #[test]
@@ -160,44 +526,14 @@ fn test_xcc_precompile_scheduled() {
 }
 
 fn test_xcc_precompile_common(is_scheduled: bool) {
-    let aurora = deploy_evm();
-    let chain_id = AuroraRunner::default().chain_id;
-    let xcc_wasm_bytes = contract_bytes();
-    aurora
-        .user
-        .call(
-            aurora.contract.account_id(),
-            "factory_update",
-            &xcc_wasm_bytes,
-            near_sdk_sim::DEFAULT_GAS,
-            0,
-        )
-        .assert_success();
-
-    let mut signer = test_utils::Signer::random();
-    let signer_address = test_utils::address_from_secret_key(&signer.secret_key);
-
-    // Setup wNEAR contract and bridge it to Aurora
-    let wnear_account = deploy_wnear(&aurora);
-    let wnear_erc20 = sim_tests::deploy_erc20_from_nep_141(&wnear_account, &aurora);
-    sim_tests::transfer_nep_141_to_erc_20(
-        &wnear_account,
-        &wnear_erc20,
-        &aurora.user,
+    let XccSimContext {
+        aurora,
+        chain_id,
+        mut signer,
         signer_address,
-        WNEAR_AMOUNT,
-        &aurora,
-    );
-    aurora
-        .user
-        .call(
-            aurora.contract.account_id(),
-            "factory_set_wnear_address",
-            wnear_erc20.0.address.as_bytes(),
-            near_sdk_sim::DEFAULT_GAS,
-            0,
-        )
-        .assert_success();
+        wnear_account,
+        wnear_erc20,
+    } = xcc_sim_setup();
     let approve_tx = wnear_erc20.approve(
         cross_contract_call::ADDRESS,
         WNEAR_AMOUNT.into(),
@@ -369,6 +705,290 @@ fn test_xcc_precompile_common(is_scheduled: bool) {
     );
 }
 
+#[test]
+fn test_xcc_precompile_eager_with_callback() {
+    let XccSimContext {
+        aurora,
+        chain_id,
+        mut signer,
+        signer_address: _,
+        wnear_account,
+        wnear_erc20,
+    } = xcc_sim_setup();
+
+    // The callback can only be given the result of one promise, so promises running in
+    // parallel are rejected.
+    let ft_balance_of = |account_id: &str| {
+        SimpleNearPromise::Create(PromiseCreateArgs {
+            target_account_id: wnear_account.account_id.as_str().parse().unwrap(),
+            method: "ft_balance_of".into(),
+            args: json!({ "account_id": account_id }).to_string().into_bytes(),
+            attached_balance: Yocto::new(0),
+            attached_gas: NearGas::new(10_000_000_000_000),
+        })
+    };
+    let root_account = aurora.user.account_id.as_str();
+    let callback = EvmCallbackArgs {
+        selector: [0; 4],
+        gas: NearGas::new(50_000_000_000_000),
+    };
+    let xcc_args = CrossContractCallArgs::EagerWithCallback(
+        PromiseArgs::Recursive(NearPromise::And(vec![
+            NearPromise::Simple(ft_balance_of(root_account)),
+            NearPromise::Simple(ft_balance_of(root_account)),
+        ])),
+        callback,
+    );
+    let transaction = TransactionLegacy {
+        nonce: signer.use_nonce().into(),
+        gas_price: 0u64.into(),
+        gas_limit: u64::MAX.into(),
+        to: Some(cross_contract_call::ADDRESS),
+        value: Wei::zero(),
+        data: xcc_args.try_to_vec().unwrap(),
+    };
+    let result = submit_sim_transaction(&aurora, chain_id, &signer, transaction);
+    assert_eq!(
+        result.status,
+        TransactionStatus::Revert(b"ERR_XCC_CALLBACK_AND".to_vec())
+    );
+
+    // Deploy a contract which receives the result of its call, and give it the wNEAR
+    // needed to pay for its router.
+    let constructor = test_utils::solidity::ContractConstructor::force_compile(
+        "src/tests/res",
+        "target/solidity_build",
+        "xcc_caller.sol",
+        "XccCaller",
+    );
+    let deploy_tx = constructor.deploy_with_args(
+        signer.use_nonce().into(),
+        &[ethabi::Token::Address(cross_contract_call::ADDRESS.raw())],
+    );
+    let result = submit_sim_transaction(&aurora, chain_id, &signer, deploy_tx);
+    let caller_address = Address::try_from_slice(&test_utils::unwrap_success(result)).unwrap();
+    let caller = constructor.deployed_at(caller_address);
+    let transfer_tx = wnear_erc20.transfer(
+        caller_address,
+        WNEAR_AMOUNT.into(),
+        signer.use_nonce().into(),
+    );
+    let result = submit_sim_transaction(&aurora, chain_id, &signer, transfer_tx);
+    assert!(result.status.is_ok());
+    let approve_tx = caller.call_method_with_args(
+        "approveWNear",
+        &[
+            ethabi::Token::Address(wnear_erc20.0.address.raw()),
+            ethabi::Token::Uint(WNEAR_AMOUNT.into()),
+        ],
+        signer.use_nonce().into(),
+    );
+    let result = submit_sim_transaction(&aurora, chain_id, &signer, approve_tx);
+    assert!(result.status.is_ok());
+
+    // The contract calls the precompile, the router executes the promise and the engine
+    // delivers its result back to the contract, all in the same NEAR transaction.
+    let callback = EvmCallbackArgs {
+        selector: caller.abi.function("onResult").unwrap().short_signature(),
+        gas: NearGas::new(50_000_000_000_000),
+    };
+    let xcc_args = CrossContractCallArgs::EagerWithCallback(
+        PromiseArgs::Recursive(NearPromise::Simple(ft_balance_of(root_account))),
+        callback,
+    );
+    let xcc_tx = caller.call_method_with_args(
+        "crossContractCall",
+        &[ethabi::Token::Bytes(xcc_args.try_to_vec().unwrap())],
+        signer.use_nonce().into(),
+    );
+    let result = submit_sim_transaction(&aurora, chain_id, &signer, xcc_tx);
+    assert!(result.status.is_ok(), "Unexpected result {:?}", result);
+
+    let root_balance = sim_tests::nep_141_balance_of(root_account, &wnear_account, &aurora);
+    let expected_result = PromiseResult::Successful(format!("\"{}\"", root_balance).into_bytes());
+    // `lastResultHash` and `callbackCount` are in the second and third storage slots.
+    assert_eq!(
+        get_storage_at(&aurora, caller_address, 1),
+        aurora_engine_sdk::keccak(&expected_result.try_to_vec().unwrap())
+    );
+    assert_eq!(
+        get_storage_at(&aurora, caller_address, 2),
+        H256::from_low_u64_be(1)
+    );
+}
+
+#[test]
+fn test_xcc_precompile_cancel_and_expire() {
+    let XccSimContext {
+        aurora,
+        chain_id,
+        mut signer,
+        signer_address,
+        wnear_account: _,
+        wnear_erc20,
+    } = xcc_sim_setup();
+    let approve_tx = wnear_erc20.approve(
+        cross_contract_call::ADDRESS,
+        WNEAR_AMOUNT.into(),
+        signer.use_nonce().into(),
+    );
+    let result = submit_sim_transaction(&aurora, chain_id, &signer, approve_tx);
+    assert!(result.status.is_ok());
+
+    let router_account = format!(
+        "{}.{}",
+        hex::encode(signer_address.as_bytes()),
+        aurora.contract.account_id.as_str()
+    );
+    let attached_near: u128 = 1_000_000_000_000_000_000_000_000;
+    let promise = PromiseArgs::Create(PromiseCreateArgs {
+        target_account_id: "some_account.near".parse().unwrap(),
+        method: "some_method".into(),
+        args: b"{}".to_vec(),
+        attached_balance: Yocto::new(attached_near),
+        attached_gas: NearGas::new(10_000_000_000_000),
+    });
+    let mut xcc_call = |xcc_args: CrossContractCallArgs| {
+        let transaction = TransactionLegacy {
+            nonce: signer.use_nonce().into(),
+            gas_price: 0u64.into(),
+            gas_limit: u64::MAX.into(),
+            to: Some(cross_contract_call::ADDRESS),
+            value: Wei::zero(),
+            data: xcc_args.try_to_vec().unwrap(),
+        };
+        let result = submit_sim_transaction(&aurora, chain_id, &signer, transaction);
+        assert!(result.status.is_ok(), "Unexpected result {:?}", result);
+    };
+    let wnear_balance = || sim_tests::erc20_balance(&wnear_erc20, signer_address, &aurora);
+    let router_call = |method: &str, nonce: u64| {
+        aurora.user.call(
+            router_account.parse().unwrap(),
+            method,
+            format!(r#"{{"nonce": "{}"}}"#, nonce).as_bytes(),
+            near_sdk_sim::DEFAULT_GAS,
+            0,
+        )
+    };
+
+    // 1. A scheduled promise which is cancelled is refunded, and can no longer be executed
+    let expiry_block_height = aurora.user.borrow_runtime().current_block().block_height + 1000;
+    xcc_call(CrossContractCallArgs::DelayedWithExpiry(
+        promise.clone(),
+        expiry_block_height,
+    ));
+    let balance_after_schedule = wnear_balance();
+    assert_eq!(
+        balance_after_schedule,
+        U256::from(WNEAR_AMOUNT - attached_near - xcc::state::STORAGE_AMOUNT.as_u128())
+    );
+
+    xcc_call(CrossContractCallArgs::Cancel(0));
+    assert_eq!(wnear_balance(), balance_after_schedule + attached_near);
+    assert!(!router_call("execute_scheduled", 0).is_ok());
+
+    // 2. Once a scheduled promise expires, anyone can remove it, which refunds it
+    let expiry_block_height = aurora.user.borrow_runtime().current_block().block_height;
+    xcc_call(CrossContractCallArgs::DelayedWithExpiry(
+        promise,
+        expiry_block_height,
+    ));
+    let balance_after_schedule = wnear_balance();
+    assert_eq!(
+        balance_after_schedule,
+        U256::from(WNEAR_AMOUNT - attached_near - xcc::state::STORAGE_AMOUNT.as_u128())
+    );
+
+    aurora.user.borrow_runtime_mut().produce_blocks(1).unwrap();
+    assert!(!router_call("execute_scheduled", 1).is_ok());
+    router_call("remove_expired", 1).assert_success();
+    assert_eq!(wnear_balance(), balance_after_schedule + attached_near);
+}
+
+struct XccSimContext {
+    aurora: AuroraAccount,
+    chain_id: u64,
+    signer: test_utils::Signer,
+    signer_address: Address,
+    wnear_account: UserAccount,
+    wnear_erc20: ERC20,
+}
+
+/// Deploys the engine with the router code, and bridges wNEAR to it. The signer is given
+/// `WNEAR_AMOUNT` wNEAR on Aurora.
+fn xcc_sim_setup() -> XccSimContext {
+    let aurora = deploy_evm();
+    let chain_id = AuroraRunner::default().chain_id;
+    let xcc_wasm_bytes = contract_bytes();
+    aurora
+        .user
+        .call(
+            aurora.contract.account_id(),
+            "factory_update",
+            &xcc_wasm_bytes,
+            near_sdk_sim::DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    let signer = test_utils::Signer::random();
+    let signer_address = test_utils::address_from_secret_key(&signer.secret_key);
+
+    // Setup wNEAR contract and bridge it to Aurora
+    let wnear_account = deploy_wnear(&aurora);
+    let wnear_erc20 = sim_tests::deploy_erc20_from_nep_141(&wnear_account, &aurora);
+    sim_tests::transfer_nep_141_to_erc_20(
+        &wnear_account,
+        &wnear_erc20,
+        &aurora.user,
+        signer_address,
+        WNEAR_AMOUNT,
+        &aurora,
+    );
+    aurora
+        .user
+        .call(
+            aurora.contract.account_id(),
+            "factory_set_wnear_address",
+            wnear_erc20.0.address.as_bytes(),
+            near_sdk_sim::DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    XccSimContext {
+        aurora,
+        chain_id,
+        signer,
+        signer_address,
+        wnear_account,
+        wnear_erc20,
+    }
+}
+
+fn submit_sim_transaction(
+    aurora: &AuroraAccount,
+    chain_id: u64,
+    signer: &test_utils::Signer,
+    transaction: TransactionLegacy,
+) -> SubmitResult {
+    let signed_transaction =
+        test_utils::sign_transaction(transaction, Some(chain_id), &signer.secret_key);
+    let result = aurora.call("submit", &rlp::encode(&signed_transaction));
+    result.assert_success();
+    result.unwrap_borsh()
+}
+
+fn get_storage_at(aurora: &AuroraAccount, address: Address, slot: u64) -> H256 {
+    let args = GetStorageAtArgs {
+        address,
+        key: H256::from_low_u64_be(slot).0,
+    };
+    let result = aurora.call("get_storage_at", &args.try_to_vec().unwrap());
+    result.assert_success();
+    H256(result.unwrap_borsh())
+}
+
 fn get_engine_near_balance(aurora: &AuroraAccount) -> u128 {
     aurora
         .user
@@ -4,10 +4,11 @@ use crate::*;
 use borsh::{BorshDeserialize, BorshSerialize};
 
 #[must_use]
-#[derive(Debug, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
 pub enum PromiseArgs {
     Create(PromiseCreateArgs),
     Callback(PromiseWithCallbackArgs),
+    Recursive(NearPromise),
 }
 
 impl PromiseArgs {
@@ -15,6 +16,7 @@ impl PromiseArgs {
         match self {
             Self::Create(call) => call.attached_gas,
             Self::Callback(cb) => cb.base.attached_gas + cb.callback.attached_gas,
+            Self::Recursive(p) => p.total_gas(),
         }
     }
 
@@ -22,6 +24,112 @@ impl PromiseArgs {
         match self {
             Self::Create(call) => call.attached_balance,
             Self::Callback(cb) => cb.base.attached_balance + cb.callback.attached_balance,
+            Self::Recursive(p) => p.total_near(),
+        }
+    }
+
+    /// Returns true if the promise deletes the given account, adds a full access key to it or
+    /// replaces its code.
+    pub fn takes_over_account(&self, account_id: &AccountId) -> bool {
+        match self {
+            Self::Create(_) | Self::Callback(_) => false,
+            Self::Recursive(p) => p.takes_over_account(account_id),
+        }
+    }
+}
+
+/// A single NEAR promise: a function call or a batch of actions on one account.
+#[must_use]
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub enum SimpleNearPromise {
+    Create(PromiseCreateArgs),
+    Batch(PromiseBatchAction),
+}
+
+impl SimpleNearPromise {
+    pub fn total_gas(&self) -> NearGas {
+        match self {
+            Self::Create(call) => call.attached_gas,
+            Self::Batch(batch) => {
+                batch
+                    .actions
+                    .iter()
+                    .fold(NearGas::new(0), |acc, action| match action {
+                        PromiseAction::FunctionCall { gas, .. } => acc + *gas,
+                        _ => acc,
+                    })
+            }
+        }
+    }
+
+    pub fn total_near(&self) -> Yocto {
+        match self {
+            Self::Create(call) => call.attached_balance,
+            Self::Batch(batch) => {
+                batch
+                    .actions
+                    .iter()
+                    .fold(ZERO_YOCTO, |acc, action| match action {
+                        PromiseAction::Transfer { amount }
+                        | PromiseAction::Stake { amount, .. } => acc + *amount,
+                        PromiseAction::FunctionCall { attached_yocto, .. } => acc + *attached_yocto,
+                        _ => acc,
+                    })
+            }
+        }
+    }
+
+    pub fn takes_over_account(&self, account_id: &AccountId) -> bool {
+        match self {
+            Self::Create(_) => false,
+            Self::Batch(batch) => {
+                &batch.target_account_id == account_id && batch.takes_over_target()
+            }
+        }
+    }
+}
+
+/// A chain of NEAR promises, built with the same combinators as the NEAR promise API.
+#[must_use]
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub enum NearPromise {
+    Simple(SimpleNearPromise),
+    /// `callback` is executed once `base` has resolved, and receives its results.
+    Then {
+        base: Box<NearPromise>,
+        callback: SimpleNearPromise,
+    },
+    /// All the promises are executed in parallel; a `Then` on this promise receives all their
+    /// results.
+    And(Vec<NearPromise>),
+}
+
+impl NearPromise {
+    pub fn total_gas(&self) -> NearGas {
+        match self {
+            Self::Simple(p) => p.total_gas(),
+            Self::Then { base, callback } => base.total_gas() + callback.total_gas(),
+            Self::And(ps) => ps
+                .iter()
+                .fold(NearGas::new(0), |acc, p| acc + p.total_gas()),
+        }
+    }
+
+    pub fn total_near(&self) -> Yocto {
+        match self {
+            Self::Simple(p) => p.total_near(),
+            Self::Then { base, callback } => base.total_near() + callback.total_near(),
+            Self::And(ps) => ps.iter().fold(ZERO_YOCTO, |acc, p| acc + p.total_near()),
+        }
+    }
+
+    pub fn takes_over_account(&self, account_id: &AccountId) -> bool {
+        match self {
+            Self::Simple(p) => p.takes_over_account(account_id),
+            Self::Then { base, callback } => {
+                base.takes_over_account(account_id) || callback.takes_over_account(account_id)
+            }
+            Self::And(ps) => ps.iter().any(|p| p.takes_over_account(account_id)),
         }
     }
 }
@@ -43,7 +151,7 @@ pub struct PromiseWithCallbackArgs {
     pub callback: PromiseCreateArgs,
 }
 
-#[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
 pub enum PromiseAction {
     CreateAccount,
     Transfer {
@@ -58,15 +166,71 @@ pub enum PromiseAction {
         attached_yocto: Yocto,
         gas: NearGas,
     },
+    Stake {
+        amount: Yocto,
+        public_key: NearPublicKey,
+    },
+    AddFullAccessKey {
+        public_key: NearPublicKey,
+        nonce: u64,
+    },
+    /// A zero `allowance` means the allowance is unlimited, and an empty `function_names`
+    /// (a comma separated list) means any method of `receiver_id` can be called.
+    AddFunctionCallKey {
+        public_key: NearPublicKey,
+        nonce: u64,
+        allowance: Yocto,
+        receiver_id: AccountId,
+        function_names: String,
+    },
+    DeleteKey {
+        public_key: NearPublicKey,
+    },
+    DeleteAccount {
+        beneficiary_id: AccountId,
+    },
+}
+
+/// Public key of a NEAR access key. Its borsh serialization (the curve type followed by the
+/// key data) is the format the NEAR host functions expect.
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub enum NearPublicKey {
+    Ed25519([u8; 32]),
+    Secp256k1([u8; 64]),
+}
+
+impl NearPublicKey {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (curve_type, key): (u8, &[u8]) = match self {
+            Self::Ed25519(key) => (0, key),
+            Self::Secp256k1(key) => (1, key),
+        };
+        [&[curve_type], key].concat()
+    }
 }
 
 #[must_use]
-#[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
 pub struct PromiseBatchAction {
     pub target_account_id: AccountId,
     pub actions: Vec<PromiseAction>,
 }
 
+impl PromiseBatchAction {
+    /// Returns true if the batch deletes its target account, adds a full access key to it or
+    /// deploys a contract to it.
+    pub fn takes_over_target(&self) -> bool {
+        self.actions.iter().any(|action| {
+            matches!(
+                action,
+                PromiseAction::AddFullAccessKey { .. }
+                    | PromiseAction::DeleteAccount { .. }
+                    | PromiseAction::DeployConotract { .. }
+            )
+        })
+    }
+}
+
 /// withdraw NEAR eth-connector call args
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
 pub struct WithdrawCallArgs {
@@ -95,4 +259,74 @@ pub enum CrossContractCallArgs {
     /// much gas left to perform it. In this case, the promise could be `Delayed` (stored in the router)
     /// and executed in a separate transaction with a fresh 300 Tgas available for it.
     Delayed(PromiseArgs),
+    /// The promise is executed immediately, as in `Eager`, and its result is then delivered back
+    /// into the EVM by calling the contract which made the cross contract call.
+    /// See `EvmCallbackArgs` for details. The promise cannot be a `NearPromise::And`, since
+    /// only a single result can be delivered.
+    EagerWithCallback(PromiseArgs, EvmCallbackArgs),
+    /// The promise is stored in the router, as in `Delayed`, but can only be executed up to
+    /// (and including) the given NEAR block height. Once it has expired anyone can remove it
+    /// from the router, which refunds the NEAR attached to it.
+    DelayedWithExpiry(PromiseArgs, u64),
+    /// Removes the promise scheduled at the given nonce from the router. The NEAR attached to it
+    /// is refunded as wNEAR to the address which scheduled it.
+    Cancel(u64),
+    /// The NEAR needed by the call is paid for with the wNEAR of the owner of the permit,
+    /// instead of the wNEAR the caller approved the precompile to spend.
+    /// The wrapped args cannot themselves be `WithPermit`.
+    WithPermit(XccPermit, Box<CrossContractCallArgs>),
+}
+
+/// An EIP-2612 style permit, allowing the cross contract call precompile to take up to `value`
+/// wNEAR (ERC-20) from the balance of `owner` without a prior ERC-20 approval.
+/// The signature is the EIP-712 signature by `owner` of
+/// `CrossContractCallPermit(address owner,address caller,uint256 value,uint256 nonce,uint256 deadline,bytes32 callHash)`,
+/// where the caller is the address calling the precompile (so its router is used), `callHash`
+/// is the keccak hash of the borsh-serialized wrapped `CrossContractCallArgs`, and the nonce is
+/// the number of permits of `owner` used so far by successful transactions.
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct XccPermit {
+    pub owner: Address,
+    pub value: RawU256,
+    /// Unix timestamp (in seconds) after which the permit can no longer be used.
+    pub deadline: u64,
+    pub v: u8,
+    pub r: RawH256,
+    pub s: RawH256,
+}
+
+/// Describes how the result of a cross contract call is delivered back to the EVM contract which
+/// made it. Once the promise resolves, the router contract calls `xcc_callback` on the engine,
+/// which calls the function with the given selector on the contract with the ABI encoding of
+/// `(bytes)`, where the bytes are the borsh serialized `PromiseResult` of the call.
+/// The callback is made with the cross contract call precompile address as `msg.sender`.
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct EvmCallbackArgs {
+    pub selector: [u8; 4],
+    /// NEAR gas attached to the `xcc_callback` call to the engine.
+    pub gas: NearGas,
+}
+
+/// Args passed to the `schedule_with_expiry` method of the router contract.
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct ScheduleWithExpiryArgs {
+    pub promise: PromiseArgs,
+    pub expiry_block_height: u64,
+}
+
+/// Args passed to the `execute_with_callback` method of the router contract.
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct ExecuteWithCallbackArgs {
+    pub promise: PromiseArgs,
+    pub callback: EvmCallbackArgs,
+}
+
+/// Metadata of the NEP-141 token a bridged ERC-20 token was deployed for, cached by the engine
+/// from the `ft_metadata` of the NEP-141 token.
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct Erc20Metadata {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub icon: Option<String>,
 }
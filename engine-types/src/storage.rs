@@ -30,6 +30,8 @@ pub enum KeyPrefix {
     Nep141Erc20Map = 0x8,
     Erc20Nep141Map = 0x9,
     CrossContractCall = 0xa,
+    EthAllowance = 0xb,
+    Erc20Metadata = 0xc,
 }
 
 impl From<KeyPrefix> for u8 {
@@ -47,6 +49,8 @@ impl From<KeyPrefix> for u8 {
             Nep141Erc20Map => 0x8,
             Erc20Nep141Map => 0x9,
             CrossContractCall => 0xa,
+            EthAllowance => 0xb,
+            Erc20Metadata => 0xc,
         }
     }
 }
@@ -25,6 +25,11 @@ pub type StorageUsage = u64;
 #[allow(dead_code)]
 pub const ERC20_MINT_SELECTOR: &[u8] = &[64, 193, 15, 25];
 
+/// Selector to call setMetadata function in ERC 20 contract
+///
+/// keccak("setMetadata(string,string,uint8)".as_bytes())[..4];
+pub const ERC20_SET_METADATA_SELECTOR: &[u8] = &[55, 210, 194, 244];
+
 #[derive(Debug)]
 pub enum AddressValidationError {
     FailedDecodeHex,
@@ -3,7 +3,7 @@ use crate::deposit_event::{DepositedEvent, FtTransferMessageData, TokenMessageDa
 use crate::engine::Engine;
 use crate::fungible_token::{self, FungibleToken, FungibleTokenMetadata, FungibleTokenOps};
 use crate::parameters::{
-    BalanceOfCallArgs, BalanceOfEthCallArgs, FinishDepositCallArgs, InitCallArgs,
+    BalanceOfCallArgs, BalanceOfEthCallArgs, FinishDepositCallArgs, Genesis, InitCallArgs,
     NEP141FtOnTransferArgs, PauseEthConnectorCallArgs, ResolveTransferCallArgs,
     SetContractDataCallArgs, StorageBalanceOfCallArgs, StorageDepositCallArgs,
     StorageWithdrawCallArgs, TransferCallArgs, TransferCallCallArgs, WithdrawResult,
@@ -99,7 +99,10 @@ impl<I: IO + Copy> EthConnectorContract<I> {
         )
         .map_err(error::InitContractError::InvalidCustodianAddress)?;
 
-        let mut ft = FungibleTokenOps::new(io);
+        // Keep the ETH supply credited to the genesis accounts, if any.
+        let mut ft =
+            get_contract_data::<FungibleToken, I>(&io, &EthConnectorStorageId::FungibleToken)
+                .map_or_else(|_| FungibleTokenOps::new(io), |ft| ft.ops(io));
         // Register FT account for current contract
         ft.internal_register_account(&owner_id);
 
@@ -120,6 +123,34 @@ impl<I: IO + Copy> EthConnectorContract<I> {
         Ok(())
     }
 
+    /// Credits the balances of the genesis accounts, which are part of the ETH supply on Aurora.
+    /// The same amount is minted on NEAR to the engine account, which holds the ETH bridged to
+    /// Aurora, so that the genesis balances can be withdrawn like deposited ones.
+    /// Used only once, on the initialization of the engine.
+    pub fn deposit_genesis_balances(
+        mut io: I,
+        current_account_id: &AccountId,
+        genesis: &Genesis,
+    ) -> Result<(), fungible_token::error::DepositError> {
+        let mut ft =
+            get_contract_data::<FungibleToken, I>(&io, &EthConnectorStorageId::FungibleToken)
+                .map_or_else(|_| FungibleTokenOps::new(io), |ft| ft.ops(io));
+        for account in genesis.accounts.iter() {
+            let amount = Wei::from(account.balance);
+            let nep141_amount = amount
+                .try_into_u128()
+                .map(NEP141Wei::new)
+                .map_err(|_| fungible_token::error::DepositError::BalanceOverflow)?;
+            ft.internal_deposit_eth_to_near(current_account_id, nep141_amount)?;
+            ft.internal_deposit_eth_to_aurora(account.address, amount)?;
+        }
+        io.write_borsh(
+            &construct_contract_key(&EthConnectorStorageId::FungibleToken),
+            &ft.data(),
+        );
+        Ok(())
+    }
+
     /// Deposit all types of tokens
     pub fn deposit(
         &self,
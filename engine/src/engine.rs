@@ -6,6 +6,7 @@ use evm::{Config, CreateScheme, ExitError, ExitFatal, ExitReason};
 
 use crate::connector::EthConnectorContract;
 use crate::errors;
+use crate::fungible_token;
 use crate::map::BijectionMap;
 use aurora_engine_sdk::caching::FullCache;
 use aurora_engine_sdk::env::Env;
@@ -13,16 +14,23 @@ use aurora_engine_sdk::io::{StorageIntermediate, IO};
 use aurora_engine_sdk::promise::{PromiseHandler, PromiseId, ReadOnlyPromiseHandler};
 
 use crate::accounting;
-use crate::parameters::{DeployErc20TokenArgs, NewCallArgs, TransactionStatus};
+use crate::parameters::{
+    BlockGasLimit, Coinbase, DeployErc20TokenArgs, Genesis, NewCallArgs, SetCoinbaseArgs,
+    SetPrecompileStatusArgs, TransactionStatus,
+};
 use crate::prelude::parameters::RefundCallArgs;
+use crate::prelude::precompiles::extensions::PrecompileExtensions;
 use crate::prelude::precompiles::native::{exit_to_ethereum, exit_to_near};
+use crate::prelude::precompiles::registry::{self, PrecompileRegistry, RegistryError};
+use crate::prelude::precompiles::wrapped_eth;
 use crate::prelude::precompiles::xcc::cross_contract_call;
 use crate::prelude::precompiles::Precompiles;
 use crate::prelude::transactions::{EthTransactionKind, NormalizedEthTransaction};
 use crate::prelude::{
     address_to_key, bytes_to_key, sdk, storage_to_key, u256_to_arr, vec, AccountId, Address,
-    BTreeMap, BorshDeserialize, BorshSerialize, KeyPrefix, PromiseArgs, PromiseCreateArgs,
-    ToString, Vec, Wei, Yocto, ERC20_MINT_SELECTOR, H160, H256, U256,
+    BTreeMap, BorshDeserialize, BorshSerialize, Cow, Erc20Metadata, KeyPrefix, NearGas,
+    PromiseArgs, PromiseCreateArgs, PromiseResult, PromiseWithCallbackArgs, ToString, Vec, Wei,
+    Yocto, ERC20_MINT_SELECTOR, ERC20_SET_METADATA_SELECTOR, H160, H256, U256, ZERO_YOCTO,
 };
 use aurora_engine_precompiles::PrecompileConstructorContext;
 use core::cell::RefCell;
@@ -34,12 +42,55 @@ const BLOCK_HASH_PREFIX_SIZE: usize = 1;
 const BLOCK_HEIGHT_SIZE: usize = 8;
 const CHAIN_ID_SIZE: usize = 32;
 
+/// The mocked coinbase address, being the EVM address for the Aurora account.
+pub const DEFAULT_COINBASE: H160 = H160([
+    0x44, 0x44, 0x58, 0x84, 0x43, 0xC3, 0xa9, 0x12, 0x88, 0xc5, 0x00, 0x24, 0x83, 0x44, 0x9A, 0xba,
+    0x10, 0x54, 0x19, 0x2b,
+]);
+
 #[cfg(not(feature = "contract"))]
 /// Block height where the bug fix for parsing transactions to the zero address
 /// is deployed. The current value is only approximate; will be updated once the
 /// fix is actually deployed.
 pub const ZERO_ADDRESS_FIX_HEIGHT: u64 = 61200152;
 
+/// Block height from which the precompiles of the Cancun hard fork are used, i.e. the KZG point
+/// evaluation precompile of EIP-4844. The London precompiles are used below it. The current value
+/// is only approximate; will be updated once the change is actually deployed.
+pub const CANCUN_PRECOMPILES_HEIGHT: u64 = 180_000_000;
+
+/// Block height from which the precompiles of the Prague hard fork are used, i.e. the BLS12-381
+/// precompiles of EIP-2537, together with the `P256Verify` and `Ed25519Verify` precompiles.
+/// The current value is only approximate; will be updated once the change is actually deployed.
+/// The Prague precompiles include the Cancun ones, so it must not be below
+/// `CANCUN_PRECOMPILES_HEIGHT`.
+pub const PRAGUE_PRECOMPILES_HEIGHT: u64 = 190_000_000;
+const _: () = assert!(PRAGUE_PRECOMPILES_HEIGHT >= CANCUN_PRECOMPILES_HEIGHT);
+
+/// Block height from which the precompiles of the Osaka hard fork are used, i.e. the `ModExp`
+/// input limits of EIP-7823 and the pricing of EIP-7883. The current value is only approximate;
+/// will be updated once the change is actually deployed. The Osaka precompiles include the Prague
+/// ones, so it must not be below `PRAGUE_PRECOMPILES_HEIGHT`.
+pub const OSAKA_PRECOMPILES_HEIGHT: u64 = 200_000_000;
+const _: () = assert!(OSAKA_PRECOMPILES_HEIGHT >= PRAGUE_PRECOMPILES_HEIGHT);
+
+/// Block height from which the Aurora precompiles giving access to the block context, the address
+/// resolver, the per-transaction random seed and wrapped ETH are installed, together with the
+/// code the wrapped ETH precompile runs to access the EVM state. The current value is only
+/// approximate; will be updated once the change is actually deployed.
+pub const EXTENDED_PRECOMPILES_HEIGHT: u64 = 185_000_000;
+
+/// NEAR gas attached to the `ft_metadata` call made to the NEP-141 token of a new bridged token.
+pub const FT_METADATA_GAS: NearGas = NearGas::new(5_000_000_000_000);
+/// NEAR gas attached to the `set_erc20_metadata` callback, which calls the ERC-20 token.
+pub const SET_ERC20_METADATA_GAS: NearGas = NearGas::new(30_000_000_000_000);
+/// Longest name and symbol of a NEP-141 token which are copied to its ERC-20 token, in bytes.
+/// Tokens with longer ones keep the placeholder metadata.
+pub const MAX_ERC20_NAME_LEN: usize = 256;
+pub const MAX_ERC20_SYMBOL_LEN: usize = 64;
+/// Longest icon of a NEP-141 token which is cached, in bytes. Longer icons are not cached.
+pub const MAX_ERC20_ICON_LEN: usize = 4_096;
+
 pub fn current_address(current_account_id: &AccountId) -> Address {
     aurora_engine_sdk::types::near_account_to_evm_address(current_account_id.as_bytes())
 }
@@ -103,6 +154,8 @@ pub enum EngineErrorKind {
     MaxPriorityGasFeeTooLarge,
     GasPayment(GasPaymentError),
     GasOverflow,
+    /// The transaction gas limit exceeds the gas remaining in the current block.
+    BlockGasLimitExceeded,
 }
 
 impl EngineErrorKind {
@@ -141,6 +194,7 @@ impl EngineErrorKind {
             MaxPriorityGasFeeTooLarge => errors::ERR_MAX_PRIORITY_FEE_GREATER,
             GasPayment(e) => e.as_ref(),
             GasOverflow => errors::ERR_GAS_OVERFLOW,
+            BlockGasLimitExceeded => errors::ERR_BLOCK_GAS_LIMIT_EXCEEDED,
         }
     }
 }
@@ -354,35 +408,61 @@ struct StackExecutorParams<'a, I, E, H> {
 }
 
 impl<'env, I: IO + Copy, E: Env, H: ReadOnlyPromiseHandler> StackExecutorParams<'env, I, E, H> {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         gas_limit: u64,
         current_account_id: AccountId,
+        chain_id: U256,
         random_seed: H256,
+        transaction_hash: H256,
         io: I,
         env: &'env E,
         ro_promise_handler: H,
+        precompile_extensions: Option<&'env PrecompileExtensions>,
     ) -> Self {
+        let new_precompiles: fn(PrecompileConstructorContext<'env, I, E, H>) -> _ =
+            if env.block_height() >= OSAKA_PRECOMPILES_HEIGHT {
+                Precompiles::new_osaka
+            } else if env.block_height() >= PRAGUE_PRECOMPILES_HEIGHT {
+                Precompiles::new_prague
+            } else if env.block_height() >= CANCUN_PRECOMPILES_HEIGHT {
+                Precompiles::new_cancun
+            } else {
+                Precompiles::new_london
+            };
         let precompiles = if cfg!(all(feature = "mainnet", not(feature = "integration-test"))) {
-            let mut tmp = Precompiles::new_london(PrecompileConstructorContext {
+            let mut tmp = new_precompiles(PrecompileConstructorContext {
                 current_account_id,
+                chain_id,
                 random_seed,
+                transaction_hash,
                 io,
                 env,
                 promise_handler: ro_promise_handler,
+                registry: get_precompile_registry(&io),
+                extended: env.block_height() >= EXTENDED_PRECOMPILES_HEIGHT,
             });
             // Cross contract calls are not enabled on mainnet yet.
             tmp.all_precompiles
                 .remove(&aurora_engine_precompiles::xcc::cross_contract_call::ADDRESS);
             tmp
         } else {
-            Precompiles::new_london(PrecompileConstructorContext {
+            new_precompiles(PrecompileConstructorContext {
                 current_account_id,
+                chain_id,
                 random_seed,
+                transaction_hash,
                 io,
                 env,
                 promise_handler: ro_promise_handler,
+                registry: get_precompile_registry(&io),
+                extended: env.block_height() >= EXTENDED_PRECOMPILES_HEIGHT,
             })
         };
+        let precompiles = match precompile_extensions {
+            Some(extensions) => precompiles.with_extensions(extensions),
+            None => precompiles,
+        };
 
         Self {
             precompiles,
@@ -414,7 +494,7 @@ pub struct GasPaymentResult {
 
 /// Engine internal state, mostly configuration.
 /// Should not contain anything large or enumerable.
-#[derive(BorshSerialize, BorshDeserialize, Default, Clone)]
+#[derive(Default, Clone)]
 pub struct EngineState {
     /// Chain id, according to the EIP-155 / ethereum-lists spec.
     pub chain_id: [u8; 32],
@@ -426,6 +506,86 @@ pub struct EngineState {
     pub bridge_prover_id: AccountId,
     /// How many blocks after staging upgrade can deploy it.
     pub upgrade_delay_blocks: u64,
+    /// Block gas limit. Use `None` for an unlimited block gas limit.
+    pub block_gas_limit: Option<BlockGasLimit>,
+}
+
+/// Storage format of the `EngineState`. Changing the fields of the state means adding
+/// a new variant, so that the state written by previous versions of the contract can
+/// still be read.
+#[derive(BorshSerialize, BorshDeserialize)]
+enum BorshableEngineState<'a> {
+    V1(BorshableEngineStateV1<'a>),
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct BorshableEngineStateV1<'a> {
+    chain_id: [u8; 32],
+    owner_id: Cow<'a, AccountId>,
+    bridge_prover_id: Cow<'a, AccountId>,
+    upgrade_delay_blocks: u64,
+    block_gas_limit: Option<BlockGasLimit>,
+}
+
+/// Format of the state written under `LEGACY_STATE_KEY` before it was versioned.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct LegacyEngineState {
+    chain_id: [u8; 32],
+    owner_id: AccountId,
+    bridge_prover_id: AccountId,
+    upgrade_delay_blocks: u64,
+}
+
+impl BorshSerialize for EngineState {
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> borsh::maybestd::io::Result<()> {
+        BorshableEngineState::V1(BorshableEngineStateV1 {
+            chain_id: self.chain_id,
+            owner_id: Cow::Borrowed(&self.owner_id),
+            bridge_prover_id: Cow::Borrowed(&self.bridge_prover_id),
+            upgrade_delay_blocks: self.upgrade_delay_blocks,
+            block_gas_limit: self.block_gas_limit,
+        })
+        .serialize(writer)
+    }
+}
+
+impl BorshDeserialize for EngineState {
+    fn deserialize(buf: &mut &[u8]) -> borsh::maybestd::io::Result<Self> {
+        match BorshableEngineState::deserialize(buf)? {
+            BorshableEngineState::V1(state) => Ok(Self {
+                chain_id: state.chain_id,
+                owner_id: state.owner_id.into_owned(),
+                bridge_prover_id: state.bridge_prover_id.into_owned(),
+                upgrade_delay_blocks: state.upgrade_delay_blocks,
+                block_gas_limit: state.block_gas_limit,
+            }),
+        }
+    }
+}
+
+impl From<LegacyEngineState> for EngineState {
+    fn from(state: LegacyEngineState) -> Self {
+        Self {
+            chain_id: state.chain_id,
+            owner_id: state.owner_id,
+            bridge_prover_id: state.bridge_prover_id,
+            upgrade_delay_blocks: state.upgrade_delay_blocks,
+            block_gas_limit: None,
+        }
+    }
+}
+
+impl EngineState {
+    /// The value reported by the `GASLIMIT` opcode.
+    pub fn gas_limit(&self) -> U256 {
+        self.block_gas_limit
+            .map_or_else(U256::max_value, |block_gas_limit| {
+                U256::from(block_gas_limit.limit)
+            })
+    }
 }
 
 impl From<NewCallArgs> for EngineState {
@@ -435,6 +595,7 @@ impl From<NewCallArgs> for EngineState {
             owner_id: args.owner_id,
             bridge_prover_id: args.bridge_prover_id,
             upgrade_delay_blocks: args.upgrade_delay_blocks,
+            block_gas_limit: None,
         }
     }
 }
@@ -446,6 +607,11 @@ pub struct Engine<'env, I: IO, E: Env> {
     current_account_id: AccountId,
     io: I,
     env: &'env E,
+    precompile_extensions: Option<&'env PrecompileExtensions>,
+    /// Hash of the Ethereum transaction being executed, only set by `submit`.
+    transaction_hash: Option<H256>,
+    /// Coinbase in effect at the current block height.
+    coinbase: Coinbase,
     generation_cache: RefCell<BTreeMap<Address, u32>>,
     account_info_cache: RefCell<FullCache<Address, Basic>>,
     contract_code_cache: RefCell<FullCache<Address, Vec<u8>>>,
@@ -455,7 +621,17 @@ pub struct Engine<'env, I: IO, E: Env> {
 pub(crate) const CONFIG: &Config = &Config::london();
 
 /// Key for storing the state of the engine.
-const STATE_KEY: &[u8; 5] = b"STATE";
+const STATE_KEY: &[u8; 12] = b"ENGINE_STATE";
+/// Key under which the state was stored before its format was versioned.
+const LEGACY_STATE_KEY: &[u8; 5] = b"STATE";
+/// Key for storing the coinbase schedule.
+const COINBASE_KEY: &[u8; 8] = b"COINBASE";
+/// Key for storing the gas used so far in the current NEAR block.
+const BLOCK_GAS_USED_KEY: &[u8; 14] = b"BLOCK_GAS_USED";
+/// Key for storing the number of receipts without an Ethereum transaction in the NEAR block.
+const BLOCK_RECEIPT_COUNT_KEY: &[u8; 19] = b"BLOCK_RECEIPT_COUNT";
+/// Key for storing the precompile registry.
+const PRECOMPILE_REGISTRY_KEY: &[u8; 19] = b"PRECOMPILE_REGISTRY";
 
 impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
     pub fn new(
@@ -474,6 +650,7 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
         io: I,
         env: &'env E,
     ) -> Self {
+        let coinbase = get_coinbase(&io, env.block_height());
         Self {
             state,
             origin,
@@ -481,6 +658,9 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
             current_account_id,
             io,
             env,
+            precompile_extensions: None,
+            transaction_hash: None,
+            coinbase,
             generation_cache: RefCell::new(BTreeMap::new()),
             account_info_cache: RefCell::new(FullCache::default()),
             contract_code_cache: RefCell::new(FullCache::default()),
@@ -488,6 +668,55 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
         }
     }
 
+    /// Installs the given precompiles on top of the ones of the engine in all transactions
+    /// executed by this instance.
+    pub fn with_precompile_extensions(mut self, extensions: &'env PrecompileExtensions) -> Self {
+        self.precompile_extensions = Some(extensions);
+        self
+    }
+
+    /// Hash identifying the transaction being executed, mixed into the per-transaction random
+    /// seed. Receipts which do not carry an Ethereum transaction are identified by the hash of
+    /// their input, their predecessor, the NEAR block and random seed instead, together with
+    /// their index among such receipts of the block, which tells identical receipts apart.
+    fn transaction_hash(&self, input: &[u8]) -> H256 {
+        self.transaction_hash.unwrap_or_else(|| {
+            let mut io = self.io;
+            let block_height = self.env.block_height();
+            let receipt_index = get_block_counter(&io, BLOCK_RECEIPT_COUNT_KEY, block_height);
+            set_block_counter(
+                &mut io,
+                BLOCK_RECEIPT_COUNT_KEY,
+                block_height,
+                receipt_index.saturating_add(1),
+            );
+            self.receipt_hash(input, receipt_index)
+        })
+    }
+
+    /// Same as `transaction_hash`, for views. They cannot write the state, so they are not
+    /// counted among the receipts of the block.
+    fn view_transaction_hash(&self, input: &[u8]) -> H256 {
+        self.transaction_hash.unwrap_or_else(|| {
+            let block_height = self.env.block_height();
+            let receipt_index = get_block_counter(&self.io, BLOCK_RECEIPT_COUNT_KEY, block_height);
+            self.receipt_hash(input, receipt_index)
+        })
+    }
+
+    fn receipt_hash(&self, input: &[u8], receipt_index: u64) -> H256 {
+        let predecessor_account_id = self.env.predecessor_account_id();
+        let data = [
+            input,
+            predecessor_account_id.as_bytes(),
+            &self.env.block_height().to_le_bytes(),
+            self.env.random_seed().as_bytes(),
+            &receipt_index.to_le_bytes(),
+        ]
+        .concat();
+        sdk::keccak(&data)
+    }
+
     pub fn charge_gas(
         &mut self,
         sender: &Address,
@@ -544,10 +773,13 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
         let executor_params = StackExecutorParams::new(
             gas_limit,
             self.current_account_id.clone(),
+            U256::from(self.state.chain_id),
             self.env.random_seed(),
+            self.transaction_hash(&input),
             self.io,
             self.env,
             handler.read_only(),
+            self.precompile_extensions,
         );
         let mut executor = executor_params.make_executor(self);
         let address = executor.create_address(CreateScheme::Legacy {
@@ -631,10 +863,13 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
         let executor_params = StackExecutorParams::new(
             gas_limit,
             self.current_account_id.clone(),
+            U256::from(self.state.chain_id),
             self.env.random_seed(),
+            self.transaction_hash(&input),
             self.io,
             self.env,
             handler.read_only(),
+            self.precompile_extensions,
         );
         let mut executor = executor_params.make_executor(self);
         let (exit_reason, result) = executor.transact_call(
@@ -683,11 +918,14 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
         let executor_params = StackExecutorParams::new(
             gas_limit,
             self.current_account_id.clone(),
+            U256::from(self.state.chain_id),
             self.env.random_seed(),
+            self.view_transaction_hash(&input),
             self.io,
             self.env,
             // View calls cannot interact with promises
             aurora_engine_sdk::promise::Noop,
+            self.precompile_extensions,
         );
         let mut executor = executor_params.make_executor(self);
         let (status, result) = executor.transact_call(
@@ -857,9 +1095,9 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
                             crate::prelude::String::from_utf8_lossy(&bytes)
                         );
                         Err(EngineError {
-                            kind: EngineErrorKind::EvmError(ExitError::Other(
-                                crate::prelude::Cow::from(error_message),
-                            )),
+                            kind: EngineErrorKind::EvmError(ExitError::Other(Cow::from(
+                                error_message,
+                            ))),
                             gas_used: submit_result.gas_used,
                         })
                     }
@@ -892,13 +1130,37 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
 }
 
 pub fn submit<I: IO + Copy, E: Env, P: PromiseHandler>(
-    mut io: I,
+    io: I,
     env: &E,
     transaction_bytes: &[u8],
     state: EngineState,
     current_account_id: AccountId,
     relayer_address: Address,
     handler: &mut P,
+) -> EngineResult<SubmitResult> {
+    submit_with_precompile_extensions(
+        io,
+        env,
+        transaction_bytes,
+        state,
+        current_account_id,
+        relayer_address,
+        handler,
+        None,
+    )
+}
+
+/// Same as `submit`, but with the given precompiles installed on top of the ones of the engine.
+#[allow(clippy::too_many_arguments)]
+pub fn submit_with_precompile_extensions<'env, I: IO + Copy, E: Env, P: PromiseHandler>(
+    mut io: I,
+    env: &'env E,
+    transaction_bytes: &[u8],
+    state: EngineState,
+    current_account_id: AccountId,
+    relayer_address: Address,
+    handler: &mut P,
+    precompile_extensions: Option<&'env PrecompileExtensions>,
 ) -> EngineResult<SubmitResult> {
     #[cfg(feature = "contract")]
     let transaction = NormalizedEthTransaction::try_from(
@@ -954,7 +1216,21 @@ pub fn submit<I: IO + Copy, E: Env, P: PromiseHandler>(
         return Err(EngineErrorKind::MaxPriorityGasFeeTooLarge.into());
     }
 
+    let enforced_block_gas_limit = state
+        .block_gas_limit
+        .filter(|block_gas_limit| block_gas_limit.enforce)
+        .map(|block_gas_limit| block_gas_limit.limit);
+    if let Some(block_gas_limit) = enforced_block_gas_limit {
+        let block_gas_used = get_block_gas_used(&io, env.block_height());
+        let remaining_gas = block_gas_limit.saturating_sub(block_gas_used);
+        if transaction.gas_limit > U256::from(remaining_gas) {
+            return Err(EngineErrorKind::BlockGasLimitExceeded.into());
+        }
+    }
+
     let mut engine = Engine::new_with_state(state, sender, current_account_id, io, env);
+    engine.precompile_extensions = precompile_extensions;
+    engine.transaction_hash = Some(sdk::keccak(transaction_bytes));
     let prepaid_amount = match engine.charge_gas(&sender, &transaction) {
         Ok(gas_result) => gas_result,
         Err(GasPaymentError::OutOfFund) => {
@@ -1011,10 +1287,30 @@ pub fn submit<I: IO + Copy, E: Env, P: PromiseHandler>(
         },
     )?;
 
+    if enforced_block_gas_limit.is_some() {
+        add_block_gas_used(&mut io, env.block_height(), gas_used);
+    }
+
     // return result to user
     result
 }
 
+/// Returns the amount of an `ft_transfer_call` made by the exit to NEAR precompile which was
+/// not used by the receiver, given the result of the call (the used amount as a JSON string).
+/// The unused tokens are returned to the engine account, so they must be refunded in the EVM.
+/// Returns `None` if everything was used, or if the result is not that of an `ft_transfer_call`.
+pub fn ft_transfer_call_unused_amount(result: &[u8], amount: U256) -> Option<U256> {
+    let used_amount: u128 = core::str::from_utf8(result)
+        .ok()?
+        .strip_prefix('"')?
+        .strip_suffix('"')?
+        .parse()
+        .ok()?;
+    amount
+        .checked_sub(U256::from(used_amount))
+        .filter(|unused_amount| !unused_amount.is_zero())
+}
+
 pub fn refund_on_error<I: IO + Copy, E: Env, P: PromiseHandler>(
     io: I,
     env: &E,
@@ -1100,18 +1396,192 @@ pub fn compute_block_hash(chain_id: [u8; 32], block_height: u64, account_id: &[u
 
 pub fn get_state<I: IO>(io: &I) -> Result<EngineState, EngineStateError> {
     match io.read_storage(&bytes_to_key(KeyPrefix::Config, STATE_KEY)) {
-        None => Err(EngineStateError::NotFound),
         Some(bytes) => EngineState::try_from_slice(&bytes.to_vec())
             .map_err(|_| EngineStateError::DeserializationFailed),
+        // Until `state_migration` runs the state is in the legacy format.
+        None => get_legacy_state(io)?.ok_or(EngineStateError::NotFound),
+    }
+}
+
+fn get_legacy_state<I: IO>(io: &I) -> Result<Option<EngineState>, EngineStateError> {
+    io.read_storage(&bytes_to_key(KeyPrefix::Config, LEGACY_STATE_KEY))
+        .map(|bytes| {
+            LegacyEngineState::try_from_slice(&bytes.to_vec())
+                .map(Into::into)
+                .map_err(|_| EngineStateError::DeserializationFailed)
+        })
+        .transpose()
+}
+
+/// Moves the state written in the legacy format to the versioned one. If the versioned state
+/// already exists it is newer than the legacy one, which is then only removed.
+pub fn migrate_legacy_state<I: IO>(io: &mut I) -> Result<(), EngineStateError> {
+    if io.storage_has_key(&bytes_to_key(KeyPrefix::Config, STATE_KEY)) {
+        io.remove_storage(&bytes_to_key(KeyPrefix::Config, LEGACY_STATE_KEY));
+    } else if let Some(state) = get_legacy_state(io)? {
+        set_state(io, state);
+    }
+    Ok(())
+}
+
+/// Returns the gas used by the transactions submitted so far in the NEAR block at `block_height`.
+pub fn get_block_gas_used<I: IO>(io: &I, block_height: u64) -> u64 {
+    get_block_counter(io, BLOCK_GAS_USED_KEY, block_height)
+}
+
+fn add_block_gas_used<I: IO>(io: &mut I, block_height: u64, gas_used: u64) {
+    let total = get_block_gas_used(io, block_height).saturating_add(gas_used);
+    set_block_counter(io, BLOCK_GAS_USED_KEY, block_height, total);
+}
+
+/// Reads a counter of the NEAR block at `block_height`, stored together with that height.
+fn get_block_counter<I: IO>(io: &I, key: &[u8], block_height: u64) -> u64 {
+    io.read_storage(&bytes_to_key(KeyPrefix::Config, key))
+        .and_then(|value| {
+            if value.len() != 16 {
+                return None;
+            }
+            let mut buf = [0u8; 16];
+            value.copy_to_slice(&mut buf);
+            let mut height = [0u8; 8];
+            height.copy_from_slice(&buf[0..8]);
+            // The counter is implicitly reset by the first transaction of each block.
+            if u64::from_le_bytes(height) != block_height {
+                return None;
+            }
+            let mut count = [0u8; 8];
+            count.copy_from_slice(&buf[8..16]);
+            Some(u64::from_le_bytes(count))
+        })
+        .unwrap_or(0)
+}
+
+fn set_block_counter<I: IO>(io: &mut I, key: &[u8], block_height: u64, count: u64) {
+    let mut buf = [0u8; 16];
+    buf[0..8].copy_from_slice(&block_height.to_le_bytes());
+    buf[8..16].copy_from_slice(&count.to_le_bytes());
+    io.write_storage(&bytes_to_key(KeyPrefix::Config, key), &buf);
+}
+
+/// The coinbase configured by the owner, with the one it replaces until it activates.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoinbaseSchedule {
+    pub previous: Coinbase,
+    pub next: Coinbase,
+    pub activation_height: u64,
+}
+
+impl CoinbaseSchedule {
+    pub fn coinbase_at(&self, block_height: u64) -> Coinbase {
+        if block_height < self.activation_height {
+            self.previous
+        } else {
+            self.next
+        }
     }
 }
 
+pub fn get_coinbase_schedule<I: IO>(io: &I) -> Option<CoinbaseSchedule> {
+    io.read_storage(&bytes_to_key(KeyPrefix::Config, COINBASE_KEY))
+        .and_then(|bytes| CoinbaseSchedule::try_from_slice(&bytes.to_vec()).ok())
+}
+
+/// Returns the coinbase in effect at the given height.
+pub fn get_coinbase<I: IO>(io: &I, block_height: u64) -> Coinbase {
+    get_coinbase_schedule(io).map_or(Coinbase::Default, |schedule| {
+        schedule.coinbase_at(block_height)
+    })
+}
+
+/// Returns the address reported by the `COINBASE` opcode for a transaction of the given relayer.
+pub fn get_coinbase_address<I: IO>(
+    io: &I,
+    coinbase: Coinbase,
+    relayer_account_id: &AccountId,
+) -> H160 {
+    match coinbase {
+        Coinbase::Default => DEFAULT_COINBASE,
+        Coinbase::Fixed(address) => address.raw(),
+        Coinbase::Relayer => io
+            .read_storage(&bytes_to_key(
+                KeyPrefix::RelayerEvmAddressMap,
+                relayer_account_id.as_bytes(),
+            ))
+            .and_then(|value| Address::try_from_slice(&value.to_vec()).ok())
+            .unwrap_or_else(|| {
+                aurora_engine_sdk::types::near_account_to_evm_address(relayer_account_id.as_bytes())
+            })
+            .raw(),
+    }
+}
+
+/// Schedules a change of the coinbase. The coinbase in effect at `block_height` (the current height)
+/// remains in use until `args.activation_height`.
+pub fn set_coinbase<I: IO>(io: &mut I, block_height: u64, args: &SetCoinbaseArgs) {
+    let schedule = CoinbaseSchedule {
+        previous: get_coinbase(io, block_height),
+        next: args.coinbase,
+        activation_height: args.activation_height,
+    };
+    io.write_storage(
+        &bytes_to_key(KeyPrefix::Config, COINBASE_KEY),
+        &schedule.try_to_vec().expect("ERR_SER"),
+    );
+}
+
+pub fn get_precompile_registry<I: IO>(io: &I) -> PrecompileRegistry {
+    io.read_storage(&bytes_to_key(KeyPrefix::Config, PRECOMPILE_REGISTRY_KEY))
+        .map(|bytes| {
+            PrecompileRegistry::try_from_slice(&bytes.to_vec())
+                .expect(registry::error::ERR_CORRUPTED_STORAGE)
+        })
+        .unwrap_or_default()
+}
+
+pub fn set_precompile_status<I: IO>(
+    io: &mut I,
+    args: &SetPrecompileStatusArgs,
+) -> Result<(), RegistryError> {
+    let mut registry = get_precompile_registry(io);
+    registry.set_status(args.address, args.status)?;
+    io.write_storage(
+        &bytes_to_key(KeyPrefix::Config, PRECOMPILE_REGISTRY_KEY),
+        &registry.try_to_vec().expect("ERR_SER"),
+    );
+    Ok(())
+}
+
+/// Installs the balances, nonces, code and storage of the accounts in `genesis`.
+/// The balances are credited to the ETH supply on Aurora and backed by the balance of
+/// `current_account_id` on NEAR, so this must only run on a new state.
+pub fn install_genesis<I: IO + Copy>(
+    io: &mut I,
+    current_account_id: &AccountId,
+    genesis: &Genesis,
+) -> Result<(), fungible_token::error::DepositError> {
+    EthConnectorContract::deposit_genesis_balances(*io, current_account_id, genesis)?;
+    for account in genesis.accounts.iter() {
+        let address = &account.address;
+        set_nonce(io, address, &U256::from_big_endian(&account.nonce));
+        if !account.code.is_empty() {
+            set_code(io, address, &account.code);
+        }
+        let generation = get_generation(io, address);
+        for (key, value) in account.storage.iter() {
+            set_storage(io, address, &H256(*key), &H256(*value), generation);
+        }
+    }
+    Ok(())
+}
+
 /// Saves state into the storage.
 pub fn set_state<I: IO>(io: &mut I, state: EngineState) {
     io.write_storage(
         &bytes_to_key(KeyPrefix::Config, STATE_KEY),
         &state.try_to_vec().expect("ERR_SER"),
     );
+    // The legacy state would be stale from now on.
+    io.remove_storage(&bytes_to_key(KeyPrefix::Config, LEGACY_STATE_KEY));
 }
 
 pub fn refund_unused_gas<I: IO>(
@@ -1147,6 +1617,9 @@ pub fn refund_unused_gas<I: IO>(
 }
 
 /// Used to bridge NEP-141 tokens from NEAR to Aurora. On Aurora the NEP-141 becomes an ERC-20.
+///
+/// The metadata of the NEP-141 token is then fetched by a promise, which needs
+/// `FT_METADATA_GAS + SET_ERC20_METADATA_GAS` (35 Tgas) on top of the gas used by the deployment.
 pub fn deploy_erc20_token<I: IO + Copy, E: Env, P: PromiseHandler>(
     args: DeployErc20TokenArgs,
     io: I,
@@ -1193,12 +1666,124 @@ pub fn deploy_erc20_token<I: IO + Copy, E: Env, P: PromiseHandler>(
 
     sdk::log!(crate::prelude::format!("Deployed ERC-20 in Aurora at: {:#?}", address).as_str());
     engine
-        .register_token(address, args.nep141)
+        .register_token(address, args.nep141.clone())
         .map_err(DeployErc20Error::Register)?;
 
+    // The ERC-20 token is deployed with placeholder metadata; the one of the NEP-141 token is
+    // copied to it by the `set_erc20_metadata` callback.
+    let promise = erc20_metadata_promise(args.nep141, address, env.current_account_id());
+    handler.promise_create_with_callback(&promise);
+
     Ok(address)
 }
 
+/// Fetches the metadata of a bridged NEP-141 token again, to copy it to its ERC-20 token once
+/// it has changed. Like `deploy_erc20_token`, the promise needs
+/// `FT_METADATA_GAS + SET_ERC20_METADATA_GAS` (35 Tgas).
+pub fn refresh_erc20_metadata<I: IO, E: Env, P: PromiseHandler>(
+    nep141: &AccountId,
+    io: &I,
+    env: &E,
+    handler: &mut P,
+) -> Result<Address, GetErc20FromNep141Error> {
+    let erc20_token = Address::try_from_slice(&get_erc20_from_nep141(io, nep141)?)
+        .map_err(|_| GetErc20FromNep141Error::Nep141NotFound)?;
+
+    let promise = erc20_metadata_promise(nep141.clone(), erc20_token, env.current_account_id());
+    handler.promise_create_with_callback(&promise);
+
+    Ok(erc20_token)
+}
+
+/// Promise calling `ft_metadata` on a NEP-141 token, with the `set_erc20_metadata` callback
+/// copying the result to its ERC-20 token.
+fn erc20_metadata_promise(
+    nep141: AccountId,
+    erc20_token: Address,
+    current_account_id: AccountId,
+) -> PromiseWithCallbackArgs {
+    PromiseWithCallbackArgs {
+        base: PromiseCreateArgs {
+            target_account_id: nep141,
+            method: "ft_metadata".into(),
+            args: Vec::new(),
+            attached_balance: ZERO_YOCTO,
+            attached_gas: FT_METADATA_GAS,
+        },
+        callback: PromiseCreateArgs {
+            target_account_id: current_account_id,
+            method: "set_erc20_metadata".into(),
+            args: erc20_token.try_to_vec().unwrap(),
+            attached_balance: ZERO_YOCTO,
+            attached_gas: SET_ERC20_METADATA_GAS,
+        },
+    }
+}
+
+/// Parses the result of `ft_metadata` on a NEP-141 token. Returns `None` if the name or symbol
+/// are too long, and drops an icon which is too long (see `MAX_ERC20_NAME_LEN`).
+pub fn parse_nep141_metadata(result: &PromiseResult) -> Option<Erc20Metadata> {
+    let json = match result {
+        PromiseResult::Successful(bytes) => crate::json::parse_json(bytes)?,
+        _ => return None,
+    };
+    let name = json.string("name").ok()?;
+    let symbol = json.string("symbol").ok()?;
+    if name.len() > MAX_ERC20_NAME_LEN || symbol.len() > MAX_ERC20_SYMBOL_LEN {
+        return None;
+    }
+    Some(Erc20Metadata {
+        name,
+        symbol,
+        decimals: u8::try_from(json.u64("decimals").ok()?).ok()?,
+        icon: json
+            .string("icon")
+            .ok()
+            .filter(|icon| icon.len() <= MAX_ERC20_ICON_LEN),
+    })
+}
+
+/// Caches the metadata of the NEP-141 token of a bridged ERC-20 token, and sets the name, symbol
+/// and decimals of the ERC-20 token to the ones of the NEP-141 token.
+pub fn set_erc20_metadata<I: IO + Copy, E: Env, P: PromiseHandler>(
+    erc20_token: Address,
+    metadata: &Erc20Metadata,
+    mut io: I,
+    env: &E,
+    handler: &mut P,
+) -> Result<(), DeployErc20Error> {
+    let current_account_id = env.current_account_id();
+    let erc20_admin_address = current_address(&current_account_id);
+    let mut engine = Engine::new(erc20_admin_address, current_account_id, io, env)
+        .map_err(DeployErc20Error::State)?;
+
+    let tail = ethabi::encode(&[
+        ethabi::Token::String(metadata.name.clone()),
+        ethabi::Token::String(metadata.symbol.clone()),
+        ethabi::Token::Uint(metadata.decimals.into()),
+    ]);
+    let result = engine
+        .call(
+            &erc20_admin_address,
+            &erc20_token,
+            Wei::zero(),
+            [ERC20_SET_METADATA_SELECTOR, tail.as_slice()].concat(),
+            u64::MAX,
+            Vec::new(),
+            handler,
+        )
+        .map_err(DeployErc20Error::Engine)?;
+    if !result.status.is_ok() {
+        return Err(DeployErc20Error::Failed(result.status));
+    }
+
+    io.write_borsh(
+        &address_to_key(KeyPrefix::Erc20Metadata, &erc20_token),
+        metadata,
+    );
+    Ok(())
+}
+
 pub fn set_code<I: IO>(io: &mut I, address: &Address, code: &[u8]) {
     io.write_storage(&address_to_key(KeyPrefix::Code, address), code);
 }
@@ -1328,6 +1913,41 @@ pub fn get_storage<I: IO>(io: &I, address: &Address, key: &H256, generation: u32
         .unwrap_or_default()
 }
 
+/// Returns the code the EVM runs at the given address. This is the stored code, except for the
+/// code which the wrapped ETH precompile uses to access the state (from the height the precompile
+/// is installed).
+fn get_evm_code<I: IO>(io: &I, block_height: u64, address: &Address) -> Vec<u8> {
+    if *address == wrapped_eth::STATE_ADDRESS && block_height >= EXTENDED_PRECOMPILES_HEIGHT {
+        return wrapped_eth::STATE_CODE.to_vec();
+    }
+    get_code(io, address)
+}
+
+/// The storage of the wrapped ETH precompile (its allowances) is kept under its own prefix,
+/// since the precompile account is empty and would otherwise have its storage cleared.
+fn get_wrapped_eth_storage<I: IO>(io: &I, index: &H256) -> H256 {
+    io.read_storage(&wrapped_eth::allowance_key(index))
+        .and_then(|value| {
+            if value.len() == 32 {
+                let mut buf = [0u8; 32];
+                value.copy_to_slice(&mut buf);
+                Some(H256(buf))
+            } else {
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
+fn set_wrapped_eth_storage<I: IO>(io: &mut I, index: &H256, value: &H256) {
+    let key = wrapped_eth::allowance_key(index);
+    if *value == H256::default() {
+        io.remove_storage(&key);
+    } else {
+        io.write_storage(&key, &value.0);
+    }
+}
+
 pub fn is_account_empty<I: IO>(io: &I, address: &Address) -> bool {
     get_balance(io, address).is_zero()
         && get_nonce(io, address).is_zero()
@@ -1397,6 +2017,11 @@ where
                                 let base_id = schedule_promise(handler, &promise.base);
                                 schedule_promise_callback(handler, base_id, &promise.callback)
                             }
+                            PromiseArgs::Recursive(_) => {
+                                // The exit precompiles only produce simple promises; recursive
+                                // promises are only created by the cross-contract call router.
+                                return None;
+                            }
                         };
                     }
                     // do not pass on these "internal logs" to caller
@@ -1423,6 +2048,14 @@ where
                             current_account_id,
                         );
                     }
+                } else if log.topics[0] == cross_contract_call::PERMIT_NONCE_TOPIC {
+                    // The permit is consumed only now that the transaction succeeded.
+                    let owner = Address::try_from_slice(&log.topics[1].as_bytes()[12..]);
+                    let nonce = log.data.as_slice().try_into().map(u64::from_le_bytes);
+                    if let (Ok(owner), Ok(nonce)) = (owner, nonce) {
+                        let mut io = *io;
+                        crate::xcc::set_permit_nonce(&mut io, &owner, nonce);
+                    }
                 }
                 // do not pass on these "internal logs" to caller
                 None
@@ -1502,15 +2135,13 @@ impl<'env, I: IO + Copy, E: Env> evm::backend::Backend for Engine<'env, I, E> {
         U256::from(self.env.block_height())
     }
 
-    /// Returns a mocked coinbase which is the EVM address for the Aurora
-    /// account, being 0x4444588443C3a91288c5002483449Aba1054192b.
+    /// Returns the coinbase configured by the owner. By default, this is a mocked coinbase
+    /// which is the EVM address for the Aurora account, being 0x4444588443C3a91288c5002483449Aba1054192b.
+    /// It can also be the EVM address of the relayer which submitted the transaction.
     ///
     /// See: https://doc.aurora.dev/develop/compat/evm#coinbase
     fn block_coinbase(&self) -> H160 {
-        H160([
-            0x44, 0x44, 0x58, 0x84, 0x43, 0xC3, 0xa9, 0x12, 0x88, 0xc5, 0x00, 0x24, 0x83, 0x44,
-            0x9A, 0xba, 0x10, 0x54, 0x19, 0x2b,
-        ])
+        get_coinbase_address(&self.io, self.coinbase, &self.env.predecessor_account_id())
     }
 
     /// Returns the current block timestamp.
@@ -1527,13 +2158,13 @@ impl<'env, I: IO + Copy, E: Env> evm::backend::Backend for Engine<'env, I, E> {
 
     /// Returns the current block gas limit.
     ///
-    /// Currently, this returns 0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff
-    /// as there isn't a gas limit alternative right now but this may change in
-    /// the future.
+    /// This is the limit configured by the owner, or
+    /// 0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff
+    /// if no limit is configured.
     ///
     /// See: https://doc.aurora.dev/develop/compat/evm#gaslimit
     fn block_gas_limit(&self) -> U256 {
-        U256::max_value()
+        self.state.gas_limit()
     }
 
     /// Returns the current base fee for the current block.
@@ -1563,7 +2194,9 @@ impl<'env, I: IO + Copy, E: Env> evm::backend::Backend for Engine<'env, I, E> {
             return true;
         }
         let mut cache = self.contract_code_cache.borrow_mut();
-        let code = cache.get_or_insert_with(address, || get_code(&self.io, &address));
+        let code = cache.get_or_insert_with(address, || {
+            get_evm_code(&self.io, self.env.block_height(), &address)
+        });
         !code.is_empty()
     }
 
@@ -1586,13 +2219,18 @@ impl<'env, I: IO + Copy, E: Env> evm::backend::Backend for Engine<'env, I, E> {
         let address = Address::new(address);
         self.contract_code_cache
             .borrow_mut()
-            .get_or_insert_with(address, || get_code(&self.io, &address))
+            .get_or_insert_with(address, || {
+                get_evm_code(&self.io, self.env.block_height(), &address)
+            })
             .clone()
     }
 
     /// Get storage value of address at index.
     fn storage(&self, address: H160, index: H256) -> H256 {
         let address = Address::new(address);
+        if address == wrapped_eth::ADDRESS {
+            return get_wrapped_eth_storage(&self.io, &index);
+        }
         let generation = *self
             .generation_cache
             .borrow_mut()
@@ -1673,7 +2311,9 @@ impl<'env, J: IO + Copy, E: Env> ApplyBackend for Engine<'env, J, E> {
                     };
 
                     for (index, value) in storage {
-                        if value == H256::default() {
+                        if address == wrapped_eth::ADDRESS {
+                            set_wrapped_eth_storage(&mut self.io, &index, &value);
+                        } else if value == H256::default() {
                             remove_storage(&mut self.io, &address, &index, next_generation)
                         } else {
                             set_storage(&mut self.io, &address, &index, &value, next_generation)
@@ -1746,4 +2386,39 @@ impl<'env, J: IO + Copy, E: Env> ApplyBackend for Engine<'env, J, E> {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nep141_metadata_lengths() {
+        let metadata = |name: &str, symbol: &str, icon: &str| {
+            let json = crate::prelude::format!(
+                r#"{{"name":"{}","symbol":"{}","decimals":18,"icon":"{}"}}"#,
+                name,
+                symbol,
+                icon
+            );
+            parse_nep141_metadata(&PromiseResult::Successful(json.into_bytes()))
+        };
+
+        let name = "a".repeat(MAX_ERC20_NAME_LEN);
+        let symbol = "A".repeat(MAX_ERC20_SYMBOL_LEN);
+        let icon = "i".repeat(MAX_ERC20_ICON_LEN);
+        assert_eq!(
+            metadata(&name, &symbol, &icon),
+            Some(Erc20Metadata {
+                name: name.clone(),
+                symbol: symbol.clone(),
+                decimals: 18,
+                icon: Some(icon.clone()),
+            })
+        );
+
+        // Too long icons are dropped
+        let long_icon = icon + "i";
+        assert_eq!(metadata(&name, &symbol, &long_icon).unwrap().icon, None);
+        // Too long names and symbols keep the placeholder metadata
+        assert_eq!(metadata(&(name.clone() + "a"), &symbol, ""), None);
+        assert_eq!(metadata(&name, &(symbol + "A"), ""), None);
+    }
+}
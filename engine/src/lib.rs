@@ -77,19 +77,21 @@ mod contract {
     use crate::parameters::{
         self, CallArgs, DeployErc20TokenArgs, GetErc20FromNep141CallArgs, GetStorageAtArgs,
         InitCallArgs, IsUsedProofCallArgs, NEP141FtOnTransferArgs, NewCallArgs,
-        PauseEthConnectorCallArgs, ResolveTransferCallArgs, SetContractDataCallArgs,
-        StorageDepositCallArgs, StorageWithdrawCallArgs, TransferCallCallArgs, ViewCallArgs,
+        NewWithGenesisCallArgs, PauseEthConnectorCallArgs, RefreshErc20MetadataArgs,
+        ResolveTransferCallArgs, SetBlockGasLimitArgs, SetCoinbaseArgs, SetContractDataCallArgs,
+        SetPrecompileStatusArgs, StorageDepositCallArgs, StorageWithdrawCallArgs,
+        TransferCallCallArgs, ViewCallArgs,
     };
     #[cfg(feature = "evm_bully")]
     use crate::parameters::{BeginBlockArgs, BeginChainArgs};
     use crate::prelude::account_id::AccountId;
-    use crate::prelude::parameters::RefundCallArgs;
+    use crate::prelude::parameters::{EvmCallbackArgs, RefundCallArgs};
     use crate::prelude::sdk::types::{
         near_account_to_evm_address, SdkExpect, SdkProcess, SdkUnwrap,
     };
     use crate::prelude::storage::{bytes_to_key, KeyPrefix};
     use crate::prelude::{
-        sdk, u256_to_arr, Address, PromiseResult, ToString, Yocto, ERR_FAILED_PARSE, H256,
+        sdk, u256_to_arr, Address, PromiseResult, ToString, Yocto, ERR_FAILED_PARSE, H256, U256,
     };
     use aurora_engine_sdk::env::Env;
     use aurora_engine_sdk::io::{StorageIntermediate, IO};
@@ -111,12 +113,32 @@ mod contract {
     #[no_mangle]
     pub extern "C" fn new() {
         let mut io = Runtime;
-        if let Ok(state) = engine::get_state(&io) {
+        let is_initialized = if let Ok(state) = engine::get_state(&io) {
             require_owner_only(&state, &io.predecessor_account_id());
-        }
+            true
+        } else {
+            false
+        };
 
-        let args: NewCallArgs = io.read_input_borsh().sdk_unwrap();
+        let input = io.read_input().to_vec();
+        // The input may be followed by a genesis specification of accounts to predeploy.
+        let (args, genesis) = match NewCallArgs::try_from_slice(&input) {
+            Ok(args) => (args, None),
+            Err(_) => {
+                let args = NewWithGenesisCallArgs::try_from_slice(&input)
+                    .sdk_expect(errors::ERR_BORSH_DESERIALIZE);
+                (args.new_args, Some(args.genesis))
+            }
+        };
+        // The genesis accounts can only be installed on a new state.
+        if genesis.is_some() && is_initialized {
+            sdk::panic_utf8(errors::ERR_CONTRACT_INITIALIZED);
+        }
         engine::set_state(&mut io, args.into());
+        if let Some(genesis) = genesis {
+            let current_account_id = io.current_account_id();
+            engine::install_genesis(&mut io, &current_account_id, &genesis).sdk_unwrap();
+        }
     }
 
     /// Get version of the contract.
@@ -161,6 +183,60 @@ mod contract {
         io.return_output(&(index + state.upgrade_delay_blocks).to_le_bytes())
     }
 
+    /// Get the block gas limit configuration (borsh-encoded `Option<BlockGasLimit>`).
+    #[no_mangle]
+    pub extern "C" fn get_block_gas_limit() {
+        let mut io = Runtime;
+        let state = engine::get_state(&io).sdk_unwrap();
+        io.return_output(
+            &state
+                .block_gas_limit
+                .try_to_vec()
+                .sdk_expect(errors::ERR_SERIALIZE),
+        )
+    }
+
+    /// Set the block gas limit reported by the `GASLIMIT` opcode, and whether
+    /// the cumulative gas of the transactions in a NEAR block is limited by it.
+    #[no_mangle]
+    pub extern "C" fn set_block_gas_limit() {
+        let mut io = Runtime;
+        let mut state = engine::get_state(&io).sdk_unwrap();
+        require_owner_only(&state, &io.predecessor_account_id());
+        let args: SetBlockGasLimitArgs = io.read_input_borsh().sdk_unwrap();
+        state.block_gas_limit = args.block_gas_limit;
+        engine::set_state(&mut io, state);
+    }
+
+    /// Set the address returned by the `COINBASE` opcode from the given block height onwards.
+    #[no_mangle]
+    pub extern "C" fn set_coinbase() {
+        let mut io = Runtime;
+        let state = engine::get_state(&io).sdk_unwrap();
+        require_owner_only(&state, &io.predecessor_account_id());
+        let block_height = io.block_height();
+        let args: SetCoinbaseArgs = io.read_input_borsh().sdk_unwrap();
+        engine::set_coinbase(&mut io, block_height, &args);
+    }
+
+    /// Enable, disable or remap the precompile with the given default address.
+    #[no_mangle]
+    pub extern "C" fn set_precompile_status() {
+        let mut io = Runtime;
+        let state = engine::get_state(&io).sdk_unwrap();
+        require_owner_only(&state, &io.predecessor_account_id());
+        let args: SetPrecompileStatusArgs = io.read_input_borsh().sdk_unwrap();
+        engine::set_precompile_status(&mut io, &args).sdk_unwrap();
+    }
+
+    /// Get the precompile overrides of this deployment (borsh-encoded `PrecompileRegistry`).
+    #[no_mangle]
+    pub extern "C" fn get_precompile_registry() {
+        let mut io = Runtime;
+        let registry = engine::get_precompile_registry(&io);
+        io.return_output(&registry.try_to_vec().sdk_expect(errors::ERR_SERIALIZE));
+    }
+
     /// Stage new code for deployment.
     #[no_mangle]
     pub extern "C" fn stage_upgrade() {
@@ -193,7 +269,8 @@ mod contract {
     /// code.
     #[no_mangle]
     pub extern "C" fn state_migration() {
-        // TODO: currently we don't have migrations
+        let mut io = Runtime;
+        engine::migrate_legacy_state(&mut io).sdk_unwrap();
     }
 
     ///
@@ -303,7 +380,73 @@ mod contract {
         let mut io = Runtime;
         io.assert_private_call().sdk_unwrap();
         let args: crate::xcc::AddressVersionUpdateArgs = io.read_input_borsh().sdk_unwrap();
-        crate::xcc::set_code_version_of_address(&mut io, &args.address, args.version);
+        crate::xcc::handle_address_version_update(&mut io, &Runtime, &args);
+    }
+
+    /// Deploys the latest router code to the routers of the given addresses which are out of
+    /// date, keeping their state. Returns how many of the addresses were processed within the
+    /// prepaid gas, as a little-endian u32; the remaining ones must be sent again.
+    #[no_mangle]
+    pub extern "C" fn factory_upgrade_routers() {
+        let mut io = Runtime;
+        let state = engine::get_state(&io).sdk_unwrap();
+        require_owner_only(&state, &io.predecessor_account_id());
+        let args: crate::xcc::UpgradeRoutersArgs = io.read_input_borsh().sdk_unwrap();
+        let processed = crate::xcc::upgrade_routers(
+            &io,
+            &mut Runtime,
+            &args.addresses,
+            io.prepaid_gas(),
+            &io.current_account_id(),
+        );
+        let processed = u32::try_from(processed).sdk_expect(errors::ERR_SERIALIZE);
+        io.return_output(&processed.to_le_bytes());
+    }
+
+    /// Returns how many routers are on each version of the router code, as a borsh serialized
+    /// `RouterVersionCounts`. The counts are flagged as incomplete if routers were deployed
+    /// before they were introduced.
+    #[no_mangle]
+    pub extern "C" fn get_xcc_router_version_counts() {
+        let mut io = Runtime;
+        let counts = crate::xcc::RouterVersionCounts {
+            counts: crate::xcc::get_router_version_counts(&io),
+            complete: crate::xcc::router_version_counts_complete(&io),
+        };
+        io.return_output(&counts.try_to_vec().sdk_expect(errors::ERR_SERIALIZE));
+    }
+
+    /// Callback from a router contract which delivers the result of a cross contract call made
+    /// with `CrossContractCallArgs::EagerWithCallback` to the EVM contract the router belongs to.
+    #[no_mangle]
+    pub extern "C" fn xcc_callback() {
+        let io = Runtime;
+        let current_account_id = io.current_account_id();
+        let address =
+            crate::xcc::router_owner_address(&io.predecessor_account_id(), &current_account_id)
+                .sdk_expect(crate::xcc::ERR_INVALID_ACCOUNT);
+        let args: EvmCallbackArgs = io.read_input_borsh().sdk_unwrap();
+
+        // This function should only be called as the callback of
+        // exactly one promise.
+        if io.promise_results_count() != 1 {
+            sdk::panic_utf8(errors::ERR_PROMISE_COUNT);
+        }
+        let promise_result = io.promise_result(0).sdk_unwrap();
+
+        let state = engine::get_state(&io).sdk_unwrap();
+        let result = crate::xcc::handle_evm_callback(
+            io,
+            &io,
+            state,
+            address,
+            &args,
+            &promise_result,
+            &mut Runtime,
+        )
+        .sdk_unwrap();
+        let result_bytes = result.try_to_vec().sdk_expect(errors::ERR_SERIALIZE);
+        io.return_output(&result_bytes);
     }
 
     /// Sets the address for the wNEAR ERC-20 contract. This contract will be used by the
@@ -357,7 +500,10 @@ mod contract {
         }
     }
 
-    /// Deploy ERC20 token mapped to a NEP141
+    /// Deploy ERC20 token mapped to a NEP141. The metadata of the NEP141 token is then copied to
+    /// the ERC20 token by a callback, which needs `engine::FT_METADATA_GAS` and
+    /// `engine::SET_ERC20_METADATA_GAS` (35 Tgas in total) to be attached on top of the
+    /// deployment gas.
     #[no_mangle]
     pub extern "C" fn deploy_erc20_token() {
         let mut io = Runtime;
@@ -376,6 +522,48 @@ mod contract {
         // TODO: charge for storage
     }
 
+    /// Callback invoked by `deploy_erc20_token` with the result of `ft_metadata` on the NEP-141
+    /// token, which is cached and set as the metadata of the bridged ERC-20 token.
+    #[no_mangle]
+    pub extern "C" fn set_erc20_metadata() {
+        let io = Runtime;
+        io.assert_private_call().sdk_unwrap();
+
+        // This function should only be called as the callback of
+        // exactly one promise.
+        if io.promise_results_count() != 1 {
+            sdk::panic_utf8(errors::ERR_PROMISE_COUNT);
+        }
+
+        let erc20_token: Address = io.read_input_borsh().sdk_unwrap();
+        // If the NEP-141 token has no valid metadata, the ERC-20 token keeps its placeholder one.
+        if let Some(metadata) = io
+            .promise_result(0)
+            .and_then(|result| engine::parse_nep141_metadata(&result))
+        {
+            engine::set_erc20_metadata(erc20_token, &metadata, io, &io, &mut Runtime).sdk_unwrap();
+        }
+    }
+
+    /// Fetches the metadata of a bridged NEP141 token again, and copies it to its ERC20 token by
+    /// the same callback as `deploy_erc20_token`. Anyone can call it, since the metadata always
+    /// comes from the NEP141 token itself. Returns the address of the ERC20 token.
+    #[no_mangle]
+    pub extern "C" fn refresh_erc20_metadata() {
+        let mut io = Runtime;
+        let args: RefreshErc20MetadataArgs = io.read_input_borsh().sdk_unwrap();
+
+        let address =
+            engine::refresh_erc20_metadata(&args.nep141, &io, &io, &mut Runtime).sdk_unwrap();
+
+        io.return_output(
+            &address
+                .as_bytes()
+                .try_to_vec()
+                .sdk_expect(errors::ERR_SERIALIZE),
+        );
+    }
+
     /// Callback invoked by exit to NEAR precompile to handle potential
     /// errors in the exit call.
     #[no_mangle]
@@ -389,19 +577,25 @@ mod contract {
             sdk::panic_utf8(errors::ERR_PROMISE_COUNT);
         }
 
-        if let Some(PromiseResult::Successful(_)) = io.promise_result(0) {
-            // Promise succeeded -- nothing to do
-        } else {
-            // Exit call failed; need to refund tokens
-            let args: RefundCallArgs = io.read_input_borsh().sdk_unwrap();
-            let state = engine::get_state(&io).sdk_unwrap();
-            let refund_result =
-                engine::refund_on_error(io, &io, state, args, &mut Runtime).sdk_unwrap();
-
-            if !refund_result.status.is_ok() {
-                sdk::panic_utf8(errors::ERR_REFUND_FAILURE);
+        let mut args: RefundCallArgs = io.read_input_borsh().sdk_unwrap();
+        if let Some(PromiseResult::Successful(result)) = io.promise_result(0) {
+            // Promise succeeded -- nothing to do, unless it was an `ft_transfer_call`
+            // whose receiver did not use all the tokens; then the rest is refunded.
+            let amount = U256::from_big_endian(&args.amount);
+            match engine::ft_transfer_call_unused_amount(&result, amount) {
+                Some(unused_amount) => args.amount = u256_to_arr(&unused_amount),
+                None => return,
             }
         }
+
+        // Exit call failed or tokens were returned; need to refund them
+        let state = engine::get_state(&io).sdk_unwrap();
+        let refund_result =
+            engine::refund_on_error(io, &io, state, args, &mut Runtime).sdk_unwrap();
+
+        if !refund_result.status.is_ok() {
+            sdk::panic_utf8(errors::ERR_REFUND_FAILURE);
+        }
     }
 
     ///
@@ -455,6 +649,16 @@ mod contract {
         io.return_output(&u256_to_arr(&nonce))
     }
 
+    /// Returns the nonce to sign the next cross contract call permit of the given address with
+    /// (see `XccPermit`), as a little-endian u64.
+    #[no_mangle]
+    pub extern "C" fn get_xcc_permit_nonce() {
+        let mut io = Runtime;
+        let address = io.read_input_arr20().sdk_unwrap();
+        let nonce = crate::xcc::get_permit_nonce(&io, &Address::from_array(address));
+        io.return_output(&nonce.to_le_bytes())
+    }
+
     #[no_mangle]
     pub extern "C" fn get_storage_at() {
         let mut io = Runtime;
@@ -3,6 +3,7 @@ use crate::errors;
 use crate::fungible_token::FungibleTokenMetadata;
 use crate::json::{JsonError, JsonValue};
 use crate::prelude::account_id::AccountId;
+use crate::prelude::precompiles::registry::PrecompileStatus;
 use crate::prelude::{
     format, Address, Balance, BorshDeserialize, BorshSerialize, RawH256, RawU256, String, Vec,
     WeiU256,
@@ -28,6 +29,34 @@ pub struct NewCallArgs {
     pub upgrade_delay_blocks: u64,
 }
 
+/// Borsh-encoded parameters for the `new` function when accounts are also
+/// installed at genesis. The encoding is that of `NewCallArgs` followed by a `Genesis`.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct NewWithGenesisCallArgs {
+    pub new_args: NewCallArgs,
+    pub genesis: Genesis,
+}
+
+/// Account state installed at genesis, e.g. a predeployed contract.
+#[derive(Debug, Clone, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GenesisAccount {
+    pub address: Address,
+    pub balance: WeiU256,
+    pub nonce: RawU256,
+    /// Empty for accounts without code.
+    pub code: Vec<u8>,
+    /// Storage slots as `(key, value)` pairs.
+    pub storage: Vec<(RawH256, RawH256)>,
+}
+
+/// Specification of the EVM state at genesis.
+#[derive(Debug, Clone, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Genesis {
+    pub accounts: Vec<GenesisAccount>,
+}
+
 /// Borsh-encoded log for use in a `SubmitResult`.
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -183,6 +212,61 @@ pub struct DeployErc20TokenArgs {
 /// Borsh-encoded parameters for `get_erc20_from_nep141` function.
 pub type GetErc20FromNep141CallArgs = DeployErc20TokenArgs;
 
+/// Borsh-encoded parameters for `refresh_erc20_metadata` function.
+pub type RefreshErc20MetadataArgs = DeployErc20TokenArgs;
+
+/// Gas limit of an Aurora block.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BlockGasLimit {
+    /// The value reported by the `GASLIMIT` opcode.
+    pub limit: u64,
+    /// If set, the total gas used by the Ethereum transactions submitted within a single
+    /// NEAR block may not exceed `limit`; a transaction whose gas limit is larger than
+    /// the remaining budget is rejected.
+    pub enforce: bool,
+}
+
+/// Borsh-encoded parameters for the `set_block_gas_limit` function.
+/// `None` restores the default of an unlimited block gas limit.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SetBlockGasLimitArgs {
+    pub block_gas_limit: Option<BlockGasLimit>,
+}
+
+/// Source of the address returned by the `COINBASE` opcode.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Coinbase {
+    /// The EVM address of the Aurora account, 0x4444588443C3a91288c5002483449Aba1054192b.
+    Default,
+    /// The EVM address registered by the relayer which submitted the transaction
+    /// (see `register_relayer`), or the address derived from its account ID
+    /// if it did not register one.
+    Relayer,
+    /// An address chosen by the owner.
+    Fixed(Address),
+}
+
+/// Borsh-encoded parameters for the `set_coinbase` function.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SetCoinbaseArgs {
+    pub coinbase: Coinbase,
+    /// Block height from which `coinbase` is used. Until then the coinbase
+    /// in effect when `set_coinbase` was called is kept.
+    pub activation_height: u64,
+}
+
+/// Borsh-encoded parameters for the `set_precompile_status` function.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SetPrecompileStatusArgs {
+    /// Default address of the precompile.
+    pub address: Address,
+    pub status: PrecompileStatus,
+}
+
 /// Borsh-encoded parameters for the `get_storage_at` function.
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct GetStorageAtArgs {
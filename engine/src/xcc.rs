@@ -1,11 +1,15 @@
-use crate::parameters::{CallArgs, FunctionCallArgsV2};
-use aurora_engine_precompiles::xcc::state;
+use crate::engine::{Engine, EngineResult, EngineState};
+use crate::parameters::{CallArgs, FunctionCallArgsV2, SubmitResult};
+use aurora_engine_precompiles::xcc::{cross_contract_call, state};
+use aurora_engine_sdk::env::Env;
 use aurora_engine_sdk::io::{StorageIntermediate, IO};
-use aurora_engine_sdk::promise::PromiseHandler;
+use aurora_engine_sdk::promise::{PromiseHandler, PromiseId};
 use aurora_engine_types::account_id::AccountId;
-use aurora_engine_types::parameters::{PromiseAction, PromiseBatchAction, PromiseCreateArgs};
+use aurora_engine_types::parameters::{
+    EvmCallbackArgs, PromiseAction, PromiseBatchAction, PromiseCreateArgs,
+};
 use aurora_engine_types::storage::{self, KeyPrefix};
-use aurora_engine_types::types::{Address, NearGas, Yocto, ZERO_YOCTO};
+use aurora_engine_types::types::{Address, NearGas, PromiseResult, Wei, Yocto, ZERO_YOCTO};
 use aurora_engine_types::{format, Cow, Vec, U256};
 use borsh::{BorshDeserialize, BorshSerialize};
 
@@ -15,13 +19,24 @@ pub const ERR_ATTACHED_NEAR: &str = "ERR_ATTACHED_XCC_NEAR";
 pub const CODE_KEY: &[u8] = b"router_code";
 pub const VERSION_UPDATE_GAS: NearGas = NearGas::new(5_000_000_000_000);
 pub const INITIALIZE_GAS: NearGas = NearGas::new(15_000_000_000_000);
+/// Migrating rewrites the storage of the router in bounded steps; routers with many scheduled
+/// promises finish the migration in later calls to `continue_migration`.
+pub const MIGRATE_GAS: NearGas = NearGas::new(25_000_000_000_000);
+/// Gas used by `factory_upgrade_routers` itself, besides the promises it creates.
+pub const UPGRADE_ROUTERS_BASE_GAS: NearGas = NearGas::new(20_000_000_000_000);
+/// Gas needed to upgrade one router: the deploy batch (which carries the router code), its
+/// `migrate` call and the callback updating the version of the router.
+pub const UPGRADE_ROUTER_GAS: NearGas = NearGas::new(40_000_000_000_000);
+pub const VERSION_COUNT_KEY: &[u8] = b"version_count";
+/// Present if the router version counts were tracked since the first router code was set.
+pub const VERSION_COUNT_COMPLETE_KEY: &[u8] = b"version_count_complete";
 pub const UNWRAP_AND_REFUND_GAS: NearGas = NearGas::new(25_000_000_000_000);
 pub const WITHDRAW_GAS: NearGas = NearGas::new(30_000_000_000_000);
 pub const WITHDRAW_TO_NEAR_SELECTOR: [u8; 4] = [0x6b, 0x35, 0x18, 0x48];
 
 pub use aurora_engine_precompiles::xcc::state::{
-    get_code_version_of_address, get_latest_code_version, get_wnear_address, CodeVersion,
-    ERR_CORRUPTED_STORAGE, STORAGE_AMOUNT, VERSION_KEY, WNEAR_KEY,
+    get_code_version_of_address, get_latest_code_version, get_permit_nonce, get_wnear_address,
+    set_permit_nonce, CodeVersion, ERR_CORRUPTED_STORAGE, STORAGE_AMOUNT, VERSION_KEY, WNEAR_KEY,
 };
 
 /// Type wrapper for router bytecode.
@@ -44,6 +59,22 @@ pub struct AddressVersionUpdateArgs {
     pub version: CodeVersion,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct UpgradeRoutersArgs {
+    pub addresses: Vec<Address>,
+}
+
+/// Number of routers on each version of the router code.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct RouterVersionCounts {
+    /// `(version, count)` pairs, from the first version to the latest one.
+    pub counts: Vec<(CodeVersion, u64)>,
+    /// False if routers were deployed before the counts were introduced. The versions of these
+    /// routers cannot be enumerated, so they are missing from the counts (and upgrading them
+    /// decrements the count of their previous version), and the counts are only estimates.
+    pub complete: bool,
+}
+
 pub fn handle_precompile_promise<I, P>(
     io: &I,
     handler: &mut P,
@@ -89,53 +120,15 @@ pub fn handle_precompile_promise<I, P>(
     //    with a batch transaction to deploy the router. This batch also has an attached
     //    callback to update the engine's storage with the new version of that router account.
     let setup_id = match &deploy_needed {
-        AddressVersionStatus::DeployNeeded { create_needed } => {
-            let mut promise_actions = Vec::with_capacity(4);
-            if *create_needed {
-                promise_actions.push(PromiseAction::CreateAccount);
-                promise_actions.push(PromiseAction::Transfer {
-                    amount: STORAGE_AMOUNT,
-                });
-            }
-            promise_actions.push(PromiseAction::DeployConotract {
-                code: get_router_code(io).0.into_owned(),
-            });
-            // After a deploy we call the contract's initialize function
-            let wnear_address = state::get_wnear_address(io);
-            let wnear_account = crate::engine::nep141_erc20_map(*io)
-                .lookup_right(&crate::engine::ERC20Address(wnear_address))
-                .unwrap();
-            let init_args = format!(
-                r#"{{"wnear_account": "{}", "must_register": {}}}"#,
-                wnear_account.0.as_ref(),
-                create_needed,
-            );
-            promise_actions.push(PromiseAction::FunctionCall {
-                name: "initialize".into(),
-                args: init_args.into_bytes(),
-                attached_yocto: ZERO_YOCTO,
-                gas: INITIALIZE_GAS,
-            });
-            let batch = PromiseBatchAction {
-                target_account_id: promise.target_account_id.clone(),
-                actions: promise_actions,
-            };
-            let promise_id = handler.promise_create_batch(&batch);
-            // Add a callback here to update the version of the account
-            let args = AddressVersionUpdateArgs {
-                address: sender,
-                version: latest_code_version,
-            };
-            let callback = PromiseCreateArgs {
-                target_account_id: current_account_id.clone(),
-                method: "factory_update_address_version".into(),
-                args: args.try_to_vec().unwrap(),
-                attached_balance: ZERO_YOCTO,
-                attached_gas: VERSION_UPDATE_GAS,
-            };
-
-            Some(handler.promise_attach_callback(promise_id, &callback))
-        }
+        AddressVersionStatus::DeployNeeded { create_needed } => Some(deploy_router(
+            io,
+            handler,
+            &promise.target_account_id,
+            sender,
+            *create_needed,
+            latest_code_version,
+            current_account_id,
+        )),
         AddressVersionStatus::UpToDate => None,
     };
     // 2. If some NEAR is required for this call (from storage staking for a new account
@@ -189,6 +182,170 @@ pub fn handle_precompile_promise<I, P>(
     };
 }
 
+/// Schedules the deployment of the latest router code for the given addresses whose router is
+/// out of date, as many as the prepaid gas allows. Addresses without a router are skipped, since
+/// their router is created with the latest code when they first use it.
+/// Returns how many of the addresses (from the start of the list) were processed; the remaining
+/// ones must be upgraded in another transaction.
+pub fn upgrade_routers<I, P>(
+    io: &I,
+    handler: &mut P,
+    addresses: &[Address],
+    prepaid_gas: NearGas,
+    current_account_id: &AccountId,
+) -> usize
+where
+    P: PromiseHandler,
+    I: IO + Copy,
+{
+    let latest_code_version = get_latest_code_version(io);
+    let mut available_gas = prepaid_gas
+        .as_u64()
+        .saturating_sub(UPGRADE_ROUTERS_BASE_GAS.as_u64());
+    for (processed, address) in addresses.iter().enumerate() {
+        match get_code_version_of_address(io, address) {
+            Some(version) if version < latest_code_version => (),
+            _ => continue,
+        }
+        if available_gas < UPGRADE_ROUTER_GAS.as_u64() {
+            return processed;
+        }
+        available_gas -= UPGRADE_ROUTER_GAS.as_u64();
+
+        let router_account_id: AccountId =
+            format!("{}.{}", address.encode(), current_account_id.as_ref())
+                .parse()
+                .expect(ERR_INVALID_ACCOUNT);
+        deploy_router(
+            io,
+            handler,
+            &router_account_id,
+            *address,
+            false,
+            latest_code_version,
+            current_account_id,
+        );
+    }
+    addresses.len()
+}
+
+/// Creates the batch deploying the latest router code to the router of `address`, creating the
+/// router account first if needed, with a callback recording the version of the router.
+/// Returns the id of the callback.
+fn deploy_router<I, P>(
+    io: &I,
+    handler: &mut P,
+    router_account_id: &AccountId,
+    address: Address,
+    create_needed: bool,
+    latest_code_version: CodeVersion,
+    current_account_id: &AccountId,
+) -> PromiseId
+where
+    P: PromiseHandler,
+    I: IO + Copy,
+{
+    let mut promise_actions = Vec::with_capacity(4);
+    if create_needed {
+        promise_actions.push(PromiseAction::CreateAccount);
+        promise_actions.push(PromiseAction::Transfer {
+            amount: STORAGE_AMOUNT,
+        });
+    }
+    promise_actions.push(PromiseAction::DeployConotract {
+        code: get_router_code(io).0.into_owned(),
+    });
+    // After a deploy we call the contract's initialize function for a new router,
+    // or its migrate function for an existing one so that it keeps its state.
+    if create_needed {
+        let wnear_address = state::get_wnear_address(io);
+        let wnear_account = crate::engine::nep141_erc20_map(*io)
+            .lookup_right(&crate::engine::ERC20Address(wnear_address))
+            .unwrap();
+        let init_args = format!(
+            r#"{{"wnear_account": "{}", "must_register": true}}"#,
+            wnear_account.0.as_ref(),
+        );
+        promise_actions.push(PromiseAction::FunctionCall {
+            name: "initialize".into(),
+            args: init_args.into_bytes(),
+            attached_yocto: ZERO_YOCTO,
+            gas: INITIALIZE_GAS,
+        });
+    } else {
+        promise_actions.push(PromiseAction::FunctionCall {
+            name: "migrate".into(),
+            args: Vec::new(),
+            attached_yocto: ZERO_YOCTO,
+            gas: MIGRATE_GAS,
+        });
+    }
+    let batch = PromiseBatchAction {
+        target_account_id: router_account_id.clone(),
+        actions: promise_actions,
+    };
+    let promise_id = handler.promise_create_batch(&batch);
+    // Add a callback here to update the version of the account
+    let args = AddressVersionUpdateArgs {
+        address,
+        version: latest_code_version,
+    };
+    let callback = PromiseCreateArgs {
+        target_account_id: current_account_id.clone(),
+        method: "factory_update_address_version".into(),
+        args: args.try_to_vec().unwrap(),
+        attached_balance: ZERO_YOCTO,
+        attached_gas: VERSION_UPDATE_GAS,
+    };
+
+    handler.promise_attach_callback(promise_id, &callback)
+}
+
+/// Returns the address a router contract belongs to, if the given account is a router of this
+/// engine, i.e. it is of the form `{address}.{aurora}`. Only the engine can create such accounts.
+pub fn router_owner_address(
+    router_account_id: &AccountId,
+    current_account_id: &AccountId,
+) -> Option<Address> {
+    let router_account_id: &str = router_account_id.as_ref();
+    let (address, parent) = router_account_id.split_once('.')?;
+    if address.len() != 40 || parent != current_account_id.as_ref() {
+        return None;
+    }
+    Address::decode(address).ok()
+}
+
+/// Delivers the result of a cross contract call made with `CrossContractCallArgs::EagerWithCallback`
+/// to the contract at `address` which made it. The contract is called from the cross contract
+/// call precompile address, with the borsh serialized promise result ABI encoded as `bytes`.
+pub fn handle_evm_callback<I: IO + Copy, E: Env, P: PromiseHandler>(
+    io: I,
+    env: &E,
+    state: EngineState,
+    address: Address,
+    args: &EvmCallbackArgs,
+    promise_result: &PromiseResult,
+    handler: &mut P,
+) -> EngineResult<SubmitResult> {
+    let xcc_address = cross_contract_call::ADDRESS;
+    let mut engine = Engine::new_with_state(state, xcc_address, env.current_account_id(), io, env);
+    let input = [
+        args.selector.as_slice(),
+        &ethabi::encode(&[ethabi::Token::Bytes(promise_result.try_to_vec().unwrap())]),
+    ]
+    .concat();
+
+    engine.call(
+        &xcc_address,
+        &address,
+        Wei::zero(),
+        input,
+        u64::MAX,
+        Vec::new(),
+        handler,
+    )
+}
+
 /// Read the current wasm bytecode for the router contracts
 pub fn get_router_code<I: IO>(io: &I) -> RouterCode {
     let key = storage::bytes_to_key(KeyPrefix::CrossContractCall, CODE_KEY);
@@ -202,6 +359,10 @@ pub fn update_router_code<I: IO>(io: &mut I, code: &RouterCode) {
     io.write_storage(&key, &code.0);
 
     let current_version = get_latest_code_version(io);
+    // No router can exist before the first code is set, so from then on every router is counted.
+    if current_version.0 == 0 {
+        io.write_storage(&version_count_complete_key(), &[1]);
+    }
     set_latest_code_version(io, current_version.increment());
 }
 
@@ -211,11 +372,74 @@ pub fn set_wnear_address<I: IO>(io: &mut I, address: &Address) {
     io.write_storage(&key, address.as_bytes());
 }
 
+/// Records the version of the router deployed by the batch this is the callback of. If the
+/// deployment failed nothing is recorded, so the router is deployed again on its next use.
+pub fn handle_address_version_update<I: IO, P: PromiseHandler>(
+    io: &mut I,
+    handler: &P,
+    args: &AddressVersionUpdateArgs,
+) {
+    let deployed = handler.promise_results_count() != 1
+        || matches!(
+            handler.promise_result(0),
+            Some(PromiseResult::Successful(_))
+        );
+    if deployed {
+        set_code_version_of_address(io, &args.address, args.version);
+    }
+}
+
 /// Set the version of the router contract deployed for the given address.
 pub fn set_code_version_of_address<I: IO>(io: &mut I, address: &Address, version: CodeVersion) {
+    let previous_version = get_code_version_of_address(io, address);
     let key = storage::bytes_to_key(KeyPrefix::CrossContractCall, address.as_bytes());
     let value_bytes = version.0.to_le_bytes();
     io.write_storage(&key, &value_bytes);
+
+    if previous_version != Some(version) {
+        if let Some(previous_version) = previous_version {
+            let count = get_router_count(io, previous_version);
+            set_router_count(io, previous_version, count.saturating_sub(1));
+        }
+        let count = get_router_count(io, version);
+        set_router_count(io, version, count + 1);
+    }
+}
+
+/// Returns how many routers are on each version, from the first version to the latest one.
+/// Routers whose version was recorded before these counts were introduced are only counted
+/// once they are upgraded (see `router_version_counts_complete`).
+pub fn get_router_version_counts<I: IO>(io: &I) -> Vec<(CodeVersion, u64)> {
+    let latest_code_version = get_latest_code_version(io);
+    (1..=latest_code_version.0)
+        .map(|version| {
+            let version = CodeVersion(version);
+            (version, get_router_count(io, version))
+        })
+        .collect()
+}
+
+/// Returns whether every router is included in the version counts, i.e. whether the engine
+/// was counting routers before the first router code was set.
+pub fn router_version_counts_complete<I: IO>(io: &I) -> bool {
+    io.storage_has_key(&version_count_complete_key())
+}
+
+fn version_count_complete_key() -> Vec<u8> {
+    storage::bytes_to_key(KeyPrefix::CrossContractCall, VERSION_COUNT_COMPLETE_KEY)
+}
+
+fn get_router_count<I: IO>(io: &I, version: CodeVersion) -> u64 {
+    io.read_u64(&router_count_key(version)).unwrap_or(0)
+}
+
+fn set_router_count<I: IO>(io: &mut I, version: CodeVersion, count: u64) {
+    io.write_storage(&router_count_key(version), &count.to_le_bytes());
+}
+
+fn router_count_key(version: CodeVersion) -> Vec<u8> {
+    let suffix = [VERSION_COUNT_KEY, &version.0.to_le_bytes()].concat();
+    storage::bytes_to_key(KeyPrefix::CrossContractCall, &suffix)
 }
 
 /// Sets the latest router contract version. This function is intentionally private because
@@ -242,7 +466,38 @@ fn withdraw_to_near_args(recipient: &AccountId, amount: Yocto) -> Vec<u8> {
 
 #[cfg(test)]
 mod tests {
-    use aurora_engine_types::{account_id::AccountId, types::Yocto, U256};
+    use aurora_engine_types::{
+        account_id::AccountId,
+        types::{Address, Yocto},
+        U256,
+    };
+
+    #[test]
+    fn test_router_owner_address() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let address = Address::from_array([0x5a; 20]);
+        let router = |account: &str| -> AccountId { account.parse().unwrap() };
+
+        assert_eq!(
+            super::router_owner_address(&router(&format!("{}.aurora", address.encode())), &aurora),
+            Some(address)
+        );
+        assert_eq!(
+            super::router_owner_address(&router(&format!("{}.other", address.encode())), &aurora),
+            None
+        );
+        assert_eq!(
+            super::router_owner_address(
+                &router(&format!("{}.sub.aurora", address.encode())),
+                &aurora
+            ),
+            None
+        );
+        assert_eq!(
+            super::router_owner_address(&router("alice.aurora"), &aurora),
+            None
+        );
+    }
 
     #[test]
     fn test_withdraw_to_near_encoding() {
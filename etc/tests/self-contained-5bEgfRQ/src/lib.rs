@@ -31,6 +31,7 @@ pub extern "C" fn run() {
         predecessor_account_id: "relay.aurora".parse().unwrap(),
         block_height: 64417403,
         block_timestamp: env::Timestamp::new(1651073772931594646),
+        epoch_height: 0,
         attached_deposit: 0,
         random_seed: H256([0u8; 32]),
         prepaid_gas: NearGas::new(300_000_000_000_000),
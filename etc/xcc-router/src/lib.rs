@@ -1,7 +1,12 @@
-use aurora_engine_types::parameters::{PromiseArgs, PromiseCreateArgs, PromiseWithCallbackArgs};
+use aurora_engine_types::parameters::{
+    ExecuteWithCallbackArgs, NearPromise, NearPublicKey, PromiseAction, PromiseArgs,
+    PromiseBatchAction, PromiseCreateArgs, PromiseWithCallbackArgs, ScheduleWithExpiryArgs,
+    SimpleNearPromise,
+};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedSet};
 use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::Serialize;
 use near_sdk::BorshStorageKey;
 use near_sdk::{env, near_bindgen, AccountId, Gas, PanicOnDefault, Promise, PromiseIndex};
 
@@ -15,14 +20,32 @@ enum StorageKey {
     Parent,
     Nonce,
     Map,
+    Expiry,
+    Pending,
+    MigrationCursor,
 }
 
-const CURRENT_VERSION: u32 = 1;
+/// Version 2 lists the pending scheduled promises (see `Router::pending_nonces`).
+const CURRENT_VERSION: u32 = 2;
+/// Storage key of the contract state written by `near_bindgen`.
+const STATE_KEY: &[u8] = b"STATE";
+/// Number of nonces a single migration step goes through, so that it fits in the gas the
+/// parent attaches to `migrate`. Routers with more nonces finish with `continue_migration`.
+const MIGRATE_BATCH_SIZE: u64 = 32;
 
 const ERR_ILLEGAL_CALLER: &str = "ERR_ILLEGAL_CALLER";
+const ERR_PROMISE_EXPIRED: &str = "ERR_PROMISE_EXPIRED";
+const ERR_NOT_INITIALIZED: &str = "ERR_CONTRACT_NOT_INITIALIZED";
+const ERR_CORRUPTED_STATE: &str = "ERR_CORRUPTED_STATE";
+const ERR_UNKNOWN_VERSION: &str = "ERR_UNKNOWN_VERSION";
+const ERR_ROUTER_TAKEOVER: &str = "ERR_ROUTER_TAKEOVER";
+const XCC_CALLBACK_NAME: &str = "xcc_callback";
 const WNEAR_WITHDRAW_GAS: Gas = Gas(5_000_000_000_000);
 const WNEAR_REGISTER_GAS: Gas = Gas(5_000_000_000_000);
 const REFUND_GAS: Gas = Gas(5_000_000_000_000);
+const WNEAR_DEPOSIT_GAS: Gas = Gas(5_000_000_000_000);
+/// Covers `ft_on_transfer` on the engine, which mints the refunded wNEAR in the EVM.
+const WNEAR_TRANSFER_CALL_GAS: Gas = Gas(70_000_000_000_000);
 const WNEAR_REGISTER_AMOUNT: u128 = 1_250_000_000_000_000_000_000;
 /// Must match arora_engine_precompiles::xcc::state::STORAGE_AMOUNT
 const REFUND_AMOUNT: u128 = 2_000_000_000_000_000_000_000_000;
@@ -41,6 +64,31 @@ pub struct Router {
     scheduled_promises: LookupMap<u64, PromiseArgs>,
     /// Account ID for the wNEAR contract.
     wnear_account: AccountId,
+    /// The last block height at which a scheduled promise can be executed, for the scheduled
+    /// promises which have one.
+    expiry_block_heights: LookupMap<u64, u64>,
+    /// Nonces of the scheduled promises which have not been executed or removed yet.
+    pending_nonces: UnorderedSet<u64>,
+}
+
+/// Layout of the contract state of version 1 of the router. Only `wnear_account` is read from
+/// it, the other fields are recreated from their storage keys. It is a prefix of the current
+/// layout, so it also reads the state of a router whose migration is still in progress.
+#[derive(BorshDeserialize)]
+struct RouterV1 {
+    _parent: LazyOption<AccountId>,
+    _version: LazyOption<u32>,
+    _nonce: LazyOption<u64>,
+    _scheduled_promises: LookupMap<u64, PromiseArgs>,
+    wnear_account: AccountId,
+}
+
+/// A scheduled promise, as returned by `get_pending_promises`.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingPromise {
+    pub nonce: U64,
+    pub expiry_block_height: Option<U64>,
 }
 
 #[near_bindgen]
@@ -52,8 +100,8 @@ impl Router {
         // call this function. The idea is that the Create, Deploy and Initialize actions are done in a single
         // NEAR batch when a new router is deployed by the engine, so the caller will be the Aurora
         // engine instance that the user's address belongs to. If we update this contract and deploy
-        // a new version of it, again the Deploy and Initialize actions will be done in a single batch
-        // by the engine.
+        // a new version of it, the Deploy and Migrate actions will be done in a single batch
+        // by the engine instead (see `migrate`).
         let caller = env::predecessor_account_id();
         let mut parent = LazyOption::new(StorageKey::Parent, None);
         match parent.get() {
@@ -77,22 +125,34 @@ impl Router {
             );
         }
 
-        let mut version = LazyOption::new(StorageKey::Version, None);
-        if version.get().unwrap_or_default() != CURRENT_VERSION {
-            // Future migrations would go here
+        let mut router = Self::from_storage(wnear_account);
+        router.migrate_storage();
+        router
+    }
 
-            version.set(&CURRENT_VERSION);
+    /// Called by the parent right after deploying a new version of the router over an existing
+    /// one. The contract state of the previous version is read according to its layout, and the
+    /// storage is migrated to the layout of the current version. Scheduled promises are kept.
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let stored_version = LazyOption::<u32>::new(StorageKey::Version, None)
+            .get()
+            .unwrap_or_default();
+        let state =
+            env::storage_read(STATE_KEY).unwrap_or_else(|| env::panic_str(ERR_NOT_INITIALIZED));
+        let wnear_account = match stored_version {
+            1 => RouterV1::deserialize(&mut state.as_slice()).map(|state| state.wnear_account),
+            CURRENT_VERSION => {
+                Self::deserialize(&mut state.as_slice()).map(|state| state.wnear_account)
+            }
+            _ => env::panic_str(ERR_UNKNOWN_VERSION),
         }
+        .unwrap_or_else(|_| env::panic_str(ERR_CORRUPTED_STATE));
 
-        let nonce = LazyOption::new(StorageKey::Nonce, None);
-        let scheduled_promises = LookupMap::new(StorageKey::Map);
-        Self {
-            parent,
-            version,
-            nonce,
-            scheduled_promises,
-            wnear_account,
-        }
+        let mut router = Self::from_storage(wnear_account);
+        router.require_parent_caller();
+        router.migrate_storage();
+        router
     }
 
     /// This function can only be called by the parent account (i.e. Aurora engine) to ensure that
@@ -107,31 +167,115 @@ impl Router {
         env::promise_return(promise_id)
     }
 
+    /// Similar security considerations here as for `execute`. Once the promise resolves, its
+    /// result is sent back to the parent, which delivers it to the EVM contract this router
+    /// belongs to (see `EvmCallbackArgs`).
+    pub fn execute_with_callback(&self, #[serializer(borsh)] args: ExecuteWithCallbackArgs) {
+        self.require_parent_caller();
+
+        let promise_id = Router::promise_create(args.promise);
+        let callback_id = env::promise_then(
+            promise_id,
+            self.get_parent(),
+            XCC_CALLBACK_NAME,
+            &args.callback.try_to_vec().unwrap(),
+            0,
+            args.callback.gas.as_u64().into(),
+        );
+        env::promise_return(callback_id)
+    }
+
     /// Similar security considerations here as for `execute`.
     pub fn schedule(&mut self, #[serializer(borsh)] promise: PromiseArgs) {
         self.require_parent_caller();
 
-        let nonce = self.nonce.get().unwrap_or_default();
-        self.scheduled_promises.insert(&nonce, &promise);
-        self.nonce.set(&(nonce + 1));
+        let nonce = self.insert_scheduled(&promise);
 
         near_sdk::log!("Promise scheduled at nonce {}", nonce);
     }
 
+    /// Similar security considerations here as for `execute`. The promise can only be executed
+    /// up to (and including) the given block height.
+    pub fn schedule_with_expiry(&mut self, #[serializer(borsh)] args: ScheduleWithExpiryArgs) {
+        self.require_parent_caller();
+
+        let nonce = self.insert_scheduled(&args.promise);
+        self.expiry_block_heights
+            .insert(&nonce, &args.expiry_block_height);
+
+        near_sdk::log!(
+            "Promise scheduled at nonce {} until block {}",
+            nonce,
+            args.expiry_block_height
+        );
+    }
+
     /// It is intentional that this function can be called by anyone (not just the parent).
     /// There is no security risk to allowing this function to be open because it can only
     /// act on promises that were created via `schedule`.
     #[payable]
     pub fn execute_scheduled(&mut self, nonce: U64) {
-        let promise = match self.scheduled_promises.remove(&nonce.0) {
-            Some(promise) => promise,
-            None => env::panic_str("ERR_PROMISE_NOT_FOUND"),
-        };
+        if let Some(expiry_block_height) = self.expiry_block_heights.get(&nonce.0) {
+            if env::block_height() > expiry_block_height {
+                env::panic_str(ERR_PROMISE_EXPIRED);
+            }
+        }
+        let promise = self.remove_scheduled(nonce.0);
 
         let promise_id = Router::promise_create(promise);
         env::promise_return(promise_id)
     }
 
+    /// Only the parent can cancel a scheduled promise, since it is done on behalf of the user
+    /// (see `execute`). The NEAR which was attached to the promise is refunded to the user.
+    pub fn cancel_scheduled(&mut self, nonce: U64) {
+        self.require_parent_caller();
+
+        let promise = self.remove_scheduled(nonce.0);
+        self.refund_to_owner(promise.total_near().as_u128());
+
+        near_sdk::log!("Scheduled promise at nonce {} cancelled", nonce.0);
+    }
+
+    /// Anyone can remove an expired promise, since it can no longer be executed. The NEAR which
+    /// was attached to the promise is refunded to the user.
+    pub fn remove_expired(&mut self, nonce: U64) {
+        match self.expiry_block_heights.get(&nonce.0) {
+            Some(expiry_block_height) if env::block_height() > expiry_block_height => (),
+            _ => env::panic_str("ERR_PROMISE_NOT_EXPIRED"),
+        }
+        let promise = self.remove_scheduled(nonce.0);
+        self.refund_to_owner(promise.total_near().as_u128());
+
+        near_sdk::log!("Expired promise at nonce {} removed", nonce.0);
+    }
+
+    /// Carries on a migration which did not fit in a single call. Anyone can call this, since
+    /// it only brings the storage up to date. Returns true once the migration is complete.
+    pub fn continue_migration(&mut self) -> bool {
+        self.migrate_storage()
+    }
+
+    /// Lists the promises which are scheduled and not executed yet. The list is incomplete
+    /// while a migration from version 1 is in progress (see `continue_migration`).
+    pub fn get_pending_promises(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<PendingPromise> {
+        let from_index = from_index.map_or(0, |x| x.0);
+        let limit = limit.map_or(u64::MAX, |x| x.0);
+        self.pending_nonces
+            .iter()
+            .skip(usize::try_from(from_index).unwrap_or(usize::MAX))
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .map(|nonce| PendingPromise {
+                nonce: nonce.into(),
+                expiry_block_height: self.expiry_block_heights.get(&nonce).map(Into::into),
+            })
+            .collect()
+    }
+
     /// The router will receive wNEAR deposits from its user. This function is to
     /// unwrap that wNEAR into NEAR. Additionally, this function will transfer some
     /// NEAR back to its parent, if needed. This transfer is done because the parent
@@ -165,23 +309,119 @@ impl Router {
 
     #[private]
     pub fn send_refund(&self) -> Promise {
-        let parent = self
-            .parent
-            .get()
-            .unwrap_or_else(|| env::panic_str("ERR_CONTRACT_NOT_INITIALIZED"));
-
-        Promise::new(parent).transfer(REFUND_AMOUNT)
+        Promise::new(self.get_parent()).transfer(REFUND_AMOUNT)
     }
 }
 
 impl Router {
+    fn from_storage(wnear_account: AccountId) -> Self {
+        Self {
+            parent: LazyOption::new(StorageKey::Parent, None),
+            version: LazyOption::new(StorageKey::Version, None),
+            nonce: LazyOption::new(StorageKey::Nonce, None),
+            scheduled_promises: LookupMap::new(StorageKey::Map),
+            wnear_account,
+            expiry_block_heights: LookupMap::new(StorageKey::Expiry),
+            pending_nonces: UnorderedSet::new(StorageKey::Pending),
+        }
+    }
+
+    /// Brings the storage written by the stored version of the router up to date with
+    /// `CURRENT_VERSION`. Each step migrates from one version to the next. A step which goes
+    /// through the scheduled promises only handles `MIGRATE_BATCH_SIZE` of them per call and
+    /// records where to resume; the stored version is updated once it is done.
+    /// Returns true once the storage is up to date.
+    fn migrate_storage(&mut self) -> bool {
+        let stored_version = self.version.get().unwrap_or_default();
+        if stored_version == CURRENT_VERSION {
+            return true;
+        }
+
+        if stored_version < 2 {
+            // Promises scheduled by version 1 are not in the set of pending promises.
+            let mut cursor = LazyOption::<u64>::new(StorageKey::MigrationCursor, None);
+            let start = cursor.get().unwrap_or_default();
+            let end = self.nonce.get().unwrap_or_default();
+            let batch_end = end.min(start.saturating_add(MIGRATE_BATCH_SIZE));
+            for nonce in start..batch_end {
+                if self.scheduled_promises.contains_key(&nonce) {
+                    self.pending_nonces.insert(&nonce);
+                }
+            }
+            if batch_end < end {
+                cursor.set(&batch_end);
+                near_sdk::log!("Migration paused at nonce {}", batch_end);
+                return false;
+            }
+            cursor.remove();
+        }
+
+        self.version.set(&CURRENT_VERSION);
+        true
+    }
+
+    fn get_parent(&self) -> AccountId {
+        self.parent
+            .get()
+            .unwrap_or_else(|| env::panic_str(ERR_NOT_INITIALIZED))
+    }
+
+    fn insert_scheduled(&mut self, promise: &PromiseArgs) -> u64 {
+        let nonce = self.nonce.get().unwrap_or_default();
+        self.scheduled_promises.insert(&nonce, promise);
+        self.pending_nonces.insert(&nonce);
+        self.nonce.set(&(nonce + 1));
+        nonce
+    }
+
+    fn remove_scheduled(&mut self, nonce: u64) -> PromiseArgs {
+        let promise = match self.scheduled_promises.remove(&nonce) {
+            Some(promise) => promise,
+            None => env::panic_str("ERR_PROMISE_NOT_FOUND"),
+        };
+        self.expiry_block_heights.remove(&nonce);
+        self.pending_nonces.remove(&nonce);
+        promise
+    }
+
+    /// Wraps the given amount of NEAR and sends it to the parent, which credits it as wNEAR
+    /// to the address this router belongs to.
+    fn refund_to_owner(&self, amount: u128) {
+        if amount == 0 {
+            return;
+        }
+        let current_account_id = env::current_account_id();
+        let owner_address = current_account_id
+            .as_str()
+            .split('.')
+            .next()
+            .unwrap_or_default();
+        let id = env::promise_create(
+            self.wnear_account.clone(),
+            "near_deposit",
+            b"{}",
+            amount,
+            WNEAR_DEPOSIT_GAS,
+        );
+        let args = format!(
+            r#"{{"receiver_id": "{}", "amount": "{}", "msg": "{}"}}"#,
+            self.get_parent(),
+            amount,
+            owner_address,
+        );
+        env::promise_then(
+            id,
+            self.wnear_account.clone(),
+            "ft_transfer_call",
+            args.as_bytes(),
+            1,
+            WNEAR_TRANSFER_CALL_GAS,
+        );
+    }
+
     fn require_parent_caller(&self) {
         let caller = env::predecessor_account_id();
-        let parent = self
-            .parent
-            .get()
-            .unwrap_or_else(|| env::panic_str("ERR_CONTRACT_NOT_INITIALIZED"));
-        if caller != parent {
+        if caller != self.get_parent() {
             env::panic_str(ERR_ILLEGAL_CALLER)
         }
     }
@@ -190,6 +430,30 @@ impl Router {
         match promise {
             PromiseArgs::Create(call) => Self::base_promise_create(call),
             PromiseArgs::Callback(cb) => Self::cb_promise_create(cb),
+            PromiseArgs::Recursive(p) => Self::recursive_promise_create(p),
+        }
+    }
+
+    fn recursive_promise_create(promise: NearPromise) -> PromiseIndex {
+        match promise {
+            NearPromise::Simple(x) => match x {
+                SimpleNearPromise::Create(call) => Self::base_promise_create(call),
+                SimpleNearPromise::Batch(batch) => Self::batch_promise_create(batch),
+            },
+            NearPromise::Then { base, callback } => {
+                let base_index = Self::recursive_promise_create(*base);
+                match callback {
+                    SimpleNearPromise::Create(call) => Self::base_promise_then(base_index, call),
+                    SimpleNearPromise::Batch(batch) => Self::batch_promise_then(base_index, batch),
+                }
+            }
+            NearPromise::And(promises) => {
+                let indices: Vec<PromiseIndex> = promises
+                    .into_iter()
+                    .map(Self::recursive_promise_create)
+                    .collect();
+                env::promise_and(&indices)
+            }
         }
     }
 
@@ -216,4 +480,117 @@ impl Router {
             promise.attached_gas.as_u64().into(),
         )
     }
+
+    fn base_promise_then(base: PromiseIndex, promise: PromiseCreateArgs) -> PromiseIndex {
+        env::promise_then(
+            base,
+            near_sdk::AccountId::new_unchecked(promise.target_account_id.to_string()),
+            promise.method.as_str(),
+            &promise.args,
+            promise.attached_balance.as_u128(),
+            promise.attached_gas.as_u64().into(),
+        )
+    }
+
+    fn batch_promise_create(promise: PromiseBatchAction) -> PromiseIndex {
+        Self::require_not_takeover(&promise);
+        let id = env::promise_batch_create(&near_sdk::AccountId::new_unchecked(
+            promise.target_account_id.to_string(),
+        ));
+        Self::add_batch_actions(id, promise.actions);
+        id
+    }
+
+    fn batch_promise_then(base: PromiseIndex, promise: PromiseBatchAction) -> PromiseIndex {
+        Self::require_not_takeover(&promise);
+        let id = env::promise_batch_then(
+            base,
+            &near_sdk::AccountId::new_unchecked(promise.target_account_id.to_string()),
+        );
+        Self::add_batch_actions(id, promise.actions);
+        id
+    }
+
+    /// The parent keeps track of the router, so neither of them can be deleted, handed over
+    /// with a full access key or have their code replaced.
+    fn require_not_takeover(promise: &PromiseBatchAction) {
+        let router_id = env::current_account_id();
+        // The router account is always a sub-account of its parent.
+        let parent_id = router_id
+            .as_str()
+            .split_once('.')
+            .map(|(_, parent_id)| parent_id);
+        let target_id: &str = promise.target_account_id.as_ref();
+        if (target_id == router_id.as_str() || Some(target_id) == parent_id)
+            && promise.takes_over_target()
+        {
+            env::panic_str(ERR_ROUTER_TAKEOVER)
+        }
+    }
+
+    fn add_batch_actions(id: PromiseIndex, actions: Vec<PromiseAction>) {
+        for action in actions {
+            match action {
+                PromiseAction::CreateAccount => env::promise_batch_action_create_account(id),
+                PromiseAction::Transfer { amount } => {
+                    env::promise_batch_action_transfer(id, amount.as_u128())
+                }
+                PromiseAction::DeployConotract { code } => {
+                    env::promise_batch_action_deploy_contract(id, &code)
+                }
+                PromiseAction::FunctionCall {
+                    name,
+                    args,
+                    attached_yocto,
+                    gas,
+                } => env::promise_batch_action_function_call(
+                    id,
+                    &name,
+                    &args,
+                    attached_yocto.as_u128(),
+                    gas.as_u64().into(),
+                ),
+                PromiseAction::Stake { amount, public_key } => env::promise_batch_action_stake(
+                    id,
+                    amount.as_u128(),
+                    &to_near_public_key(&public_key),
+                ),
+                PromiseAction::AddFullAccessKey { public_key, nonce } => {
+                    env::promise_batch_action_add_key_with_full_access(
+                        id,
+                        &to_near_public_key(&public_key),
+                        nonce,
+                    )
+                }
+                PromiseAction::AddFunctionCallKey {
+                    public_key,
+                    nonce,
+                    allowance,
+                    receiver_id,
+                    function_names,
+                } => env::promise_batch_action_add_key_with_function_call(
+                    id,
+                    &to_near_public_key(&public_key),
+                    nonce,
+                    allowance.as_u128(),
+                    &near_sdk::AccountId::new_unchecked(receiver_id.to_string()),
+                    &function_names,
+                ),
+                PromiseAction::DeleteKey { public_key } => {
+                    env::promise_batch_action_delete_key(id, &to_near_public_key(&public_key))
+                }
+                PromiseAction::DeleteAccount { beneficiary_id } => {
+                    env::promise_batch_action_delete_account(
+                        id,
+                        &near_sdk::AccountId::new_unchecked(beneficiary_id.to_string()),
+                    )
+                }
+            }
+        }
+    }
+}
+
+fn to_near_public_key(public_key: &NearPublicKey) -> near_sdk::PublicKey {
+    near_sdk::PublicKey::try_from(public_key.to_bytes())
+        .unwrap_or_else(|_| env::panic_str("ERR_INVALID_PUBLIC_KEY"))
 }
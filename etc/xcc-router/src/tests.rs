@@ -1,12 +1,18 @@
-use super::Router;
-use aurora_engine_types::parameters::{PromiseArgs, PromiseCreateArgs, PromiseWithCallbackArgs};
+use super::{PendingPromise, Router, CURRENT_VERSION, MIGRATE_BATCH_SIZE};
+use aurora_engine_types::parameters::{
+    EvmCallbackArgs, ExecuteWithCallbackArgs, NearPromise, NearPublicKey, PromiseAction,
+    PromiseArgs, PromiseBatchAction, PromiseCreateArgs, PromiseWithCallbackArgs,
+    ScheduleWithExpiryArgs, SimpleNearPromise,
+};
 use aurora_engine_types::types::{NearGas, Yocto};
+use near_sdk::borsh::BorshSerialize;
 use near_sdk::mock::VmAction;
 use near_sdk::test_utils::test_env::{alice, bob, carol};
 use near_sdk::test_utils::{self, VMContextBuilder};
 use near_sdk::testing_env;
 
 const WNEAR_ACCOUNT: &str = "wrap.near";
+const OWNER_ADDRESS: &str = "some_address";
 
 #[test]
 fn test_initialize() {
@@ -119,6 +125,247 @@ fn test_execute_callback() {
     validate_function_call_action(&callback.actions, promise.callback);
 }
 
+#[test]
+fn test_execute_batch() {
+    let (_parent, contract) = create_contract();
+
+    let public_key = NearPublicKey::Ed25519([7; 32]);
+    let promise = PromiseBatchAction {
+        target_account_id: bob().as_str().parse().unwrap(),
+        actions: vec![
+            PromiseAction::Stake {
+                amount: Yocto::new(1000),
+                public_key: public_key.clone(),
+            },
+            PromiseAction::AddFullAccessKey {
+                public_key: public_key.clone(),
+                nonce: 0,
+            },
+            PromiseAction::AddFunctionCallKey {
+                public_key: NearPublicKey::Secp256k1([8; 64]),
+                nonce: 1,
+                allowance: Yocto::new(0),
+                receiver_id: bob().as_str().parse().unwrap(),
+                function_names: "some_method,another_method".into(),
+            },
+            PromiseAction::DeleteKey { public_key },
+        ],
+    };
+
+    contract.execute(PromiseArgs::Recursive(NearPromise::Simple(
+        SimpleNearPromise::Batch(promise),
+    )));
+
+    let receipts = test_utils::get_created_receipts();
+    assert_eq!(receipts.len(), 1);
+    let receipt = &receipts[0];
+    assert_eq!(receipt.receiver_id, bob());
+    assert_eq!(receipt.actions.len(), 4);
+    assert!(matches!(
+        receipt.actions[0],
+        VmAction::Stake { stake: 1000, .. }
+    ));
+    assert!(matches!(
+        receipt.actions[1],
+        VmAction::AddKeyWithFullAccess { nonce: 0, .. }
+    ));
+    assert!(matches!(
+        receipt.actions[2],
+        VmAction::AddKeyWithFunctionCall { nonce: 1, .. }
+    ));
+    assert!(matches!(receipt.actions[3], VmAction::DeleteKey { .. }));
+}
+
+// The router cannot be handed over with a full access key.
+#[test]
+#[should_panic]
+fn test_execute_batch_add_full_access_key_to_router() {
+    let (parent, contract) = create_contract();
+
+    let promise = PromiseBatchAction {
+        target_account_id: format!("{}.{}", OWNER_ADDRESS, parent).parse().unwrap(),
+        actions: vec![PromiseAction::AddFullAccessKey {
+            public_key: NearPublicKey::Ed25519([7; 32]),
+            nonce: 0,
+        }],
+    };
+
+    contract.execute(PromiseArgs::Recursive(NearPromise::Simple(
+        SimpleNearPromise::Batch(promise),
+    )));
+}
+
+// The router cannot be deleted, even after another promise.
+#[test]
+#[should_panic]
+fn test_execute_batch_delete_router() {
+    let (parent, contract) = create_contract();
+
+    let promise = PromiseBatchAction {
+        target_account_id: format!("{}.{}", OWNER_ADDRESS, parent).parse().unwrap(),
+        actions: vec![PromiseAction::DeleteAccount {
+            beneficiary_id: bob().as_str().parse().unwrap(),
+        }],
+    };
+
+    contract.execute(PromiseArgs::Recursive(NearPromise::Then {
+        base: Box::new(NearPromise::Simple(SimpleNearPromise::Create(
+            create_promise(),
+        ))),
+        callback: SimpleNearPromise::Batch(promise),
+    }));
+}
+
+// The code of the router cannot be replaced.
+#[test]
+#[should_panic]
+fn test_execute_batch_deploy_to_router() {
+    let (parent, contract) = create_contract();
+
+    let promise = PromiseBatchAction {
+        target_account_id: format!("{}.{}", OWNER_ADDRESS, parent).parse().unwrap(),
+        actions: vec![PromiseAction::DeployConotract {
+            code: b"some_code".to_vec(),
+        }],
+    };
+
+    contract.execute(PromiseArgs::Recursive(NearPromise::Simple(
+        SimpleNearPromise::Batch(promise),
+    )));
+}
+
+// Nor can the code of its parent.
+#[test]
+#[should_panic]
+fn test_execute_batch_deploy_to_parent() {
+    let (parent, contract) = create_contract();
+
+    let promise = PromiseBatchAction {
+        target_account_id: parent.as_str().parse().unwrap(),
+        actions: vec![PromiseAction::DeployConotract {
+            code: b"some_code".to_vec(),
+        }],
+    };
+
+    contract.execute(PromiseArgs::Recursive(NearPromise::Simple(
+        SimpleNearPromise::Batch(promise),
+    )));
+}
+
+#[test]
+fn test_execute_recursive() {
+    let (_parent, contract) = create_contract();
+
+    let call = |account: near_sdk::AccountId, method: &str| PromiseCreateArgs {
+        target_account_id: account.as_str().parse().unwrap(),
+        method: method.into(),
+        args: b"hello_world".to_vec(),
+        attached_balance: Yocto::new(1),
+        attached_gas: NearGas::new(10_000_000_000_000),
+    };
+    let first = call(bob(), "first_method");
+    let second = call(carol(), "second_method");
+    let callback = PromiseBatchAction {
+        target_account_id: bob().as_str().parse().unwrap(),
+        actions: vec![
+            PromiseAction::Transfer {
+                amount: Yocto::new(42),
+            },
+            PromiseAction::FunctionCall {
+                name: "callback_method".into(),
+                args: Vec::new(),
+                attached_yocto: Yocto::new(0),
+                gas: NearGas::new(5_000_000_000_000),
+            },
+        ],
+    };
+    let promise = NearPromise::Then {
+        base: Box::new(NearPromise::And(vec![
+            NearPromise::Simple(SimpleNearPromise::Create(first.clone())),
+            NearPromise::Simple(SimpleNearPromise::Create(second.clone())),
+        ])),
+        callback: SimpleNearPromise::Batch(callback),
+    };
+    let args = PromiseArgs::Recursive(promise);
+    assert_eq!(args.total_gas(), NearGas::new(25_000_000_000_000));
+    assert_eq!(args.total_near(), Yocto::new(44));
+
+    contract.execute(args);
+
+    let receipts = test_utils::get_created_receipts();
+    assert_eq!(receipts.len(), 3);
+    validate_function_call_action(&receipts[0].actions, first);
+    validate_function_call_action(&receipts[1].actions, second);
+    let callback = &receipts[2];
+    assert_eq!(callback.receiver_id, bob());
+    assert_eq!(callback.actions[0], VmAction::Transfer { deposit: 42 });
+}
+
+#[test]
+fn test_execute_with_callback() {
+    let (parent, contract) = create_contract();
+
+    let promise = PromiseCreateArgs {
+        target_account_id: bob().as_str().parse().unwrap(),
+        method: "some_method".into(),
+        args: b"hello_world".to_vec(),
+        attached_balance: Yocto::new(56),
+        attached_gas: NearGas::new(100_000_000_000_000),
+    };
+    let callback = EvmCallbackArgs {
+        selector: [0x12, 0x34, 0x56, 0x78],
+        gas: NearGas::new(50_000_000_000_000),
+    };
+
+    contract.execute_with_callback(ExecuteWithCallbackArgs {
+        promise: PromiseArgs::Create(promise.clone()),
+        callback,
+    });
+
+    let receipts = test_utils::get_created_receipts();
+    assert_eq!(receipts.len(), 2);
+    let base = &receipts[0];
+    let engine_callback = &receipts[1];
+
+    validate_function_call_action(&base.actions, promise);
+    assert_eq!(engine_callback.receiver_id, parent);
+    validate_function_call_action(
+        &engine_callback.actions,
+        PromiseCreateArgs {
+            target_account_id: parent.as_str().parse().unwrap(),
+            method: "xcc_callback".into(),
+            args: callback.try_to_vec().unwrap(),
+            attached_balance: Yocto::new(0),
+            attached_gas: callback.gas,
+        },
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_execute_with_callback_wrong_caller() {
+    let (_parent, contract) = create_contract();
+
+    let promise = PromiseCreateArgs {
+        target_account_id: bob().as_str().parse().unwrap(),
+        method: "some_method".into(),
+        args: b"hello_world".to_vec(),
+        attached_balance: Yocto::new(56),
+        attached_gas: NearGas::new(100_000_000_000_000),
+    };
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(bob())
+        .build());
+    contract.execute_with_callback(ExecuteWithCallbackArgs {
+        promise: PromiseArgs::Create(promise),
+        callback: EvmCallbackArgs {
+            selector: [0x12, 0x34, 0x56, 0x78],
+            gas: NearGas::new(50_000_000_000_000),
+        },
+    });
+}
+
 #[test]
 #[should_panic]
 fn test_schedule_wrong_caller() {
@@ -184,6 +431,220 @@ fn test_schedule_and_execute() {
     validate_function_call_action(&receipt.actions, promise);
 }
 
+#[test]
+fn test_schedule_with_expiry() {
+    let (parent, mut contract) = create_contract();
+
+    let promise = create_promise();
+    contract.schedule(PromiseArgs::Create(promise.clone()));
+    contract.schedule_with_expiry(ScheduleWithExpiryArgs {
+        promise: PromiseArgs::Create(promise.clone()),
+        expiry_block_height: 100,
+    });
+
+    assert_eq!(
+        contract.get_pending_promises(None, None),
+        vec![
+            PendingPromise {
+                nonce: 0.into(),
+                expiry_block_height: None,
+            },
+            PendingPromise {
+                nonce: 1.into(),
+                expiry_block_height: Some(100.into()),
+            },
+        ]
+    );
+    assert_eq!(
+        contract
+            .get_pending_promises(Some(1.into()), Some(1.into()))
+            .len(),
+        1
+    );
+
+    // Not expired yet, so it cannot be removed
+    set_context(&parent, bob(), 100);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        contract.remove_expired(1.into())
+    }));
+    assert!(result.is_err());
+
+    // Once expired anyone can remove it, and its NEAR is refunded to the owner
+    set_context(&parent, bob(), 101);
+    contract.remove_expired(1.into());
+    assert_eq!(contract.get_pending_promises(None, None).len(), 1);
+    assert!(!contract.scheduled_promises.contains_key(&1));
+    validate_refund(&parent, promise.attached_balance.as_u128());
+}
+
+#[test]
+#[should_panic]
+fn test_execute_expired() {
+    let (parent, mut contract) = create_contract();
+
+    contract.schedule_with_expiry(ScheduleWithExpiryArgs {
+        promise: PromiseArgs::Create(create_promise()),
+        expiry_block_height: 100,
+    });
+
+    set_context(&parent, bob(), 101);
+    contract.execute_scheduled(0.into());
+}
+
+#[test]
+fn test_cancel_scheduled() {
+    let (parent, mut contract) = create_contract();
+
+    let promise = create_promise();
+    contract.schedule(PromiseArgs::Create(promise.clone()));
+    contract.cancel_scheduled(0.into());
+
+    assert!(contract.get_pending_promises(None, None).is_empty());
+    assert!(!contract.scheduled_promises.contains_key(&0));
+    validate_refund(&parent, promise.attached_balance.as_u128());
+}
+
+#[test]
+#[should_panic]
+fn test_cancel_scheduled_wrong_caller() {
+    let (parent, mut contract) = create_contract();
+
+    contract.schedule(PromiseArgs::Create(create_promise()));
+
+    set_context(&parent, bob(), 0);
+    contract.cancel_scheduled(0.into());
+}
+
+#[test]
+fn test_migrate() {
+    let (parent, mut contract) = create_contract();
+
+    // A promise scheduled by version 1 of the router, which did not list pending promises
+    let promise = PromiseArgs::Create(create_promise());
+    contract.scheduled_promises.insert(&0, &promise);
+    contract.nonce.set(&1);
+    contract.version.set(&1);
+    near_sdk::env::state_write(&contract);
+    drop(contract);
+
+    set_context(&parent, parent.clone(), 0);
+    let contract = Router::migrate();
+
+    assert_eq!(contract.version.get().unwrap(), CURRENT_VERSION);
+    assert_eq!(contract.wnear_account.as_str(), WNEAR_ACCOUNT);
+    assert_eq!(contract.scheduled_promises.get(&0).unwrap(), promise);
+    assert_eq!(
+        contract.get_pending_promises(None, None),
+        vec![PendingPromise {
+            nonce: 0.into(),
+            expiry_block_height: None,
+        }]
+    );
+}
+
+#[test]
+fn test_migrate_in_batches() {
+    let (parent, mut contract) = create_contract();
+
+    // More promises than a single migration step goes through, some of them already executed
+    let promise = PromiseArgs::Create(create_promise());
+    let nonce = 2 * MIGRATE_BATCH_SIZE + 1;
+    for i in (0..nonce).filter(|i| i % 3 != 0) {
+        contract.scheduled_promises.insert(&i, &promise);
+    }
+    contract.nonce.set(&nonce);
+    contract.version.set(&1);
+    near_sdk::env::state_write(&contract);
+    drop(contract);
+
+    set_context(&parent, parent.clone(), 0);
+    let contract = Router::migrate();
+    assert_eq!(contract.version.get().unwrap(), 1);
+    near_sdk::env::state_write(&contract);
+    drop(contract);
+
+    // A migration in progress can be resumed by `migrate` as well as `continue_migration`
+    let mut contract = Router::migrate();
+    assert_eq!(contract.version.get().unwrap(), 1);
+    set_context(&parent, bob(), 0);
+    assert!(contract.continue_migration());
+    assert_eq!(contract.version.get().unwrap(), CURRENT_VERSION);
+    assert!(contract.continue_migration());
+
+    let mut pending: Vec<u64> = contract
+        .get_pending_promises(None, None)
+        .into_iter()
+        .map(|p| p.nonce.0)
+        .collect();
+    pending.sort_unstable();
+    assert_eq!(
+        pending,
+        (0..nonce).filter(|i| i % 3 != 0).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_migrate_wrong_caller() {
+    let (parent, contract) = create_contract();
+
+    near_sdk::env::state_write(&contract);
+    drop(contract);
+
+    set_context(&parent, bob(), 0);
+    let _contract = Router::migrate();
+}
+
+fn create_promise() -> PromiseCreateArgs {
+    PromiseCreateArgs {
+        target_account_id: bob().as_str().parse().unwrap(),
+        method: "some_method".into(),
+        args: b"hello_world".to_vec(),
+        attached_balance: Yocto::new(56),
+        attached_gas: NearGas::new(100_000_000_000_000),
+    }
+}
+
+fn set_context(parent: &near_sdk::AccountId, predecessor: near_sdk::AccountId, block_height: u64) {
+    testing_env!(VMContextBuilder::new()
+        .current_account_id(format!("{}.{}", OWNER_ADDRESS, parent).try_into().unwrap())
+        .predecessor_account_id(predecessor)
+        .block_index(block_height)
+        .build());
+}
+
+/// Checks the refund wraps the NEAR and sends it to the parent for the owner of the router.
+fn validate_refund(parent: &near_sdk::AccountId, amount: u128) {
+    let receipts = test_utils::get_created_receipts();
+    assert_eq!(receipts.len(), 2);
+    assert_eq!(receipts[0].receiver_id.as_str(), WNEAR_ACCOUNT);
+    assert_eq!(receipts[1].receiver_id.as_str(), WNEAR_ACCOUNT);
+    validate_function_call_action(
+        &receipts[0].actions,
+        PromiseCreateArgs {
+            target_account_id: WNEAR_ACCOUNT.parse().unwrap(),
+            method: "near_deposit".into(),
+            args: b"{}".to_vec(),
+            attached_balance: Yocto::new(amount),
+            attached_gas: NearGas::new(5_000_000_000_000),
+        },
+    );
+    let args = format!(
+        r#"{{"receiver_id": "{}", "amount": "{}", "msg": "{}"}}"#,
+        parent, amount, OWNER_ADDRESS,
+    );
+    validate_function_call_action(
+        &receipts[1].actions,
+        PromiseCreateArgs {
+            target_account_id: WNEAR_ACCOUNT.parse().unwrap(),
+            method: "ft_transfer_call".into(),
+            args: args.into_bytes(),
+            attached_balance: Yocto::new(1),
+            attached_gas: NearGas::new(70_000_000_000_000),
+        },
+    );
+}
+
 fn validate_function_call_action(actions: &[VmAction], promise: PromiseCreateArgs) {
     assert_eq!(actions.len(), 1);
     let action = &actions[0];
@@ -202,7 +663,7 @@ fn validate_function_call_action(actions: &[VmAction], promise: PromiseCreateArg
 fn create_contract() -> (near_sdk::AccountId, Router) {
     let parent = alice();
     testing_env!(VMContextBuilder::new()
-        .current_account_id(format!("some_address.{}", parent).try_into().unwrap())
+        .current_account_id(format!("{}.{}", OWNER_ADDRESS, parent).try_into().unwrap())
         .predecessor_account_id(parent.clone())
         .build());
     let contract = Router::initialize(WNEAR_ACCOUNT.parse().unwrap(), false);